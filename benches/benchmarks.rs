@@ -32,10 +32,7 @@ fn index_list_insert_benchmark(c: &mut Criterion) {
     }
 
     for elem in between.sample_iter(&mut rng).take(iterations) {
-        let mut vector = Vec::with_capacity(size);
-        for _ in 0..size {
-            vector.push(0);
-        }
+        let mut vector = vec![0; size];
 
         group_insert.bench_with_input(
             BenchmarkId::from_parameter(format!("Vector-{}-{:?}", elem, Instant::now())),
@@ -78,10 +75,7 @@ fn index_list_remove_benchmark(c: &mut Criterion) {
     }
 
     for elem in between.sample_iter(&mut rng).take(iterations) {
-        let mut vector = Vec::with_capacity(size);
-        for _ in 0..size {
-            vector.push(0);
-        }
+        let mut vector = vec![0; size];
 
         group_remove.bench_with_input(
             BenchmarkId::from_parameter(format!("Vector-{}-{:?}", elem, Instant::now())),
@@ -0,0 +1,96 @@
+//! The vector the entries of a list are stored in.
+//!
+//! With the `allocator-api2` feature this is the `Vec` of that crate, which allocates from
+//! any [`Allocator`]. Without it a standard `Vec` stands in, and the allocator parameter of
+//! the list types can only be the global allocator.
+
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::{Allocator, Global};
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::vec::Vec;
+
+#[cfg(not(feature = "allocator-api2"))]
+pub use self::global::{Allocator, Global, Vec};
+
+#[cfg(not(feature = "allocator-api2"))]
+mod global {
+    use std::ops::{Deref, DerefMut};
+
+    /// Stands in for the `Allocator` trait of `allocator-api2`. Only [`Global`] implements
+    /// it.
+    pub trait Allocator {}
+
+    /// The global allocator.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Global;
+
+    impl Allocator for Global {}
+
+    /// A standard `Vec` that carries the allocator it would have been allocated from.
+    #[derive(Debug)]
+    pub struct Vec<T, A: Allocator = Global> {
+        items: std::vec::Vec<T>,
+        alloc: A,
+    }
+
+    impl<T> Vec<T> {
+        pub fn with_capacity(capacity: usize) -> Self {
+            Self::with_capacity_in(capacity, Global)
+        }
+    }
+
+    impl<T, A: Allocator> Vec<T, A> {
+        pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+            Vec {
+                items: std::vec::Vec::with_capacity(capacity),
+                alloc,
+            }
+        }
+
+        pub fn allocator(&self) -> &A {
+            &self.alloc
+        }
+
+        pub fn reserve(&mut self, additional: usize) {
+            self.items.reserve(additional);
+        }
+    }
+
+    impl<T, A: Allocator> Deref for Vec<T, A> {
+        type Target = std::vec::Vec<T>;
+
+        fn deref(&self) -> &Self::Target {
+            &self.items
+        }
+    }
+
+    impl<T, A: Allocator> DerefMut for Vec<T, A> {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.items
+        }
+    }
+
+    impl<T: PartialEq, A: Allocator> PartialEq for Vec<T, A> {
+        fn eq(&self, other: &Self) -> bool {
+            self.items == other.items
+        }
+    }
+
+    impl<T, A: Allocator> IntoIterator for Vec<T, A> {
+        type Item = T;
+        type IntoIter = std::vec::IntoIter<T>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.items.into_iter()
+        }
+    }
+
+    impl<T, const N: usize> From<[T; N]> for Vec<T> {
+        fn from(items: [T; N]) -> Self {
+            Vec {
+                items: items.into(),
+                alloc: Global,
+            }
+        }
+    }
+}
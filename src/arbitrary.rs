@@ -0,0 +1,70 @@
+//! Support for generating `IndexList`s with the `arbitrary` crate.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::IndexList;
+
+/// Builds the list by replaying a random history of insertions and removals, so that fuzzers
+/// see free slots, reused slots and mixed generations, not only freshly pushed lists.
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for IndexList<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut list = IndexList::new();
+        let mut indices = Vec::new();
+        while u.arbitrary()? {
+            let op = if indices.is_empty() {
+                u.int_in_range(0..=1)?
+            } else {
+                u.int_in_range(0..=4)?
+            };
+            match op {
+                0 => indices.push(list.push_back(u.arbitrary()?)),
+                1 => indices.push(list.push_front(u.arbitrary()?)),
+                2 | 3 => {
+                    let at = indices[u.choose_index(indices.len())?];
+                    let item = u.arbitrary()?;
+                    let index = if op == 2 {
+                        list.insert_before(at, item)
+                    } else {
+                        list.insert_after(at, item)
+                    };
+                    indices.push(index.expect("Corrupted list"));
+                }
+                _ => {
+                    let at = indices.swap_remove(u.choose_index(indices.len())?);
+                    list.remove(at);
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arbitrary_lists_are_valid() {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut saw_reused_slots = false;
+        for _ in 0..64 {
+            let bytes: Vec<u8> = (0..512)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    seed as u8
+                })
+                .collect();
+            let list = IndexList::<u8>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            assert_eq!(list.validate(), Ok(()));
+            assert_eq!(list.iter().count(), list.len());
+            saw_reused_slots |= list.generation > 0 && list.len() > 1;
+        }
+        assert!(saw_reused_slots);
+    }
+}
@@ -0,0 +1,307 @@
+//! An `IndexList` that can be shared between threads.
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::{Index, IndexList};
+
+/// `SyncIndexList` is an [`IndexList`] behind a lock, so that it can be shared between
+/// threads, for example behind an `Arc`, and used through `&self`.
+///
+/// Every method takes the lock for the duration of a single operation on the underlying
+/// list, so indices keep their generational guarantees: an index handed to another thread
+/// either still refers to the element it was returned for, or is rejected. Use
+/// [`SyncIndexList::with`] to run several operations without another thread interleaving.
+///
+/// A panic while the lock is held does not make the list unusable, the next caller simply
+/// gets the list as the panicking thread left it.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use indexlist::concurrent::SyncIndexList;
+///
+/// let list = Arc::new(SyncIndexList::new());
+/// let producer = {
+///     let list = Arc::clone(&list);
+///     thread::spawn(move || {
+///         for item in 0..10 {
+///             list.push_back(item);
+///         }
+///     })
+/// };
+/// producer.join().unwrap();
+///
+/// let mut received = Vec::new();
+/// while let Some(item) = list.pop_front() {
+///     received.push(item);
+/// }
+/// assert_eq!(received, (0..10).collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Default)]
+pub struct SyncIndexList<T> {
+    list: Mutex<IndexList<T>>,
+}
+
+impl<T> From<IndexList<T>> for SyncIndexList<T> {
+    fn from(list: IndexList<T>) -> Self {
+        SyncIndexList {
+            list: Mutex::new(list),
+        }
+    }
+}
+
+impl<T> SyncIndexList<T> {
+    /// Creates a new, empty `SyncIndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::concurrent::SyncIndexList;
+    /// let list: SyncIndexList<i32> = SyncIndexList::new();
+    /// ```
+    pub fn new() -> Self {
+        IndexList::new().into()
+    }
+
+    /// Creates a new, empty `SyncIndexList` with the specified capacity.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::concurrent::SyncIndexList;
+    /// let list: SyncIndexList<i32> = SyncIndexList::with_capacity(10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexList::with_capacity(capacity).into()
+    }
+
+    /// Consumes the `SyncIndexList` and returns the underlying `IndexList`. All indices
+    /// remain valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// let five = list.push_back(5);
+    /// let list = list.into_inner();
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn into_inner(self) -> IndexList<T> {
+        self.list
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, IndexList<T>> {
+        self.list.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the list and runs `f` on it, no other thread can access the list until `f`
+    /// returns.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// let one = list.push_back(1);
+    /// list.with(|list| list.insert_after(one, 2));
+    /// assert_eq!(list.with(|list| list.iter().copied().collect::<Vec<_>>()), vec![1, 2]);
+    /// ```
+    pub fn with<R>(&self, f: impl FnOnce(&mut IndexList<T>) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the list contains no elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list: indexlist::concurrent::SyncIndexList<i32> = Default::default();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns `true` if the index refers to an element of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// let five = list.push_back(5);
+    /// assert!(list.contains_index(five));
+    /// list.remove(five);
+    /// assert!(!list.contains_index(five));
+    /// ```
+    pub fn contains_index(&self, index: Index<T>) -> bool {
+        self.lock().contains_index(index)
+    }
+
+    /// Returns a clone of the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.get_cloned(five), Some(5));
+    /// ```
+    pub fn get_cloned(&self, index: Index<T>) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.lock().get(index).cloned()
+    }
+
+    /// Adds an element to the back of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.get_cloned(five), Some(5));
+    /// ```
+    pub fn push_back(&self, item: T) -> Index<T> {
+        self.lock().push_back(item)
+    }
+
+    /// Adds an element to the front of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// list.push_back(5);
+    /// list.push_front(4);
+    /// assert_eq!(list.pop_front(), Some(4));
+    /// ```
+    pub fn push_front(&self, item: T) -> Index<T> {
+        self.lock().push_front(item)
+    }
+
+    /// Removes the first element of the list and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.pop_front(), Some(5));
+    /// assert_eq!(list.pop_front(), None);
+    /// ```
+    pub fn pop_front(&self) -> Option<T> {
+        self.lock().pop_front()
+    }
+
+    /// Removes the last element of the list and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// assert_eq!(list.pop_back(), Some(6));
+    /// ```
+    pub fn pop_back(&self) -> Option<T> {
+        self.lock().pop_back()
+    }
+
+    /// Removes the element at the given index and returns it, if it still exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::concurrent::SyncIndexList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.remove(five), Some(5));
+    /// assert_eq!(list.remove(five), None);
+    /// ```
+    pub fn remove(&self, index: Index<T>) -> Option<T> {
+        self.lock().remove(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn producers_and_consumers_see_every_element_once() {
+        let list = Arc::new(SyncIndexList::new());
+        let producers: Vec<_> = (0..4)
+            .map(|producer| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    for item in 0..250 {
+                        list.push_back(producer * 1000 + item);
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    let mut received = Vec::new();
+                    while received.len() < 250 {
+                        if let Some(item) = list.pop_front() {
+                            received.push(item);
+                        } else {
+                            thread::yield_now();
+                        }
+                    }
+                    received
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut received: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+        received.sort_unstable();
+        let mut expected: Vec<_> = (0..4)
+            .flat_map(|producer| (0..250).map(move |item| producer * 1000 + item))
+            .collect();
+        expected.sort_unstable();
+        assert_eq!(received, expected);
+        assert!(list.is_empty());
+        assert_eq!(list.with(|list| list.validate()), Ok(()));
+    }
+
+    #[test]
+    fn stale_indices_are_rejected_across_threads() {
+        let list = Arc::new(SyncIndexList::new());
+        let indexes: Vec<_> = (0..100).map(|item| list.push_back(item)).collect();
+        let removed = {
+            let list = Arc::clone(&list);
+            let indexes = indexes.clone();
+            thread::spawn(move || {
+                indexes
+                    .iter()
+                    .step_by(2)
+                    .filter_map(|&index| list.remove(index))
+                    .count()
+            })
+        };
+        assert_eq!(removed.join().unwrap(), 50);
+        for item in 100..150 {
+            list.push_back(item);
+        }
+        for (item, &index) in indexes.iter().enumerate() {
+            let expected = (item % 2 == 1).then_some(item);
+            assert_eq!(list.get_cloned(index), expected);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Error types returned by the fallible `try_*` methods of `IndexList`.
+
+use std::error::Error;
+use std::fmt;
+
+/// The reason an [`Index`](crate::Index) could not be used to access an element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessError {
+    /// The element the index referred to was removed and its slot is currently free.
+    FreedSlot,
+    /// The element the index referred to was removed and its slot reused by a newer element.
+    StaleGeneration,
+    /// The index was not created by this list.
+    ///
+    /// Indices from other lists are always detected with the `list-id` feature. Without it,
+    /// they are only detected if they point past the end of this list's storage.
+    ForeignIndex,
+    /// The element was taken out of the order with
+    /// [`IndexList::detach`](crate::IndexList::detach), so it has no neighbors to insert
+    /// next to.
+    Detached,
+    /// The links around the element are inconsistent, the list is corrupted.
+    CorruptedLinks,
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            AccessError::FreedSlot => "the element was removed",
+            AccessError::StaleGeneration => "the element was removed and its slot reused",
+            AccessError::ForeignIndex => "the index belongs to a different list",
+            AccessError::Detached => "the element is detached from the list",
+            AccessError::CorruptedLinks => "the list is corrupted",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for AccessError {}
+
+/// The error returned when an element could not be inserted relative to an index.
+///
+/// It gives the element that was not inserted back to the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryInsertError<T> {
+    kind: AccessError,
+    item: T,
+}
+
+impl<T> TryInsertError<T> {
+    pub(crate) fn new(kind: AccessError, item: T) -> Self {
+        TryInsertError { kind, item }
+    }
+
+    /// Returns the reason the element could not be inserted.
+    pub fn kind(&self) -> AccessError {
+        self.kind
+    }
+
+    /// Returns the element that could not be inserted.
+    pub fn into_item(self) -> T {
+        self.item
+    }
+}
+
+impl<T> fmt::Display for TryInsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "could not insert the element: {}", self.kind)
+    }
+}
+
+impl<T: fmt::Debug> Error for TryInsertError<T> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+/// The invariant of an `IndexList` that [`IndexList::validate`](crate::IndexList::validate)
+/// found to be broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InvariantError {
+    /// The stored length does not match the number of occupied slots.
+    CountMismatch {
+        /// The length stored in the list.
+        stored: usize,
+        /// The number of occupied slots.
+        occupied: usize,
+    },
+    /// The free list visits an occupied slot, visits a slot twice, or misses a free slot, or
+    /// free slots are kept in both the free list and the ordered set of a reuse policy.
+    BrokenFreeList,
+    /// A link points to a free slot or past the end of the storage.
+    LinkToFreeSlot {
+        /// The slot the link points to.
+        slot: usize,
+    },
+    /// The `prev` link of an element does not point back to the element before it.
+    BrokenLink {
+        /// The slot of the element.
+        slot: usize,
+    },
+    /// Following the links from the head does not visit every element exactly once and end
+    /// at the tail.
+    BrokenChain,
+    /// An element has a newer generation than the list itself.
+    FutureGeneration {
+        /// The slot of the element.
+        slot: usize,
+    },
+    /// A generation is too large for the index type of the list, see
+    /// [`IndexType`](crate::IndexType).
+    GenerationOutOfRange,
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::CountMismatch { stored, occupied } => write!(
+                f,
+                "the list stores a length of {stored} but has {occupied} occupied slots"
+            ),
+            InvariantError::BrokenFreeList => f.write_str("the free list is broken"),
+            InvariantError::LinkToFreeSlot { slot } => {
+                write!(f, "a link points to slot {slot}, which is not occupied")
+            }
+            InvariantError::BrokenLink { slot } => {
+                write!(f, "the element in slot {slot} does not link back")
+            }
+            InvariantError::BrokenChain => {
+                f.write_str("the elements do not form a chain from head to tail")
+            }
+            InvariantError::FutureGeneration { slot } => {
+                write!(
+                    f,
+                    "the element in slot {slot} has a newer generation than the list"
+                )
+            }
+            InvariantError::GenerationOutOfRange => {
+                f.write_str("a generation is too large for the index type")
+            }
+        }
+    }
+}
+
+impl Error for InvariantError {}
@@ -0,0 +1,275 @@
+//! A C interface to `IndexList`, for using it from C or other languages with a C FFI.
+//!
+//! The list is handed out as an opaque pointer created by [`indexlist_new`] and destroyed by
+//! [`indexlist_free`]. Its elements are untyped pointers owned by the caller, the list never
+//! dereferences or frees them. Indices cross the boundary as a [`RawIndex`], which is checked
+//! against the list on every use, so stale or garbage indices are rejected instead of
+//! reaching the wrong element.
+//!
+//! The crate is built as a Rust library; to link it into a C program, build it as a static
+//! or dynamic library, for example with
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+
+// The functions in this module take raw pointers from C, which cannot be done without
+// `unsafe`. Every pointer is only dereferenced after a null check.
+#![allow(unsafe_code)]
+
+use std::ffi::c_void;
+
+use crate::{Index, IndexList};
+
+/// An `IndexList` of untyped pointers, as seen from C.
+pub struct FfiIndexList(IndexList<*mut c_void>);
+
+/// An [`Index`] split into plain integers, see [`Index::into_raw`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawIndex {
+    /// The slot of the element.
+    pub slot: usize,
+    /// The generation of the element.
+    pub generation: usize,
+}
+
+impl RawIndex {
+    fn from_index(index: Index<*mut c_void>) -> Self {
+        let (slot, generation) = index.into_raw();
+        RawIndex { slot, generation }
+    }
+}
+
+/// Writes `index` to `out`. Returns `false` if either is missing.
+unsafe fn write_index(out: *mut RawIndex, index: Option<Index<*mut c_void>>) -> bool {
+    match (index, out.is_null()) {
+        (Some(index), false) => {
+            *out = RawIndex::from_index(index);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Turns `index` back into an index of `list`, if it refers to one of its elements.
+unsafe fn resolve(list: *const FfiIndexList, index: RawIndex) -> Option<Index<*mut c_void>> {
+    let list = list.as_ref()?;
+    list.0.index_from_raw(index.slot, index.generation)
+}
+
+/// Creates a new, empty list. It must be destroyed with [`indexlist_free`].
+#[no_mangle]
+pub extern "C" fn indexlist_new() -> *mut FfiIndexList {
+    Box::into_raw(Box::new(FfiIndexList(IndexList::new())))
+}
+
+/// Destroys a list created by [`indexlist_new`]. The elements are not freed.
+///
+/// # Safety
+///
+/// `list` must be null or a list created by [`indexlist_new`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_free(list: *mut FfiIndexList) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+/// Returns the number of elements in the list, or 0 if `list` is null.
+///
+/// # Safety
+///
+/// `list` must be null or a live list created by [`indexlist_new`].
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_len(list: *const FfiIndexList) -> usize {
+    list.as_ref().map_or(0, |list| list.0.len())
+}
+
+/// Adds `item` to the back of the list and writes its index to `out`. Returns `false`, and
+/// adds nothing, if `list` or `out` is null.
+///
+/// # Safety
+///
+/// `list` must be null or a live list created by [`indexlist_new`], `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_push_back(
+    list: *mut FfiIndexList,
+    item: *mut c_void,
+    out: *mut RawIndex,
+) -> bool {
+    match list.as_mut() {
+        Some(list) if !out.is_null() => write_index(out, Some(list.0.push_back(item))),
+        _ => false,
+    }
+}
+
+/// Adds `item` to the front of the list and writes its index to `out`. Returns `false`, and
+/// adds nothing, if `list` or `out` is null.
+///
+/// # Safety
+///
+/// `list` must be null or a live list created by [`indexlist_new`], `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_push_front(
+    list: *mut FfiIndexList,
+    item: *mut c_void,
+    out: *mut RawIndex,
+) -> bool {
+    match list.as_mut() {
+        Some(list) if !out.is_null() => write_index(out, Some(list.0.push_front(item))),
+        _ => false,
+    }
+}
+
+/// Writes the element at `index` to `out`. Returns `false` if `list` or `out` is null or the
+/// index does not refer to an element of the list.
+///
+/// # Safety
+///
+/// `list` must be null or a live list created by [`indexlist_new`], `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_get(
+    list: *const FfiIndexList,
+    index: RawIndex,
+    out: *mut *mut c_void,
+) -> bool {
+    match resolve(list, index) {
+        Some(index) if !out.is_null() => {
+            *out = *(*list).0.get(index).expect("index was resolved");
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Removes the element at `index` and writes it to `out`, unless `out` is null. Returns
+/// `false` if `list` is null or the index does not refer to an element of the list.
+///
+/// # Safety
+///
+/// `list` must be null or a live list created by [`indexlist_new`], `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_remove(
+    list: *mut FfiIndexList,
+    index: RawIndex,
+    out: *mut *mut c_void,
+) -> bool {
+    let Some(index) = resolve(list, index) else {
+        return false;
+    };
+    let item = (*list).0.remove(index).expect("index was resolved");
+    if !out.is_null() {
+        *out = item;
+    }
+    true
+}
+
+/// Writes the index of the first element to `out`. Returns `false` if `list` or `out` is
+/// null or the list is empty.
+///
+/// Together with [`indexlist_next`], this iterates over the list:
+///
+/// ```c
+/// RawIndex index;
+/// for (bool more = indexlist_head(list, &index); more; more = indexlist_next(list, index, &index)) {
+///     /* ... */
+/// }
+/// ```
+///
+/// # Safety
+///
+/// `list` must be null or a live list created by [`indexlist_new`], `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_head(list: *const FfiIndexList, out: *mut RawIndex) -> bool {
+    let Some(list) = list.as_ref() else {
+        return false;
+    };
+    write_index(out, list.0.head_index())
+}
+
+/// Writes the index of the element after `index` to `out`. Returns `false` if `list` or `out`
+/// is null, the index does not refer to an element of the list, or it is the last element.
+///
+/// # Safety
+///
+/// `list` must be null or a live list created by [`indexlist_new`], `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn indexlist_next(
+    list: *const FfiIndexList,
+    index: RawIndex,
+    out: *mut RawIndex,
+) -> bool {
+    let Some(index) = resolve(list, index) else {
+        return false;
+    };
+    write_index(out, (*list).0.next_index(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn list_round_trip_through_the_c_interface() {
+        let mut items = [10, 20, 30];
+        let [a, b, c] = items.each_mut().map(|item| item as *mut i32 as *mut c_void);
+        let mut index = RawIndex {
+            slot: 0,
+            generation: 0,
+        };
+        let mut item = ptr::null_mut();
+        unsafe {
+            let list = indexlist_new();
+            assert!(indexlist_push_back(list, b, &mut index));
+            let middle = index;
+            assert!(indexlist_push_back(list, c, &mut index));
+            assert!(indexlist_push_front(list, a, &mut index));
+            assert_eq!(indexlist_len(list), 3);
+
+            let mut seen = Vec::new();
+            let mut more = indexlist_head(list, &mut index);
+            while more {
+                assert!(indexlist_get(list, index, &mut item));
+                seen.push(*(item as *mut i32));
+                more = indexlist_next(list, index, &mut index);
+            }
+            assert_eq!(seen, vec![10, 20, 30]);
+
+            assert!(indexlist_remove(list, middle, &mut item));
+            assert_eq!(item, b);
+            assert!(!indexlist_get(list, middle, &mut item));
+            assert!(!indexlist_remove(list, middle, ptr::null_mut()));
+            assert_eq!(indexlist_len(list), 2);
+            indexlist_free(list);
+        }
+    }
+
+    #[test]
+    fn null_pointers_are_rejected() {
+        let mut index = RawIndex {
+            slot: 0,
+            generation: 0,
+        };
+        unsafe {
+            assert_eq!(indexlist_len(ptr::null()), 0);
+            assert!(!indexlist_push_back(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut index
+            ));
+            assert!(!indexlist_head(ptr::null(), &mut index));
+            let list = indexlist_new();
+            assert!(!indexlist_push_back(list, ptr::null_mut(), ptr::null_mut()));
+            assert_eq!(indexlist_len(list), 0);
+            assert!(!indexlist_get(list, index, ptr::null_mut()));
+            indexlist_free(list);
+            indexlist_free(ptr::null_mut());
+        }
+    }
+}
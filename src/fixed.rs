@@ -0,0 +1,505 @@
+//! A fixed-capacity `IndexList` that keeps its elements inline and never allocates.
+
+use std::fmt;
+
+use crate::storage::Storage;
+use crate::Entry::{Free, Occupied};
+use crate::{Entry, Finger, Index, IndexList, ListId, Slot, Stats};
+
+/// `FixedIndexList` is a doubly linked list of at most `N` elements, stored in an array
+/// inside the list itself, so it never allocates.
+///
+/// It hands out the same generational [`Index`] as [`IndexList`](crate::IndexList) and
+/// follows the same rules: removing an element moves the list to a new generation, so an
+/// index of a removed element never refers to a newer element in the same slot. Adding an
+/// element to a full list fails and gives the element back.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::fixed::FixedIndexList;
+///
+/// let mut list: FixedIndexList<i32, 2> = FixedIndexList::new();
+/// let one = list.push_back(1).unwrap();
+/// list.push_back(2).unwrap();
+/// assert_eq!(list.push_back(3), Err(3));
+///
+/// list.remove(one);
+/// list.push_front(0).unwrap();
+/// assert_eq!(list.get(one), None);
+/// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 2]);
+/// ```
+pub struct FixedIndexList<T, const N: usize> {
+    id: ListId,
+    contents: [Entry<T>; N],
+    generation: usize,
+    next_free: Option<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    count: usize,
+}
+
+impl<T, const N: usize> Default for FixedIndexList<T, N> {
+    fn default() -> Self {
+        FixedIndexList {
+            id: ListId::next(),
+            contents: std::array::from_fn(|slot| Free {
+                next_free: (slot + 1 < N).then(|| Slot::new(slot + 1)),
+            }),
+            generation: 0,
+            next_free: (N > 0).then_some(0),
+            head: None,
+            tail: None,
+            count: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> FixedIndexList<T, N> {
+    /// Creates a new, empty `FixedIndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::fixed::FixedIndexList;
+    /// let list: FixedIndexList<i32, 8> = FixedIndexList::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of elements the list can hold, `N`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::fixed::FixedIndexList;
+    /// let list: FixedIndexList<i32, 8> = FixedIndexList::new();
+    /// assert_eq!(list.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 8> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns `true` if the list contains no elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list: indexlist::fixed::FixedIndexList<i32, 8> = Default::default();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns `true` if no more elements can be added to the list.
+    ///
+    /// A slot whose element was removed in the last possible generation is never reused,
+    /// so once the generation saturates the list can become full with fewer than `N`
+    /// elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 1> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// assert!(list.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.next_free.is_none()
+    }
+
+    /// Appends an element to the back of the list and returns its index, or gives the
+    /// element back if the list is full.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 1> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.get(five), Some(&5));
+    /// assert_eq!(list.push_back(6), Err(6));
+    /// ```
+    pub fn push_back(&mut self, item: T) -> Result<Index<T>, T> {
+        let slot = self.allocate(item)?;
+        match self.tail {
+            Some(tail) => self.contents.link_after(&mut self.tail, tail, slot),
+            None => {
+                self.head = Some(slot);
+                self.tail = Some(slot);
+            }
+        }
+        Ok(self.contents.index_at(slot, self.id))
+    }
+
+    /// Adds an element to the front of the list and returns its index, or gives the element
+    /// back if the list is full.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 2> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// list.push_front(4).unwrap();
+    /// assert_eq!(list.head(), Some(&4));
+    /// ```
+    pub fn push_front(&mut self, item: T) -> Result<Index<T>, T> {
+        let slot = self.allocate(item)?;
+        match self.head {
+            Some(head) => self.contents.link_before(&mut self.head, head, slot),
+            None => {
+                self.head = Some(slot);
+                self.tail = Some(slot);
+            }
+        }
+        Ok(self.contents.index_at(slot, self.id))
+    }
+
+    /// Inserts an element right after the specified index and returns its index. Gives the
+    /// element back if the list is full or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let one = list.push_back(1).unwrap();
+    /// list.push_back(3).unwrap();
+    /// list.insert_after(one, 2).unwrap();
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn insert_after(&mut self, index: Index<T>, item: T) -> Result<Index<T>, T> {
+        if !self.contains_index(index) {
+            return Err(item);
+        }
+        let slot = self.allocate(item)?;
+        self.contents.link_after(&mut self.tail, index.slot(), slot);
+        Ok(self.contents.index_at(slot, self.id))
+    }
+
+    /// Inserts an element right before the specified index and returns its index. Gives the
+    /// element back if the list is full or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// list.push_back(1).unwrap();
+    /// let three = list.push_back(3).unwrap();
+    /// list.insert_before(three, 2).unwrap();
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn insert_before(&mut self, index: Index<T>, item: T) -> Result<Index<T>, T> {
+        if !self.contains_index(index) {
+            return Err(item);
+        }
+        let slot = self.allocate(item)?;
+        self.contents
+            .link_before(&mut self.head, index.slot(), slot);
+        Ok(self.contents.index_at(slot, self.id))
+    }
+
+    /// Removes the first element of the list and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// assert_eq!(list.pop_front(), Some(5));
+    /// assert_eq!(list.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        let index = self.head_index()?;
+        self.remove(index)
+    }
+
+    /// Removes the last element of the list and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// list.push_back(6).unwrap();
+    /// assert_eq!(list.pop_back(), Some(6));
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        let index = self.tail_index()?;
+        self.remove(index)
+    }
+
+    /// Removes the element at the given index and returns it, if it still exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.remove(five), Some(5));
+    /// assert_eq!(list.remove(five), None);
+    /// ```
+    pub fn remove(&mut self, index: Index<T>) -> Option<T> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        let slot = index.slot();
+        let (prev, next) = {
+            let oc = self.contents.occupied(slot);
+            (oc.prev(), oc.next())
+        };
+        match prev {
+            Some(prev) => self.contents.occupied_mut(prev).set_next(next),
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.contents.occupied_mut(next).set_prev(prev),
+            None => self.tail = prev,
+        }
+        self.generation = self.generation.saturating_add(1);
+        let Occupied(oc) = std::mem::replace(&mut self.contents[slot], Free { next_free: None })
+        else {
+            panic!("Corrupted list");
+        };
+        // A reused slot would get the saturated generation again and revive stale indices.
+        if oc.generation != usize::MAX {
+            self.contents[slot] = Free {
+                next_free: self.next_free.map(Slot::new),
+            };
+            self.next_free = Some(slot);
+        }
+        self.count -= 1;
+        Some(oc.item)
+    }
+
+    /// Returns `true` if the index refers to an element of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// assert!(list.contains_index(five));
+    /// ```
+    pub fn contains_index(&self, index: Index<T>) -> bool {
+        index.list == self.id
+            && matches!(
+                self.contents.get(index.slot()),
+                Some(Occupied(oc)) if oc.generation == index.generation
+            )
+    }
+
+    /// Returns a reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn get(&self, index: Index<T>) -> Option<&T> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        Some(&self.contents.occupied(index.slot()).item)
+    }
+
+    /// Returns a mutable reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// *list.get_mut(five).unwrap() = 6;
+    /// assert_eq!(list.get(five), Some(&6));
+    /// ```
+    pub fn get_mut(&mut self, index: Index<T>) -> Option<&mut T> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        Some(&mut self.contents.occupied_mut(index.slot()).item)
+    }
+
+    /// Returns a reference to the first element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// assert_eq!(list.head(), Some(&5));
+    /// ```
+    pub fn head(&self) -> Option<&T> {
+        Some(&self.contents.occupied(self.head?).item)
+    }
+
+    /// Returns a reference to the last element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// list.push_back(6).unwrap();
+    /// assert_eq!(list.tail(), Some(&6));
+    /// ```
+    pub fn tail(&self) -> Option<&T> {
+        Some(&self.contents.occupied(self.tail?).item)
+    }
+
+    /// Returns the index of the first element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.head_index(), Some(five));
+    /// ```
+    pub fn head_index(&self) -> Option<Index<T>> {
+        Some(self.contents.index_at(self.head?, self.id))
+    }
+
+    /// Returns the index of the last element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// let six = list.push_back(6).unwrap();
+    /// assert_eq!(list.tail_index(), Some(six));
+    /// ```
+    pub fn tail_index(&self) -> Option<Index<T>> {
+        Some(self.contents.index_at(self.tail?, self.id))
+    }
+
+    /// Returns the index of the element after the given one, or `None` if it is the last
+    /// element or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// let six = list.push_back(6).unwrap();
+    /// assert_eq!(list.next_index(five), Some(six));
+    /// assert_eq!(list.next_index(six), None);
+    /// ```
+    pub fn next_index(&self, index: Index<T>) -> Option<Index<T>> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        let next = self.contents.occupied(index.slot()).next()?;
+        Some(self.contents.index_at(next, self.id))
+    }
+
+    /// Returns the index of the element before the given one, or `None` if it is the first
+    /// element or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// let five = list.push_back(5).unwrap();
+    /// let six = list.push_back(6).unwrap();
+    /// assert_eq!(list.prev_index(six), Some(five));
+    /// ```
+    pub fn prev_index(&self, index: Index<T>) -> Option<Index<T>> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        let prev = self.contents.occupied(index.slot()).prev()?;
+        Some(self.contents.index_at(prev, self.id))
+    }
+
+    /// Returns an iterator over the elements in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::fixed::FixedIndexList<i32, 4> = Default::default();
+    /// list.push_back(5).unwrap();
+    /// list.push_front(4).unwrap();
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![4, 5]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        let mut next = self.head;
+        std::iter::from_fn(move || {
+            let oc = self.contents.occupied(next?);
+            next = oc.next();
+            Some(&oc.item)
+        })
+    }
+
+    /// Moves the elements into an `IndexList` with the same layout, generation and id, so
+    /// every index of this list keeps referring to the same element.
+    pub(crate) fn into_list(self) -> IndexList<T> {
+        IndexList {
+            id: self.id,
+            contents: crate::alloc::Vec::from(self.contents),
+            generation: self.generation,
+            next_free: self.next_free,
+            ordered_free: None,
+            head: self.head,
+            tail: self.tail,
+            count: self.count,
+            max_capacity: None,
+            detached: 0,
+            finger: Finger::default(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// Stores `item` in a free slot without linking it into the list, or gives it back if
+    /// there is none.
+    fn allocate(&mut self, item: T) -> Result<usize, T> {
+        let slot = self
+            .contents
+            .allocate(&mut self.next_free, item, self.generation)?;
+        self.count += 1;
+        Ok(slot)
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for FixedIndexList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_list_rejects_new_elements() {
+        let mut list: FixedIndexList<i32, 3> = FixedIndexList::new();
+        let two = list.push_back(2).unwrap();
+        list.push_front(0).unwrap();
+        list.insert_before(two, 1).unwrap();
+        assert!(list.is_full());
+        assert_eq!(list.push_back(3), Err(3));
+        assert_eq!(list.insert_after(two, 3), Err(3));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        assert_eq!(list.pop_front(), Some(0));
+        let three = list.push_back(3).unwrap();
+        assert_eq!(list.prev_index(three), Some(two));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn stale_indices_do_not_see_reused_slots() {
+        let mut list: FixedIndexList<i32, 1> = FixedIndexList::new();
+        let one = list.push_back(1).unwrap();
+        assert_eq!(list.remove(one), Some(1));
+        let two = list.push_back(2).unwrap();
+        assert_eq!(two.slot(), one.slot());
+        assert_eq!(list.get(one), None);
+        assert_eq!(list.remove(one), None);
+        assert_eq!(list.get(two), Some(&2));
+        assert!(list.insert_after(one, 3).is_err());
+    }
+
+    #[test]
+    fn empty_capacity() {
+        let mut list: FixedIndexList<i32, 0> = FixedIndexList::new();
+        assert!(list.is_full());
+        assert_eq!(list.push_front(1), Err(1));
+        assert_eq!(list.pop_back(), None);
+    }
+}
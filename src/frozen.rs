@@ -0,0 +1,246 @@
+//! A read-only `IndexList`, laid out in list order for fast traversal.
+
+use std::slice;
+
+use crate::{Allocator, Index, IndexList, IndexType, ListId};
+
+/// `FrozenIndexList` is an [`IndexList`] that no longer changes, created with
+/// [`IndexList::freeze`].
+///
+/// The elements are stored in a plain vector in list order, so iterating over it runs at
+/// slice speed and the element at any position is found in O(1) time. Indices handed out by
+/// the list before it was frozen are translated through a table of their slots, so they
+/// keep referring to the same elements.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut list = indexlist::IndexList::new();
+/// let two = list.push_back(2);
+/// list.push_front(1);
+/// let frozen = list.freeze();
+///
+/// assert_eq!(frozen.as_slice(), &[1, 2]);
+/// assert_eq!(frozen.get(two), Some(&2));
+/// assert_eq!(frozen.position_of(two), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrozenIndexList<T, I: IndexType = usize> {
+    id: ListId,
+    items: Vec<T>,
+    /// The generation and position of the element in each slot of the frozen list, `None`
+    /// for free slots.
+    remap: Vec<Option<(I, usize)>>,
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> IndexList<T, I, A> {
+    /// Consumes the list and returns a read-only copy of it, with the elements stored next
+    /// to each other in list order and the free-list bookkeeping dropped.
+    ///
+    /// Indices into the list remain usable with the frozen list. Detached elements are not
+    /// part of the order and are dropped.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.remove(one);
+    /// let frozen = list.freeze();
+    /// assert_eq!(frozen.len(), 1);
+    /// assert_eq!(frozen.get(one), None);
+    /// assert_eq!(frozen.get(two), Some(&2));
+    /// ```
+    pub fn freeze(self) -> FrozenIndexList<T, I> {
+        let order = self.slots();
+        let mut remap = vec![None; self.contents.len()];
+        for (position, &slot) in order.iter().enumerate() {
+            remap[slot] = Some((self.occupied(slot).generation, position));
+        }
+        FrozenIndexList {
+            id: self.id,
+            items: self.into_items_at(&order),
+            remap,
+        }
+    }
+}
+
+impl<T, I: IndexType> FrozenIndexList<T, I> {
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.freeze().len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the list contains no elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list: indexlist::IndexList<i32> = indexlist::IndexList::new();
+    /// assert!(list.freeze().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the position of the element an index of the unfrozen list refers to, or
+    /// `None` if the index was not valid when the list was frozen.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// list.push_front(0);
+    /// assert_eq!(list.freeze().position_of(five), Some(1));
+    /// ```
+    pub fn position_of(&self, index: Index<T, I>) -> Option<usize> {
+        if index.list != self.id {
+            return None;
+        }
+        match self.remap.get(index.slot())? {
+            Some((generation, position)) if *generation == index.generation => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the element an index of the unfrozen list refers to, if it
+    /// exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.freeze().get(five), Some(&5));
+    /// ```
+    pub fn get(&self, index: Index<T, I>) -> Option<&T> {
+        self.position_of(index)
+            .map(|position| &self.items[position])
+    }
+
+    /// Returns a reference to the element at the given position, in O(1) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// let frozen = list.freeze();
+    /// assert_eq!(frozen.get_by_position(1), Some(&10));
+    /// assert_eq!(frozen.get_by_position(2), None);
+    /// ```
+    pub fn get_by_position(&self, position: usize) -> Option<&T> {
+        self.items.get(position)
+    }
+
+    /// Returns the elements as a slice, in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(2);
+    /// list.push_front(1);
+    /// assert_eq!(list.freeze().as_slice(), &[1, 2]);
+    /// ```
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns an iterator over the elements in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// let sum: i32 = list.freeze().iter().sum();
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Consumes the list and returns its elements in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(2);
+    /// list.push_front(1);
+    /// assert_eq!(list.freeze().into_vec(), vec![1, 2]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T, I: IndexType> std::ops::Index<usize> for FrozenIndexList<T, I> {
+    type Output = T;
+
+    fn index(&self, position: usize) -> &T {
+        &self.items[position]
+    }
+}
+
+impl<'a, T, I: IndexType> IntoIterator for &'a FrozenIndexList<T, I> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, I: IndexType> IntoIterator for FrozenIndexList<T, I> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freeze_translates_old_indices() {
+        let mut list = IndexList::new();
+        let indices: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.remove(indices[2]);
+        let front = list.push_front(10);
+        list.detach(indices[4]);
+        list.reverse();
+
+        let frozen = list.freeze();
+        assert_eq!(frozen.as_slice(), &[5, 3, 1, 0, 10]);
+        assert_eq!(frozen.get(front), Some(&10));
+        assert_eq!(frozen.position_of(indices[3]), Some(1));
+        assert_eq!(frozen.get(indices[2]), None);
+        assert_eq!(frozen.get(indices[4]), None);
+        assert_eq!(frozen[2], 1);
+
+        let other = IndexList::<i32>::new().freeze();
+        assert_eq!(other.get(front), None);
+    }
+
+    #[test]
+    fn freeze_drops_detached_element_at_the_end() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        list.detach(two);
+
+        let frozen = list.freeze();
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen.as_slice(), &[1]);
+        assert_eq!(frozen.get(one), Some(&1));
+        assert_eq!(frozen.get(two), None);
+    }
+}
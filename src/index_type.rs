@@ -0,0 +1,99 @@
+//! The integer types a list can store its slots, links and generations as.
+
+use std::fmt;
+use std::hash::Hash;
+use std::num::NonZero;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for usize {}
+    impl Sealed for u32 {}
+}
+
+/// The integer type an [`IndexList`](crate::IndexList) stores its slots, links and
+/// generations as, and that every [`Index`](crate::Index) into it carries.
+///
+/// `usize` is the default. With `u32`, an `Index` and the bookkeeping of every element take
+/// half the space on 64-bit targets, in exchange for two limits: a list holds fewer than
+/// `u32::MAX` slots, and after `u32::MAX` removals its generation saturates, from which point
+/// freed slots are no longer reused.
+///
+/// The trait is sealed, `usize` and `u32` are its only implementations.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::{Index, IndexList};
+///
+/// let mut list: IndexList<u8, u32> = IndexList::default();
+/// let five = list.push_back(5);
+/// assert_eq!(list.get(five), Some(&5));
+/// assert!(std::mem::size_of::<Index<u8, u32>>() < std::mem::size_of::<Index<u8>>());
+/// ```
+pub trait IndexType:
+    sealed::Sealed + Copy + Ord + Hash + Default + fmt::Debug + Send + Sync + 'static
+{
+    /// The slot number as stored, which is never zero so that options of it take no extra
+    /// space.
+    #[doc(hidden)]
+    type Stored: Copy + Eq + Hash + Send + Sync + 'static;
+
+    /// The largest value of the type, where generations saturate.
+    #[doc(hidden)]
+    const MAX: Self;
+
+    /// Converts a slot or generation, or returns `None` if it does not fit.
+    #[doc(hidden)]
+    fn from_usize(value: usize) -> Option<Self>;
+
+    #[doc(hidden)]
+    fn to_usize(self) -> usize;
+
+    /// Returns the next generation, or the same one once it has saturated.
+    #[doc(hidden)]
+    fn saturating_inc(self) -> Self;
+
+    /// Stores a slot as the bitwise complement of its number, or returns `None` if it does
+    /// not fit.
+    #[doc(hidden)]
+    fn store(slot: usize) -> Option<Self::Stored>;
+
+    #[doc(hidden)]
+    fn load(stored: Self::Stored) -> usize;
+}
+
+macro_rules! index_type {
+    ($ty:ty) => {
+        impl IndexType for $ty {
+            type Stored = NonZero<$ty>;
+
+            const MAX: Self = <$ty>::MAX;
+
+            #[allow(clippy::useless_conversion)]
+            fn from_usize(value: usize) -> Option<Self> {
+                <$ty>::try_from(value).ok()
+            }
+
+            #[allow(clippy::unnecessary_cast)]
+            fn to_usize(self) -> usize {
+                self as usize
+            }
+
+            fn saturating_inc(self) -> Self {
+                self.saturating_add(1)
+            }
+
+            fn store(slot: usize) -> Option<Self::Stored> {
+                NonZero::new(!Self::from_usize(slot)?)
+            }
+
+            fn load(stored: Self::Stored) -> usize {
+                (!stored.get()).to_usize()
+            }
+        }
+    };
+}
+
+index_type!(usize);
+index_type!(u32);
@@ -0,0 +1,754 @@
+//! An `IndexList` with an order-statistic tree on the side, for O(log n) positional access.
+
+use std::cmp::Ordering;
+
+use crate::{Index, IndexList, Iter};
+
+/// `IndexedList` is an [`IndexList`] that additionally keeps track of the position of every
+/// element, so that positional queries run in O(log n) instead of O(n).
+///
+/// The positions are maintained in an implicit treap over the slots of the list. Every
+/// structural change pays an extra O(log n), everything else behaves exactly like the
+/// underlying `IndexList`, including its generational indices.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::IndexedList;
+///
+/// let mut list = IndexedList::new();
+/// let one = list.push_back(1);
+/// list.push_back(3);
+/// list.insert_at(1, 2);
+///
+/// assert_eq!(list.position_of(one), Some(0));
+/// assert_eq!(list.get_by_position(1).map(|(_, item)| *item), Some(2));
+/// ```
+#[derive(Debug)]
+pub struct IndexedList<T> {
+    list: IndexList<T>,
+    tree: OrderTree,
+}
+
+impl<T> Default for IndexedList<T> {
+    fn default() -> Self {
+        IndexedList {
+            list: IndexList::default(),
+            tree: OrderTree::default(),
+        }
+    }
+}
+
+impl<T> IndexedList<T> {
+    /// Creates a new, empty `IndexedList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::IndexedList;
+    /// let list: IndexedList<i32> = IndexedList::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new, empty `IndexedList` with the specified capacity.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::IndexedList;
+    /// let list: IndexedList<i32> = IndexedList::with_capacity(10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexedList {
+            list: IndexList::with_capacity(capacity),
+            tree: OrderTree::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the underlying `IndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(5);
+    /// assert!(list.as_list().contains(&5));
+    /// ```
+    pub fn as_list(&self) -> &IndexList<T> {
+        &self.list
+    }
+
+    /// Consumes the `IndexedList` and returns the underlying `IndexList`. All indices remain
+    /// valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let five = list.push_back(5);
+    /// let list = list.into_list();
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn into_list(self) -> IndexList<T> {
+        self.list
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the list contains no elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list: indexlist::IndexedList<i32> = indexlist::IndexedList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns a reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let index = list.push_back(5);
+    /// assert_eq!(list.get(index), Some(&5));
+    /// ```
+    pub fn get(&self, index: Index<T>) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Returns a mutable reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let index = list.push_back(5);
+    /// *list.get_mut(index).unwrap() = 10;
+    /// assert_eq!(list.get(index), Some(&10));
+    /// ```
+    pub fn get_mut(&mut self, index: Index<T>) -> Option<&mut T> {
+        self.list.get_mut(index)
+    }
+
+    /// Returns `true` if the index refers to an element that is still in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let index = list.push_back(5);
+    /// list.remove(index);
+    /// assert!(!list.contains_index(index));
+    /// ```
+    pub fn contains_index(&self, index: Index<T>) -> bool {
+        self.list.contains_index(index)
+    }
+
+    /// Returns the generational index of the first element in the list, or `None` if the
+    /// list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.head_index(), Some(five));
+    /// ```
+    pub fn head_index(&self) -> Option<Index<T>> {
+        self.list.head_index()
+    }
+
+    /// Returns the generational index of the last element in the list, or `None` if the
+    /// list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.tail_index(), Some(five));
+    /// ```
+    pub fn tail_index(&self) -> Option<Index<T>> {
+        self.list.tail_index()
+    }
+
+    /// Returns the next index after the given one, or `None` if it is the last element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.next_index(five), Some(ten));
+    /// ```
+    pub fn next_index(&self, index: Index<T>) -> Option<Index<T>> {
+        self.list.next_index(index)
+    }
+
+    /// Returns the previous index before the given one, or `None` if it is the first element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.prev_index(ten), Some(five));
+    /// ```
+    pub fn prev_index(&self, index: Index<T>) -> Option<Index<T>> {
+        self.list.prev_index(index)
+    }
+
+    /// Appends an element to the back of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let index = list.push_back(5);
+    /// assert_eq!(list.position_of(index), Some(0));
+    /// ```
+    pub fn push_back(&mut self, item: T) -> Index<T> {
+        let index = self.list.push_back(item);
+        self.tree.insert(index.slot(), self.list.len() - 1);
+        index
+    }
+
+    /// Appends an element to the front of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(5);
+    /// let index = list.push_front(0);
+    /// assert_eq!(list.position_of(index), Some(0));
+    /// ```
+    pub fn push_front(&mut self, item: T) -> Index<T> {
+        let index = self.list.push_front(item);
+        self.tree.insert(index.slot(), 0);
+        index
+    }
+
+    /// Removes the first element from the list and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.pop_front(), Some(5));
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(self.list.head_index()?)
+    }
+
+    /// Removes the last element from the list and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.pop_back(), Some(5));
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.list.tail_index()?)
+    }
+
+    /// Inserts an element before the specified index and returns its new index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let two = list.push_back(2);
+    /// let one = list.insert_before(two, 1).unwrap();
+    /// assert_eq!(list.position_of(one), Some(0));
+    /// ```
+    pub fn insert_before(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        let position = self.position_of(index)?;
+        let new = self.list.insert_before(index, item)?;
+        self.tree.insert(new.slot(), position);
+        Some(new)
+    }
+
+    /// Inserts an element after the specified index and returns its new index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.insert_after(one, 2).unwrap();
+    /// assert_eq!(list.position_of(two), Some(1));
+    /// ```
+    pub fn insert_after(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        let position = self.position_of(index)?;
+        let new = self.list.insert_after(index, item)?;
+        self.tree.insert(new.slot(), position + 1);
+        Some(new)
+    }
+
+    /// Inserts an element so that it ends up at `position` and returns its index, or `None`
+    /// if `position` is greater than the length of the list. Takes O(log n) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(1);
+    /// list.push_back(3);
+    /// list.insert_at(1, 2);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// assert!(list.insert_at(10, 4).is_none());
+    /// ```
+    pub fn insert_at(&mut self, position: usize, item: T) -> Option<Index<T>> {
+        if position == self.len() {
+            return Some(self.push_back(item));
+        }
+        let (index, _) = self.get_by_position(position)?;
+        self.insert_before(index, item)
+    }
+
+    /// Removes the element at the given index and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.remove(five), Some(5));
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn remove(&mut self, index: Index<T>) -> Option<T> {
+        if !self.list.contains_index(index) {
+            return None;
+        }
+        self.tree.remove(index.slot());
+        self.list.remove(index)
+    }
+
+    /// Removes the element at `position` and returns it, or `None` if `position` is out of
+    /// bounds. Takes O(log n) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert_eq!(list.remove_at(1), Some(2));
+    /// assert_eq!(list.remove_at(1), None);
+    /// ```
+    pub fn remove_at(&mut self, position: usize) -> Option<T> {
+        let (index, _) = self.get_by_position(position)?;
+        self.remove(index)
+    }
+
+    /// Returns the index of and a reference to the element at `position` in list order, or
+    /// `None` if `position` is out of bounds. Takes O(log n) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.get_by_position(1), Some((ten, &10)));
+    /// ```
+    pub fn get_by_position(&self, position: usize) -> Option<(Index<T>, &T)> {
+        let slot = self.tree.select(position)?;
+        Some((self.list.index_at(slot), &self.list.occupied(slot).item))
+    }
+
+    /// Returns the position of the element at the given index in list order, or `None` if
+    /// the index is not valid. Takes O(log n) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let ten = list.push_back(10);
+    /// list.push_front(5);
+    /// assert_eq!(list.position_of(ten), Some(1));
+    /// ```
+    pub fn position_of(&self, index: Index<T>) -> Option<usize> {
+        if !self.list.contains_index(index) {
+            return None;
+        }
+        Some(self.tree.position(index.slot()))
+    }
+
+    /// Returns `true` if the element at `a` comes before the element at `b` in the list, or
+    /// `None` if either index is not valid. Takes O(log n) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.is_before(five, ten), Some(true));
+    /// assert_eq!(list.is_before(ten, five), Some(false));
+    /// ```
+    pub fn is_before(&self, a: Index<T>, b: Index<T>) -> Option<bool> {
+        Some(self.position_of(a)? < self.position_of(b)?)
+    }
+
+    /// Returns the signed number of hops from `a` to `b` in list order, or `None` if either
+    /// index is not valid. Takes O(log n) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// let three = list.push_back(3);
+    /// assert_eq!(list.distance(three, one), Some(-2));
+    /// ```
+    pub fn distance(&self, a: Index<T>, b: Index<T>) -> Option<isize> {
+        Some(self.position_of(b)? as isize - self.position_of(a)? as isize)
+    }
+
+    /// Binary searches a sorted list with a comparator function in O(log n) time.
+    ///
+    /// See [`IndexList::binary_search_by`] for the meaning of `f` and of the result.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(1);
+    /// let three = list.push_back(3);
+    /// assert_eq!(list.binary_search_by(|item| item.cmp(&3)), Ok(three));
+    /// assert_eq!(list.binary_search_by(|item| item.cmp(&2)), Err(Some(three)));
+    /// assert_eq!(list.binary_search_by(|item| item.cmp(&4)), Err(None));
+    /// ```
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<Index<T>, Option<Index<T>>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let mut node = self.tree.root;
+        let mut insert_before = None;
+        while let Some(slot) = node {
+            match f(&self.list.occupied(slot).item) {
+                Ordering::Less => node = self.tree.nodes[slot].right,
+                Ordering::Greater => {
+                    insert_before = Some(slot);
+                    node = self.tree.nodes[slot].left;
+                }
+                Ordering::Equal => return Ok(self.list.index_at(slot)),
+            }
+        }
+        Err(insert_before.map(|slot| self.list.index_at(slot)))
+    }
+
+    /// Returns a non-consuming iterator over the elements of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexedList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![5]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+}
+
+impl<T> From<IndexList<T>> for IndexedList<T> {
+    fn from(list: IndexList<T>) -> Self {
+        let mut tree = OrderTree::with_capacity(list.contents.len());
+        for (position, slot) in list.slots().into_iter().enumerate() {
+            tree.insert(slot, position);
+        }
+        IndexedList { list, tree }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IndexedList<T> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An implicit treap over the slots of a list, ordered by list position.
+#[derive(Debug, Default)]
+struct OrderTree {
+    nodes: Vec<Node>,
+    root: Option<usize>,
+    seed: u64,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Node {
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+    size: usize,
+    priority: u64,
+}
+
+impl OrderTree {
+    fn with_capacity(capacity: usize) -> Self {
+        OrderTree {
+            nodes: Vec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    fn size(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |node| self.nodes[node].size)
+    }
+
+    /// Returns the next pseudo-random priority (xorshift64*).
+    fn next_priority(&mut self) -> u64 {
+        if self.seed == 0 {
+            self.seed = 0x9E37_79B9_7F4A_7C15;
+        }
+        self.seed ^= self.seed >> 12;
+        self.seed ^= self.seed << 25;
+        self.seed ^= self.seed >> 27;
+        self.seed.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Recomputes the size of `node` and points its children back at it.
+    fn update(&mut self, node: usize) {
+        let Node { left, right, .. } = self.nodes[node];
+        self.nodes[node].size = 1 + self.size(left) + self.size(right);
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    fn set_root(&mut self, root: Option<usize>) {
+        if let Some(root) = root {
+            self.nodes[root].parent = None;
+        }
+        self.root = root;
+    }
+
+    fn merge(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        match (a, b) {
+            (None, tree) | (tree, None) => tree,
+            (Some(a), Some(b)) => {
+                if self.nodes[a].priority > self.nodes[b].priority {
+                    self.nodes[a].right = self.merge(self.nodes[a].right, Some(b));
+                    self.update(a);
+                    Some(a)
+                } else {
+                    self.nodes[b].left = self.merge(Some(a), self.nodes[b].left);
+                    self.update(b);
+                    Some(b)
+                }
+            }
+        }
+    }
+
+    /// Splits `tree` into its first `k` nodes and the rest.
+    fn split(&mut self, tree: Option<usize>, k: usize) -> (Option<usize>, Option<usize>) {
+        let Some(node) = tree else {
+            return (None, None);
+        };
+        let left_size = self.size(self.nodes[node].left);
+        if k <= left_size {
+            let (left, right) = self.split(self.nodes[node].left, k);
+            self.nodes[node].left = right;
+            self.update(node);
+            (left, Some(node))
+        } else {
+            let (left, right) = self.split(self.nodes[node].right, k - left_size - 1);
+            self.nodes[node].right = left;
+            self.update(node);
+            (Some(node), right)
+        }
+    }
+
+    fn insert(&mut self, slot: usize, position: usize) {
+        if self.nodes.len() <= slot {
+            self.nodes.resize(slot + 1, Node::default());
+        }
+        self.nodes[slot] = Node {
+            size: 1,
+            priority: self.next_priority(),
+            ..Node::default()
+        };
+        let (left, right) = self.split(self.root, position);
+        let left = self.merge(left, Some(slot));
+        let root = self.merge(left, right);
+        self.set_root(root);
+    }
+
+    fn remove(&mut self, slot: usize) {
+        let position = self.position(slot);
+        let (left, right) = self.split(self.root, position);
+        let (_, right) = self.split(right, 1);
+        let root = self.merge(left, right);
+        self.set_root(root);
+    }
+
+    fn position(&self, slot: usize) -> usize {
+        let mut position = self.size(self.nodes[slot].left);
+        let mut node = slot;
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].right == Some(node) {
+                position += self.size(self.nodes[parent].left) + 1;
+            }
+            node = parent;
+        }
+        position
+    }
+
+    fn select(&self, mut position: usize) -> Option<usize> {
+        let mut node = self.root?;
+        loop {
+            let left_size = self.size(self.nodes[node].left);
+            match position.cmp(&left_size) {
+                Ordering::Less => node = self.nodes[node].left?,
+                Ordering::Equal => return Some(node),
+                Ordering::Greater => {
+                    position -= left_size + 1;
+                    node = self.nodes[node].right?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_positions<T: PartialEq + std::fmt::Debug>(list: &IndexedList<T>) {
+        assert_eq!(list.tree.size(list.tree.root), list.len());
+        for (position, slot) in list.list.slots().into_iter().enumerate() {
+            let index = list.list.index_at(slot);
+            assert_eq!(list.position_of(index), Some(position));
+            assert_eq!(list.get_by_position(position).map(|(i, _)| i), Some(index));
+        }
+    }
+
+    #[test]
+    fn push_and_remove() {
+        let mut list = IndexedList::new();
+
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        list.push_front(0);
+        list.push_back(3);
+        check_positions(&list);
+
+        assert_eq!(list.remove(two), Some(2));
+        check_positions(&list);
+        assert_eq!(list.position_of(two), None);
+        assert_eq!(list.position_of(one), Some(1));
+
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.pop_back(), Some(3));
+        check_positions(&list);
+        assert_eq!(list.position_of(one), Some(0));
+    }
+
+    #[test]
+    fn insert_and_remove_at() {
+        let mut list = IndexedList::new();
+        let mut expected = vec![];
+
+        for i in 0..200usize {
+            let position = (i * 7919) % (expected.len() + 1);
+            list.insert_at(position, i);
+            expected.insert(position, i);
+            if i % 3 == 0 {
+                let position = (i * 31) % expected.len();
+                assert_eq!(list.remove_at(position), Some(expected.remove(position)));
+            }
+        }
+
+        check_positions(&list);
+        let items: Vec<_> = (0..list.len())
+            .map(|position| *list.get_by_position(position).unwrap().1)
+            .collect();
+        assert_eq!(items, expected);
+        assert!(list.insert_at(expected.len() + 1, 0).is_none());
+        assert!(list.remove_at(expected.len()).is_none());
+    }
+
+    #[test]
+    fn insert_before_and_after() {
+        let mut list = IndexedList::new();
+
+        let two = list.push_back(2);
+        list.insert_before(two, 0);
+        list.insert_after(two, 4);
+        let one = list.insert_before(two, 1).unwrap();
+        list.insert_after(two, 3);
+        check_positions(&list);
+
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(list.position_of(one), Some(1));
+    }
+
+    #[test]
+    fn from_index_list() {
+        let mut inner = IndexList::new();
+        let one = inner.push_back(1);
+        let two = inner.push_back(2);
+        inner.push_front(0);
+        inner.remove(one);
+
+        let list = IndexedList::from(inner);
+
+        check_positions(&list);
+        assert_eq!(list.position_of(two), Some(1));
+        assert_eq!(list.into_list().get(two), Some(&2));
+    }
+
+    #[test]
+    fn binary_search_by() {
+        let mut list = IndexedList::new();
+
+        let indexes: Vec<_> = (0..50).map(|item| list.push_back(item * 2)).collect();
+
+        for (i, &index) in indexes.iter().enumerate() {
+            let target = i * 2;
+            assert_eq!(list.binary_search_by(|item| item.cmp(&target)), Ok(index));
+            let missing = target + 1;
+            assert_eq!(
+                list.binary_search_by(|item| item.cmp(&missing)),
+                Err(indexes.get(i + 1).copied())
+            );
+        }
+        assert!(IndexedList::<i32>::new()
+            .binary_search_by(|item| item.cmp(&1))
+            .is_err());
+    }
+
+    #[test]
+    fn is_before_and_distance() {
+        let mut list = IndexedList::new();
+
+        let indexes: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[2]);
+
+        assert_eq!(list.is_before(indexes[0], indexes[5]), Some(true));
+        assert_eq!(list.is_before(indexes[5], indexes[0]), Some(false));
+        assert_eq!(list.is_before(indexes[3], indexes[3]), Some(false));
+        assert_eq!(list.is_before(indexes[2], indexes[3]), None);
+        assert_eq!(list.distance(indexes[5], indexes[0]), Some(-4));
+        assert_eq!(list.distance(indexes[1], indexes[3]), Some(1));
+        assert_eq!(list.distance(indexes[2], indexes[3]), None);
+    }
+
+    #[test]
+    fn get_removed_returns_none() {
+        let mut list = IndexedList::new();
+
+        let five = list.push_back(5);
+        list.remove(five);
+
+        assert!(list.get(five).is_none());
+        assert!(list.get_mut(five).is_none());
+        assert!(list.remove(five).is_none());
+        assert!(list.position_of(five).is_none());
+    }
+}
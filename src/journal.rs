@@ -0,0 +1,581 @@
+//! An `IndexList` that records its changes, so they can be undone and redone.
+
+use crate::Entry::{Free, Occupied};
+use crate::{Index, IndexList, OccupiedEntry, Slot};
+
+/// A change made to the list, holding what it takes to revert it.
+#[derive(Debug)]
+enum Change<T> {
+    Inserted {
+        slot: usize,
+    },
+    Removed {
+        slot: usize,
+        generation: usize,
+        item: T,
+        prev: Option<usize>,
+    },
+    Replaced {
+        slot: usize,
+        item: T,
+    },
+    Moved {
+        slot: usize,
+        prev: Option<usize>,
+    },
+}
+
+/// `JournaledIndexList` is an [`IndexList`] that records every change made through it, so
+/// that changes can be undone and redone.
+///
+/// Changes are grouped into steps with [`JournaledIndexList::checkpoint`], and
+/// [`JournaledIndexList::undo`] and [`JournaledIndexList::redo`] revert and repeat one step
+/// at a time. Elements brought back by undoing or redoing return to the slot and generation
+/// they had, so every index handed out for them is valid again, and indices of elements
+/// taken away by undoing or redoing are invalid, just as if they had been removed.
+///
+/// Removed and replaced elements are kept in the history to be brought back, so
+/// [`JournaledIndexList::remove`] and [`JournaledIndexList::replace`] do not return them.
+/// [`JournaledIndexList::clear_history`] drops them.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::journal::JournaledIndexList;
+///
+/// let mut list = JournaledIndexList::new();
+/// let one = list.push_back(1);
+/// list.push_back(2);
+/// list.checkpoint();
+///
+/// list.remove(one);
+/// list.push_front(0);
+/// assert_eq!(list.list().to_string(), "[0, 2]");
+///
+/// assert!(list.undo());
+/// assert_eq!(list.list().to_string(), "[1, 2]");
+/// assert_eq!(list.list().get(one), Some(&1));
+///
+/// assert!(list.redo());
+/// assert_eq!(list.list().to_string(), "[0, 2]");
+/// ```
+#[derive(Debug)]
+pub struct JournaledIndexList<T> {
+    list: IndexList<T>,
+    /// The changes since the last checkpoint, in the order they were made.
+    pending: Vec<Change<T>>,
+    undo: Vec<Vec<Change<T>>>,
+    redo: Vec<Vec<Change<T>>>,
+}
+
+impl<T> Default for JournaledIndexList<T> {
+    fn default() -> Self {
+        IndexList::new().into()
+    }
+}
+
+/// The history starts out empty, the elements already in the list cannot be undone.
+impl<T> From<IndexList<T>> for JournaledIndexList<T> {
+    fn from(list: IndexList<T>) -> Self {
+        JournaledIndexList {
+            list,
+            pending: Vec::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+}
+
+impl<T> JournaledIndexList<T> {
+    /// Creates a new, empty `JournaledIndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::journal::JournaledIndexList;
+    /// let list: JournaledIndexList<i32> = JournaledIndexList::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the underlying list, for reading it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.list().get(five), Some(&5));
+    /// ```
+    pub fn list(&self) -> &IndexList<T> {
+        &self.list
+    }
+
+    /// Consumes the journaled list and returns the underlying list, dropping the history.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.into_inner().len(), 1);
+    /// ```
+    pub fn into_inner(self) -> IndexList<T> {
+        self.list
+    }
+
+    /// Adds an element to the back of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// list.push_back(5);
+    /// assert_eq!(list.list().tail(), Some(&5));
+    /// ```
+    pub fn push_back(&mut self, item: T) -> Index<T> {
+        let index = self.list.push_back(item);
+        self.record(Change::Inserted { slot: index.slot() });
+        index
+    }
+
+    /// Adds an element to the front of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// list.push_back(5);
+    /// list.push_front(4);
+    /// assert_eq!(list.list().head(), Some(&4));
+    /// ```
+    pub fn push_front(&mut self, item: T) -> Index<T> {
+        let index = self.list.push_front(item);
+        self.record(Change::Inserted { slot: index.slot() });
+        index
+    }
+
+    /// Inserts an element before the given index and returns its index, or `None` if the
+    /// index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let two = list.push_back(2);
+    /// list.insert_before(two, 1);
+    /// assert_eq!(list.list().to_string(), "[1, 2]");
+    /// ```
+    pub fn insert_before(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        let index = self.list.insert_before(index, item)?;
+        self.record(Change::Inserted { slot: index.slot() });
+        Some(index)
+    }
+
+    /// Inserts an element after the given index and returns its index, or `None` if the
+    /// index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let one = list.push_back(1);
+    /// list.insert_after(one, 2);
+    /// assert_eq!(list.list().to_string(), "[1, 2]");
+    /// ```
+    pub fn insert_after(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        let index = self.list.insert_after(index, item)?;
+        self.record(Change::Inserted { slot: index.slot() });
+        Some(index)
+    }
+
+    /// Removes the element at the given index, keeping it in the history, and returns
+    /// `true` if the index was valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let five = list.push_back(5);
+    /// assert!(list.remove(five));
+    /// assert!(list.list().is_empty());
+    /// ```
+    pub fn remove(&mut self, index: Index<T>) -> bool {
+        if !self.list.in_sequence(index) {
+            return false;
+        }
+        let prev = self.list.occupied(index.slot()).prev();
+        let Some(item) = self.list.remove(index) else {
+            return false;
+        };
+        self.record(Change::Removed {
+            slot: index.slot(),
+            generation: index.generation,
+            item,
+            prev,
+        });
+        true
+    }
+
+    /// Replaces the element at the given index, keeping the old one in the history, and
+    /// returns `true` if the index was valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let five = list.push_back(5);
+    /// assert!(list.replace(five, 6));
+    /// assert_eq!(list.list().get(five), Some(&6));
+    /// ```
+    pub fn replace(&mut self, index: Index<T>, item: T) -> bool {
+        let Some(item) = self.list.replace(index, item) else {
+            return false;
+        };
+        self.record(Change::Replaced {
+            slot: index.slot(),
+            item,
+        });
+        true
+    }
+
+    /// Moves the element at the given index right before `target`, and returns `true` on
+    /// success. All indices stay valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// assert!(list.move_before(two, one));
+    /// assert_eq!(list.list().to_string(), "[2, 1]");
+    /// ```
+    pub fn move_before(&mut self, index: Index<T>, target: Index<T>) -> bool {
+        let prev = self.prev_slot(index);
+        if !self.list.move_range_before(index, index, target) {
+            return false;
+        }
+        self.record(Change::Moved {
+            slot: index.slot(),
+            prev,
+        });
+        true
+    }
+
+    /// Moves the element at the given index right after `target`, and returns `true` on
+    /// success. All indices stay valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// assert!(list.move_after(one, two));
+    /// assert_eq!(list.list().to_string(), "[2, 1]");
+    /// ```
+    pub fn move_after(&mut self, index: Index<T>, target: Index<T>) -> bool {
+        let prev = self.prev_slot(index);
+        if !self.list.move_range_after(index, index, target) {
+            return false;
+        }
+        self.record(Change::Moved {
+            slot: index.slot(),
+            prev,
+        });
+        true
+    }
+
+    /// Ends the current step: the changes made since the last checkpoint are undone
+    /// together by the next [`JournaledIndexList::undo`]. Does nothing if there were no
+    /// changes.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// list.push_back(1);
+    /// list.checkpoint();
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// list.undo();
+    /// assert_eq!(list.list().to_string(), "[1]");
+    /// ```
+    pub fn checkpoint(&mut self) {
+        if !self.pending.is_empty() {
+            self.undo.push(std::mem::take(&mut self.pending));
+        }
+    }
+
+    /// Reverts the last step, ending the current one first, and returns `true` if there
+    /// was a step to revert.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// list.push_back(1);
+    /// assert!(list.undo());
+    /// assert!(list.list().is_empty());
+    /// assert!(!list.undo());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        self.checkpoint();
+        let Some(step) = self.undo.pop() else {
+            return false;
+        };
+        let step = self.revert(step);
+        self.redo.push(step);
+        true
+    }
+
+    /// Repeats the last step reverted by [`JournaledIndexList::undo`], and returns `true`
+    /// if there was one. Any other change to the list forgets the reverted steps.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// let one = list.push_back(1);
+    /// list.undo();
+    /// assert!(list.redo());
+    /// assert_eq!(list.list().get(one), Some(&1));
+    /// assert!(!list.redo());
+    /// ```
+    pub fn redo(&mut self) -> bool {
+        let Some(step) = self.redo.pop() else {
+            return false;
+        };
+        self.checkpoint();
+        let step = self.revert(step);
+        self.undo.push(step);
+        true
+    }
+
+    /// Returns `true` if there is a step that [`JournaledIndexList::undo`] can revert.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// assert!(!list.can_undo());
+    /// list.push_back(1);
+    /// assert!(list.can_undo());
+    /// ```
+    pub fn can_undo(&self) -> bool {
+        !self.pending.is_empty() || !self.undo.is_empty()
+    }
+
+    /// Returns `true` if there is a step that [`JournaledIndexList::redo`] can repeat.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// list.push_back(1);
+    /// list.undo();
+    /// assert!(list.can_redo());
+    /// ```
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets all steps, dropping the removed and replaced elements kept for them. The list
+    /// itself is left unchanged.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::journal::JournaledIndexList::new();
+    /// list.push_back(1);
+    /// list.clear_history();
+    /// assert!(!list.undo());
+    /// assert_eq!(list.list().len(), 1);
+    /// ```
+    pub fn clear_history(&mut self) {
+        self.pending.clear();
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn record(&mut self, change: Change<T>) {
+        self.redo.clear();
+        self.pending.push(change);
+    }
+
+    fn prev_slot(&self, index: Index<T>) -> Option<usize> {
+        self.list
+            .in_sequence(index)
+            .then(|| self.list.occupied(index.slot()).prev())
+            .flatten()
+    }
+
+    /// Reverts the changes of a step, last change first, and returns the step that reverts
+    /// them again.
+    fn revert(&mut self, step: Vec<Change<T>>) -> Vec<Change<T>> {
+        let list = &mut self.list;
+        let step = step
+            .into_iter()
+            .rev()
+            .map(|change| match change {
+                Change::Inserted { slot } => {
+                    let oc = list.occupied(slot);
+                    let (generation, prev) = (oc.generation, oc.prev());
+                    list.unlink_range(slot, slot);
+                    list.generation = list.generation.saturating_add(1);
+                    let item = list.free(slot);
+                    Change::Removed {
+                        slot,
+                        generation,
+                        item,
+                        prev,
+                    }
+                }
+                Change::Removed {
+                    slot,
+                    generation,
+                    item,
+                    prev,
+                } => {
+                    list.restore(slot, generation, item, prev);
+                    Change::Inserted { slot }
+                }
+                Change::Replaced { slot, item } => {
+                    let item = std::mem::replace(&mut list.occupied_mut(slot).item, item);
+                    Change::Replaced { slot, item }
+                }
+                Change::Moved { slot, prev } => {
+                    let current = list.occupied(slot).prev();
+                    list.unlink_range(slot, slot);
+                    list.link_at(prev, slot);
+                    Change::Moved {
+                        slot,
+                        prev: current,
+                    }
+                }
+            })
+            .collect();
+        list.debug_validate();
+        step
+    }
+}
+
+impl<T> IndexList<T> {
+    /// Puts `item` back into the free `slot` with the generation it had, and links it in
+    /// right after `prev`, or at the front for `None`.
+    ///
+    /// The slot is taken off the free list, which is walked from the slot freed last, so
+    /// restoring the elements removed last is fast.
+    fn restore(&mut self, slot: usize, generation: usize, item: T, prev: Option<usize>) {
+        self.claim_free_slot(slot);
+        self.contents[slot] = Occupied(OccupiedEntry {
+            item,
+            generation,
+            next: None,
+            prev: None,
+        });
+        self.count += 1;
+        self.stats.inserted(true);
+        self.link_at(prev, slot);
+    }
+
+    /// Takes the free `slot` off the free list, if it is on it.
+    fn claim_free_slot(&mut self, slot: usize) {
+        if let Some(ordered_free) = &mut self.ordered_free {
+            ordered_free.remove(&slot);
+            return;
+        }
+        let next_of = |list: &Self, slot: usize| match list.contents[slot] {
+            Free { next_free } => next_free.map(Slot::get),
+            _ => panic!("Corrupted list"),
+        };
+        let mut prev = None;
+        let mut iter = self.next_free;
+        while let Some(current) = iter {
+            let next = next_of(self, current);
+            if current == slot {
+                match prev {
+                    Some(prev) => {
+                        self.contents[prev] = Free {
+                            next_free: next.map(Slot::new),
+                        }
+                    }
+                    None => self.next_free = next,
+                }
+                return;
+            }
+            prev = Some(current);
+            iter = next;
+        }
+    }
+
+    /// Links the unlinked `slot` into the list right after `prev`, or at the front for
+    /// `None`.
+    fn link_at(&mut self, prev: Option<usize>, slot: usize) {
+        let next = match prev {
+            Some(prev) => self.occupied(prev).next(),
+            None => self.head,
+        };
+        self.splice(prev, next, slot, slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReusePolicy;
+
+    #[test]
+    fn undo_and_redo_restore_indices() {
+        let mut list = JournaledIndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        list.checkpoint();
+
+        list.remove(two);
+        let four = list.insert_after(one, 4).unwrap();
+        list.replace(three, 30);
+        list.move_before(three, one);
+        list.checkpoint();
+        let zero = list.push_front(0);
+        list.remove(one);
+        assert_eq!(list.list().to_string(), "[0, 30, 4]");
+
+        assert!(list.undo());
+        assert_eq!(list.list().to_string(), "[30, 1, 4]");
+        assert_eq!(list.list().get(zero), None);
+        assert!(list.undo());
+        assert_eq!(list.list().to_string(), "[1, 2, 3]");
+        assert_eq!(list.list().get(two), Some(&2));
+        assert_eq!(list.list().get(four), None);
+        assert_eq!(list.list().validate(), Ok(()));
+
+        assert!(list.redo());
+        assert_eq!(list.list().to_string(), "[30, 1, 4]");
+        assert_eq!(list.list().get(four), Some(&4));
+        assert_eq!(list.list().get(two), None);
+        assert!(list.redo());
+        assert!(!list.redo());
+        assert_eq!(list.list().to_string(), "[0, 30, 4]");
+        assert_eq!(list.list().get(zero), Some(&0));
+
+        list.undo();
+        list.push_back(5);
+        assert!(!list.can_redo());
+        assert_eq!(list.list().get(zero), None);
+        assert_eq!(list.list().validate(), Ok(()));
+    }
+
+    #[test]
+    fn undo_everything_with_either_reuse_policy() {
+        for policy in [ReusePolicy::LastFreedFirst, ReusePolicy::LowestIndexFirst] {
+            let mut inner = IndexList::new();
+            inner.set_reuse_policy(policy);
+            let mut list = JournaledIndexList::from(inner);
+            let indices: Vec<_> = (0..8).map(|item| list.push_back(item)).collect();
+            list.checkpoint();
+            for (step, &index) in indices.iter().enumerate().rev() {
+                if step % 2 == 0 {
+                    list.remove(index);
+                } else {
+                    list.move_after(index, indices[0]);
+                }
+                list.push_front(step * 10);
+                list.checkpoint();
+            }
+            while list.undo() {
+                assert_eq!(list.list().validate(), Ok(()));
+            }
+            assert!(list.list().is_empty());
+            while list.redo() {}
+            assert_eq!(list.list().validate(), Ok(()));
+            assert_eq!(list.list().len(), 12);
+        }
+    }
+}
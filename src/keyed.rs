@@ -0,0 +1,430 @@
+//! An `IndexList` of distinct values with a hash map on the side, for O(1) lookup by value.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Index, IndexList, Iter};
+
+/// `KeyedIndexList` is an [`IndexList`] of distinct values that additionally maps every value
+/// to its index, so that [`KeyedIndexList::index_of`] and [`KeyedIndexList::contains`] run in
+/// O(1) instead of O(n).
+///
+/// Every value is stored twice, once in the list and once as a key of the map, which is why
+/// it has to be `Clone`. Adding a value that is already in the list fails and gives the value
+/// back. Elements cannot be changed in place, since that would leave the map out of date.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::KeyedIndexList;
+///
+/// let mut list = KeyedIndexList::new();
+/// let a = list.push_back("a".to_string()).unwrap();
+/// list.push_back("b".to_string()).unwrap();
+/// assert!(list.push_back("a".to_string()).is_err());
+///
+/// assert_eq!(list.index_of("a"), Some(a));
+/// assert_eq!(list.remove_item("b"), Some("b".to_string()));
+/// assert!(!list.contains("b"));
+/// ```
+#[derive(Debug)]
+pub struct KeyedIndexList<T> {
+    list: IndexList<T>,
+    lookup: HashMap<T, Index<T>>,
+}
+
+impl<T> Default for KeyedIndexList<T> {
+    fn default() -> Self {
+        KeyedIndexList {
+            list: IndexList::default(),
+            lookup: HashMap::default(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> KeyedIndexList<T> {
+    /// Creates a new, empty `KeyedIndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::KeyedIndexList;
+    /// let list: KeyedIndexList<i32> = KeyedIndexList::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new, empty `KeyedIndexList` with the specified capacity.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::KeyedIndexList;
+    /// let list: KeyedIndexList<i32> = KeyedIndexList::with_capacity(10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        KeyedIndexList {
+            list: IndexList::with_capacity(capacity),
+            lookup: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the underlying `IndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// assert_eq!(list.as_list().len(), 1);
+    /// ```
+    pub fn as_list(&self) -> &IndexList<T> {
+        &self.list
+    }
+
+    /// Consumes the `KeyedIndexList` and returns the underlying `IndexList`. All indices
+    /// remain valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// let list = list.into_list();
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn into_list(self) -> IndexList<T> {
+        self.list
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the list contains no elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list: indexlist::KeyedIndexList<i32> = indexlist::KeyedIndexList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns a reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let index = list.push_back(5).unwrap();
+    /// assert_eq!(list.get(index), Some(&5));
+    /// ```
+    pub fn get(&self, index: Index<T>) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Returns `true` if the index refers to an element that is still in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let index = list.push_back(5).unwrap();
+    /// list.remove(index);
+    /// assert!(!list.contains_index(index));
+    /// ```
+    pub fn contains_index(&self, index: Index<T>) -> bool {
+        self.list.contains_index(index)
+    }
+
+    /// Returns the index of `item`, if it is in the list. This takes O(1) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.index_of(&5), Some(five));
+    /// assert_eq!(list.index_of(&6), None);
+    /// ```
+    pub fn index_of<Q>(&self, item: &Q) -> Option<Index<T>>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lookup.get(item).copied()
+    }
+
+    /// Returns `true` if `item` is in the list. This takes O(1) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// assert!(list.contains(&5));
+    /// ```
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lookup.contains_key(item)
+    }
+
+    /// Returns the generational index of the first element in the list, or `None` if the
+    /// list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.head_index(), Some(five));
+    /// ```
+    pub fn head_index(&self) -> Option<Index<T>> {
+        self.list.head_index()
+    }
+
+    /// Returns the generational index of the last element in the list, or `None` if the
+    /// list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.tail_index(), Some(five));
+    /// ```
+    pub fn tail_index(&self) -> Option<Index<T>> {
+        self.list.tail_index()
+    }
+
+    /// Returns the next index after the given one, or `None` if it is the last element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// let ten = list.push_back(10).unwrap();
+    /// assert_eq!(list.next_index(five), Some(ten));
+    /// ```
+    pub fn next_index(&self, index: Index<T>) -> Option<Index<T>> {
+        self.list.next_index(index)
+    }
+
+    /// Returns the previous index before the given one, or `None` if it is the first element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// let ten = list.push_back(10).unwrap();
+    /// assert_eq!(list.prev_index(ten), Some(five));
+    /// ```
+    pub fn prev_index(&self, index: Index<T>) -> Option<Index<T>> {
+        self.list.prev_index(index)
+    }
+
+    /// Appends an element to the back of the list and returns its index, or gives it back if
+    /// it is already in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.push_back(5), Err(5));
+    /// ```
+    pub fn push_back(&mut self, item: T) -> Result<Index<T>, T> {
+        if self.lookup.contains_key(&item) {
+            return Err(item);
+        }
+        let index = self.list.push_back(item.clone());
+        self.lookup.insert(item, index);
+        Ok(index)
+    }
+
+    /// Adds an element to the front of the list and returns its index, or gives it back if it
+    /// is already in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// let four = list.push_front(4).unwrap();
+    /// assert_eq!(list.head_index(), Some(four));
+    /// ```
+    pub fn push_front(&mut self, item: T) -> Result<Index<T>, T> {
+        if self.lookup.contains_key(&item) {
+            return Err(item);
+        }
+        let index = self.list.push_front(item.clone());
+        self.lookup.insert(item, index);
+        Ok(index)
+    }
+
+    /// Inserts an element before the specified index and returns its index. Gives the element
+    /// back if it is already in the list or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let ten = list.push_back(10).unwrap();
+    /// let five = list.insert_before(ten, 5).unwrap();
+    /// assert_eq!(list.next_index(five), Some(ten));
+    /// ```
+    pub fn insert_before(&mut self, index: Index<T>, item: T) -> Result<Index<T>, T> {
+        if self.lookup.contains_key(&item) || !self.list.contains_index(index) {
+            return Err(item);
+        }
+        let index = self
+            .list
+            .insert_before(index, item.clone())
+            .expect("index was checked");
+        self.lookup.insert(item, index);
+        Ok(index)
+    }
+
+    /// Inserts an element after the specified index and returns its index. Gives the element
+    /// back if it is already in the list or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// let ten = list.insert_after(five, 10).unwrap();
+    /// assert_eq!(list.prev_index(ten), Some(five));
+    /// ```
+    pub fn insert_after(&mut self, index: Index<T>, item: T) -> Result<Index<T>, T> {
+        if self.lookup.contains_key(&item) || !self.list.contains_index(index) {
+            return Err(item);
+        }
+        let index = self
+            .list
+            .insert_after(index, item.clone())
+            .expect("index was checked");
+        self.lookup.insert(item, index);
+        Ok(index)
+    }
+
+    /// Removes the first element from the list and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// assert_eq!(list.pop_front(), Some(5));
+    /// assert!(!list.contains(&5));
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        let item = self.list.pop_front()?;
+        self.lookup.remove(&item);
+        Some(item)
+    }
+
+    /// Removes the last element from the list and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// list.push_back(10).unwrap();
+    /// assert_eq!(list.pop_back(), Some(10));
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        let item = self.list.pop_back()?;
+        self.lookup.remove(&item);
+        Some(item)
+    }
+
+    /// Removes the element at the given index and returns it, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// let five = list.push_back(5).unwrap();
+    /// assert_eq!(list.remove(five), Some(5));
+    /// assert_eq!(list.index_of(&5), None);
+    /// ```
+    pub fn remove(&mut self, index: Index<T>) -> Option<T> {
+        let item = self.list.remove(index)?;
+        self.lookup.remove(&item);
+        Some(item)
+    }
+
+    /// Removes `item` from the list and returns the stored element, if it is in the list.
+    /// This takes O(1) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// assert_eq!(list.remove_item(&5), Some(5));
+    /// assert_eq!(list.remove_item(&5), None);
+    /// ```
+    pub fn remove_item<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.lookup.remove(item)?;
+        self.list.remove(index)
+    }
+
+    /// Returns an iterator over the elements in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::KeyedIndexList::new();
+    /// list.push_back(5).unwrap();
+    /// list.push_front(4).unwrap();
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![4, 5]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_follows_every_change() {
+        let mut list = KeyedIndexList::new();
+        let b = list.push_back('b').unwrap();
+        let a = list.push_front('a').unwrap();
+        let d = list.push_back('d').unwrap();
+        let c = list.insert_before(d, 'c').unwrap();
+        assert_eq!(list.insert_after(a, 'c'), Err('c'));
+        assert_eq!(
+            ['a', 'b', 'c', 'd'].map(|item| list.index_of(&item)),
+            [Some(a), Some(b), Some(c), Some(d)]
+        );
+
+        assert_eq!(list.pop_front(), Some('a'));
+        assert_eq!(list.pop_back(), Some('d'));
+        assert_eq!(list.remove(b), Some('b'));
+        assert_eq!(list.remove_item(&'c'), Some('c'));
+        assert!(list.is_empty());
+        assert!(list.lookup.is_empty());
+
+        let a = list.push_back('a').unwrap();
+        assert_eq!(list.index_of(&'a'), Some(a));
+        assert_eq!(list.as_list().validate(), Ok(()));
+    }
+
+    #[test]
+    fn stale_index_does_not_remove_a_newer_value() {
+        let mut list = KeyedIndexList::new();
+        let one = list.push_back(1).unwrap();
+        list.remove(one);
+        let two = list.push_back(2).unwrap();
+        assert_eq!(list.remove(one), None);
+        assert_eq!(list.insert_after(one, 3), Err(3));
+        assert_eq!(list.index_of(&2), Some(two));
+        assert_eq!(list.len(), 1);
+    }
+}
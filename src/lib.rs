@@ -1,132 +1,323 @@
-/// `IndexList` is a high-performance, doubly-linked list implementation that allows
-/// efficient insertion, deletion, and iteration over elements.
-/// It uses std::Vec internally. The underlying vector only grows, never shrinks.
-///
-/// # Generational Index
+//! `IndexList` is a high-performance, doubly-linked list implementation that allows
+//! efficient insertion, deletion, and iteration over elements.
+//! It uses std::Vec internally. The underlying vector only grows, never shrinks.
+//!
+//! # Generational Index
+//!
+//! `IndexList` uses a generational index system to ensure strong ownership semantics
+//!  and prevent dangling references.
+//! This system prevents access to elements that have been removed but not yet
+//! deallocated by tracking the generation of each element.
+//!
+//! The list moves to a new generation on every removal. The generation counter saturates
+//! instead of wrapping around, and a slot whose element was removed in the last generation
+//! is never reused, so a stale index can never refer to a newer element.
+//!
+//! # Examples
+//!
+//! ## Creating and using an `IndexList`
+//!
+//! ```rust
+//! use indexlist::IndexList;
+//!
+//! let mut list = IndexList::new();
+//! list.push_back(5);
+//! list.push_back(10);
+//! assert_eq!(list.len(), 2);
+//!
+//! if let Some(index) = list.index_of(&5) {
+//!     list.remove(index);
+//! }
+//! assert_eq!(list.len(), 1);
+//! ```
+//!
+//! ## Iterating over an `IndexList`
+//!
+//! ```rust
+//! use indexlist::IndexList;
+//!
+//! let mut list = IndexList::new();
+//! list.push_back(1);
+//! list.push_back(2);
+//! list.push_back(3);
+//!
+//! for item in &list {
+//!     println!("{}", *item);
+//! }
+//!
+//! // Output:
+//! // 1
+//! // 2
+//! // 3
+//! ```
+//!
+//! ## Modifying elements with `IndexList`
+//!
+//! ```rust
+//! use indexlist::IndexList;
+//!
+//! let mut list = IndexList::new();
+//! let index = list.push_back(5);
+//!
+//! if let Some(item) = list.get_mut(index) {
+//!     *item += 1;
+//! }
+//!
+//! assert_eq!(list.len(), 1);
+//! assert_eq!(*list.get(index).unwrap(), 6);
+//! ```
+//!
+//! ## Inserting elements before and after other elements
+//!
+//! ```rust
+//! use indexlist::IndexList;
+//!
+//! let mut list = IndexList::new();
+//! let head = list.push_back(1);
+//! let tail = list.push_back(3);
+//!
+//! // Insert 2 before the tail
+//! list.insert_before(tail, 2);
+//! assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+//!
+//! // Insert 0 after the head
+//! list.insert_after(head, 0);
+//! assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 0, 2, 3]);
+//! ```
+//!
+//! ## Removing elements and checking for their absence
+//!
+//! ```rust
+//! use indexlist::IndexList;
+//!
+//! let mut list = IndexList::new();
+//! let index = list.push_back(5);
+//!
+//! assert!(list.contains(&5));
+//! list.remove(index);
+//! assert!(!list.contains(&5));
+//! ```
+
+#![deny(unsafe_code)]
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashSet, LinkedList, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
+
+#[cfg(feature = "allocator-api2")]
+pub use alloc::{Allocator, Global};
+#[cfg(not(feature = "allocator-api2"))]
+use alloc::{Allocator, Global};
+use storage::Storage;
+use Entry::{Free, Occupied};
+
+/// Emits a `tracing` event at the given level with the `tracing` feature, and compiles to
+/// nothing without it.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::$level!($($arg)*);
+    };
+}
+
+/// Creates an [`IndexList`] holding the given elements in order.
 ///
-/// `IndexList` uses a generational index system to ensure strong ownership semantics
-///  and prevent dangling references.
-/// This system prevents access to elements that have been removed but not yet
-/// deallocated by tracking the generation of each element.
+/// The storage is allocated once, with room for exactly the given elements, which take up
+/// consecutive slots.
 ///
 /// # Examples
-///
-/// ## Creating and using an `IndexList`
-///
-/// ```rust
-/// use indexlist::IndexList;
-///
-/// let mut list = IndexList::new();
-/// list.push_back(5);
-/// list.push_back(10);
-/// assert_eq!(list.len(), 2);
-///
-/// if let Some(index) = list.index_of(&5) {
-///     list.remove(index);
-/// }
-/// assert_eq!(list.len(), 1);
-/// ```
-///
-/// ## Iterating over an `IndexList`
-///
-/// ```rust
-/// use indexlist::IndexList;
-///
-/// let mut list = IndexList::new();
-/// list.push_back(1);
-/// list.push_back(2);
-/// list.push_back(3);
-///
-/// for item in &list {
-///     println!("{}", *item);
-/// }
-///
-/// // Output:
-/// // 1
-/// // 2
-/// // 3
-/// ```
-///
-/// ## Modifying elements with `IndexList`
-///
-/// ```rust
-/// use indexlist::IndexList;
-///
-/// let mut list = IndexList::new();
-/// let index = list.push_back(5);
-///
-/// if let Some(item) = list.get_mut(index) {
-///     *item += 1;
-/// }
-///
-/// assert_eq!(list.len(), 1);
-/// assert_eq!(*list.get(index).unwrap(), 6);
-/// ```
-///
-/// ## Inserting elements before and after other elements
-///
 /// ```rust
-/// use indexlist::IndexList;
+/// use indexlist::indexlist;
 ///
-/// let mut list = IndexList::new();
-/// let head = list.push_back(1);
-/// let tail = list.push_back(3);
-///
-/// // Insert 2 before the tail
-/// list.insert_before(tail, 2);
+/// let list = indexlist![1, 2, 3];
 /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
 ///
-/// // Insert 0 after the head
-/// list.insert_after(head, 0);
-/// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 0, 2, 3]);
-/// ```
-///
-/// ## Removing elements and checking for their absence
-///
-/// ```rust
-/// use indexlist::IndexList;
-///
-/// let mut list = IndexList::new();
-/// let index = list.push_back(5);
-///
-/// assert!(list.contains(&5));
-/// list.remove(index);
-/// assert!(!list.contains(&5));
+/// let empty: indexlist::IndexList<i32> = indexlist![];
+/// assert!(empty.is_empty());
 /// ```
-///
-// #![deny(unsafe_code)]
-use std::marker::PhantomData;
-use Entry::{Free, Occupied};
+#[macro_export]
+macro_rules! indexlist {
+    () => {
+        $crate::IndexList::new()
+    };
+    ($($item:expr),+ $(,)?) => {{
+        let items = [$($item),+];
+        let mut list = $crate::IndexList::with_capacity(items.len());
+        for item in items {
+            list.push_back(item);
+        }
+        list
+    }};
+}
+
+mod alloc;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+pub mod concurrent;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixed;
+pub mod frozen;
+mod index_type;
+mod indexed;
+pub mod journal;
+mod keyed;
+#[cfg(feature = "lru")]
+pub mod lru;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+mod raw;
+#[cfg(feature = "rkyv")]
+mod rkyv;
+mod secondary;
+#[cfg(feature = "serde")]
+mod serde;
+pub mod shared;
+pub mod small;
+mod storage;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+#[cfg(feature = "unchecked")]
+mod unchecked;
+pub mod unrolled;
+
+pub use error::{AccessError, InvariantError, TryInsertError};
+pub use index_type::IndexType;
+pub use indexed::IndexedList;
+pub use keyed::KeyedIndexList;
+pub use raw::{RawEntry, RawParts};
+pub use secondary::SecondaryMap;
 
 /// A doubly linked list, backed by a vector.
-#[derive(Debug, PartialEq)]
-pub struct IndexList<T> {
-    contents: Vec<Entry<T>>,
-    generation: usize,
+///
+/// Slots, links and generations are stored as `I`, see [`IndexType`]. Lists of the default
+/// `usize` are created with [`IndexList::new`], others with [`Default::default`].
+///
+/// The vector is allocated from `A`, the global allocator by default. With the
+/// `allocator-api2` feature, use `IndexList::new_in` or `IndexList::with_capacity_in` to
+/// keep a list in another allocator, such as an arena.
+#[derive(Debug)]
+#[cfg_attr(feature = "rkyv", derive(::rkyv::Archive, ::rkyv::Serialize))]
+pub struct IndexList<T, I: IndexType = usize, A: Allocator + Clone = Global> {
+    id: ListId,
+    #[cfg_attr(feature = "rkyv", rkyv(with = crate::rkyv::Storage))]
+    contents: alloc::Vec<Entry<T, I>, A>,
+    #[cfg_attr(feature = "rkyv", rkyv(with = crate::rkyv::Wide))]
+    generation: I,
     next_free: Option<usize>,
+    /// The free slots when reusing them with `ReusePolicy::LowestIndexFirst`, which leaves
+    /// the free list above empty.
+    ordered_free: Option<BTreeSet<usize>>,
     head: Option<usize>,
     tail: Option<usize>,
     count: usize,
+    /// The elements taken out of the order with `detach`. They keep their slots, with both
+    /// links cleared, but are not part of `count`.
+    detached: usize,
+    /// The most elements `try_push_back` and `try_push_front` let the list hold.
+    max_capacity: Option<usize>,
+    #[cfg_attr(feature = "rkyv", rkyv(with = ::rkyv::with::Skip))]
+    finger: Finger,
+    #[cfg_attr(feature = "rkyv", rkyv(with = ::rkyv::with::Skip))]
+    stats: Stats,
 }
 
-#[derive(Debug, PartialEq)]
-enum Entry<T> {
-    Free { next_free: Option<usize> },
-    Occupied(OccupiedEntry<T>),
+/// The slot and position of the element last reached by a positional operation that takes
+/// `&mut self`, where positional lookups can start walking from. Any change to the links
+/// clears it.
+///
+/// Lookups through `&self` only read it, so that readers sharing a list never write to it.
+#[derive(Debug, Default, Clone, Copy)]
+struct Finger(Option<(usize, usize)>);
+
+impl Finger {
+    fn get(self) -> Option<(usize, usize)> {
+        self.0
+    }
+
+    fn set(&mut self, slot: usize, position: usize) {
+        self.0 = Some((slot, position));
+    }
+
+    fn clear(&mut self) {
+        self.0 = None;
+    }
 }
 
-#[derive(Debug, PartialEq)]
-struct OccupiedEntry<T> {
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(::rkyv::Archive, ::rkyv::Serialize, ::rkyv::Deserialize)
+)]
+enum Entry<T, I: IndexType = usize> {
+    Free { next_free: Option<Slot<I>> },
+    Occupied(OccupiedEntry<T, I>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(::rkyv::Archive, ::rkyv::Serialize, ::rkyv::Deserialize)
+)]
+struct OccupiedEntry<T, I: IndexType = usize> {
     item: T,
-    generation: usize,
-    next: Option<usize>,
-    prev: Option<usize>,
+    #[cfg_attr(feature = "rkyv", rkyv(with = crate::rkyv::Wide))]
+    generation: I,
+    next: Option<Slot<I>>,
+    prev: Option<Slot<I>>,
+}
+
+impl<T, I: IndexType> OccupiedEntry<T, I> {
+    fn next(&self) -> Option<usize> {
+        self.next.map(Slot::get)
+    }
+
+    fn prev(&self) -> Option<usize> {
+        self.prev.map(Slot::get)
+    }
+
+    fn set_next(&mut self, next: Option<usize>) {
+        self.next = next.map(Slot::new);
+    }
+
+    fn set_prev(&mut self, prev: Option<usize>) {
+        self.prev = prev.map(Slot::new);
+    }
+}
+
+/// A slot number as stored in links and indices. It holds the bitwise complement of the slot
+/// in a `NonZero`, so that `Option<Slot>` and `Option<Index<T>>` take no extra space.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Slot<I: IndexType = usize>(I::Stored);
+
+impl<I: IndexType> Slot<I> {
+    /// Panics if the list has outgrown the index type.
+    fn new(slot: usize) -> Self {
+        Slot::checked(slot).expect("IndexList exceeded the maximum number of slots")
+    }
+
+    fn checked(slot: usize) -> Option<Self> {
+        I::store(slot).map(Slot)
+    }
+
+    fn get(self) -> usize {
+        I::load(self.0)
+    }
+}
+
+impl<I: IndexType> fmt::Debug for Slot<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
 }
 
 /// `Index` is a generational index used to reference elements in an `IndexList`.
 ///
 /// It contains the index of the element and its generation, which helps prevent access
-/// to elements that have been removed but not yet deallocated.
+/// to elements that have been removed but not yet deallocated. Both are stored as `I`, the
+/// index type of the list, see [`IndexType`].
 ///
 /// # Examples
 ///
@@ -139,41 +330,270 @@ struct OccupiedEntry<T> {
 /// assert_eq!(Some(five), index);
 /// ```
 #[derive(Debug, PartialEq)]
-pub struct Index<T> {
-    index: usize,
-    generation: usize,
+#[cfg_attr(
+    feature = "rkyv",
+    derive(::rkyv::Archive, ::rkyv::Serialize, ::rkyv::Deserialize)
+)]
+pub struct Index<T, I: IndexType = usize> {
+    index: Slot<I>,
+    #[cfg_attr(feature = "rkyv", rkyv(with = crate::rkyv::Wide))]
+    generation: I,
+    list: ListId,
     _marker: PhantomData<T>,
 }
 
-impl<T> Index<T> {
-    fn new(index: usize, generation: usize) -> Self {
+impl<T, I: IndexType> Index<T, I> {
+    fn new(slot: usize, generation: I, list: ListId) -> Self {
         Index {
-            index,
+            index: Slot::new(slot),
             generation,
+            list,
+            _marker: PhantomData,
+        }
+    }
+
+    fn slot(self) -> usize {
+        self.index.get()
+    }
+
+    /// Returns the slot and generation of the index as plain integers, for passing it
+    /// through systems that can only carry numbers. Use [`IndexList::index_from_raw`] to
+    /// turn them back into an index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let (slot, generation) = five.into_raw();
+    /// assert_eq!(list.index_from_raw(slot, generation), Some(five));
+    /// ```
+    pub fn into_raw(self) -> (usize, usize) {
+        (self.slot(), self.generation.to_usize())
+    }
+
+    /// Converts the index into an index of the same element in a list created from its list
+    /// by [`IndexList::map`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let names = list.map(|item| item.to_string());
+    /// assert_eq!(names.get(five.cast()), Some(&"5".to_string()));
+    /// ```
+    pub fn cast<U>(self) -> Index<U, I> {
+        Index {
+            index: self.index,
+            generation: self.generation,
+            list: self.list,
             _marker: PhantomData,
         }
     }
+
+    /// Returns `true` if the index still refers to an element of `list`, the same as
+    /// [`IndexList::contains_index`]. Handy for pruning stale indices from side tables.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// list.remove(five);
+    /// let mut handles = vec![five, ten];
+    /// handles.retain(|index| index.is_current(&list));
+    /// assert_eq!(handles, vec![ten]);
+    /// ```
+    pub fn is_current<A: Allocator + Clone>(self, list: &IndexList<T, I, A>) -> bool {
+        list.contains_index(self)
+    }
+}
+
+/// Identifies the list that handed out an `Index`, so that indices from other lists can be
+/// rejected. Only carries an id with the `list-id` feature, otherwise it is zero-sized.
+#[cfg(feature = "list-id")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rkyv", derive(::rkyv::Archive, ::rkyv::Serialize))]
+struct ListId(u64);
+
+#[cfg(not(feature = "list-id"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "rkyv", derive(::rkyv::Archive, ::rkyv::Serialize))]
+struct ListId;
+
+#[cfg(feature = "list-id")]
+static NEXT_LIST_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+impl ListId {
+    #[cfg(feature = "list-id")]
+    fn next() -> Self {
+        ListId(NEXT_LIST_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+    }
+
+    /// Recreates a deserialized id, making sure that no list created from now on gets it.
+    /// Every list loaded from the same save gets the same id back.
+    #[cfg(feature = "list-id")]
+    fn restore(id: u64) -> Self {
+        NEXT_LIST_ID.fetch_max(id.saturating_add(1), std::sync::atomic::Ordering::Relaxed);
+        ListId(id)
+    }
+
+    #[cfg(not(feature = "list-id"))]
+    fn next() -> Self {
+        ListId
+    }
 }
 
-impl<T> Clone for Index<T> {
+impl<T, I: IndexType> Clone for Index<T, I> {
     fn clone(&self) -> Self {
         *self
     }
 }
 
-impl<T> Copy for Index<T> {}
+impl<T, I: IndexType> Copy for Index<T, I> {}
+
+impl<T: PartialEq, I: IndexType, A: Allocator + Clone> PartialEq for IndexList<T, I, A> {
+    // The list id is deliberately left out, it only identifies where indices came from.
+    fn eq(&self, other: &Self) -> bool {
+        self.contents == other.contents
+            && self.generation == other.generation
+            && self.next_free == other.next_free
+            && self.ordered_free == other.ordered_free
+            && self.head == other.head
+            && self.tail == other.tail
+            && self.count == other.count
+            && self.max_capacity == other.max_capacity
+    }
+}
+
+/// The clone is laid out exactly like the original, so indices into the original can be used
+/// with it. The operation counts of the `stats` feature start over.
+impl<T: Clone, I: IndexType, A: Allocator + Clone> Clone for IndexList<T, I, A> {
+    fn clone(&self) -> Self {
+        let mut list = self.empty_like(self.contents.len());
+        list.clone_from(self);
+        list.stats = Stats::default();
+        list
+    }
+
+    /// Overwrites the list with a copy of `source`, reusing its storage: elements are cloned
+    /// into the slots that are occupied in both lists with [`Clone::clone_from`], and the
+    /// vector only grows if `source` has more slots.
+    fn clone_from(&mut self, source: &Self) {
+        self.contents.truncate(source.contents.len());
+        for (entry, source) in self.contents.iter_mut().zip(source.contents.iter()) {
+            match (entry, source) {
+                (Occupied(oc), Occupied(source)) => {
+                    oc.item.clone_from(&source.item);
+                    oc.generation = source.generation;
+                    oc.next = source.next;
+                    oc.prev = source.prev;
+                }
+                (entry, source) => *entry = source.clone(),
+            }
+        }
+        let len = self.contents.len();
+        self.contents.extend(source.contents[len..].iter().cloned());
+        self.id = source.id;
+        self.generation = source.generation;
+        self.next_free = source.next_free;
+        self.ordered_free.clone_from(&source.ordered_free);
+        self.head = source.head;
+        self.tail = source.tail;
+        self.count = source.count;
+        self.detached = source.detached;
+        self.max_capacity = source.max_capacity;
+        self.finger.clear();
+    }
+}
+
+/// Which free slot an `IndexList` reuses for a new element, see
+/// [`IndexList::set_reuse_policy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReusePolicy {
+    /// Reuse the slot that was freed last. This takes O(1) time.
+    #[default]
+    LastFreedFirst,
+    /// Reuse the free slot closest to the start of the underlying vector, which keeps the
+    /// elements of a long-lived list packed together. This takes O(log n) time.
+    LowestIndexFirst,
+}
+
+/// Counts of the operations performed on an `IndexList` since it was created, as returned
+/// by [`IndexList::ops_stats`].
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct OpsStats {
+    /// The number of elements inserted, by any method.
+    pub insertions: u64,
+    /// The number of elements removed, by any method.
+    pub removals: u64,
+    /// The number of insertions that reused a free slot instead of taking a new one.
+    pub slot_reuses: u64,
+    /// The number of times the underlying vector was reallocated to grow.
+    pub reallocations: u64,
+}
+
+/// The operation counts of a list with the `stats` feature, zero-sized without it.
+#[derive(Debug, Clone, Copy, Default)]
+struct Stats {
+    #[cfg(feature = "stats")]
+    counts: OpsStats,
+}
+
+impl Stats {
+    #[inline]
+    fn inserted(&mut self, reused: bool) {
+        #[cfg(feature = "stats")]
+        {
+            self.counts.insertions += 1;
+            self.counts.slot_reuses += u64::from(reused);
+        }
+        #[cfg(not(feature = "stats"))]
+        let _ = reused;
+    }
+
+    #[inline]
+    fn removed(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.counts.removals += 1;
+        }
+    }
+
+    #[inline]
+    fn grew(&mut self) {
+        #[cfg(feature = "stats")]
+        {
+            self.counts.reallocations += 1;
+        }
+    }
+}
+
+/// A snapshot of the memory held by an `IndexList`, as returned by
+/// [`IndexList::memory_usage`].
+///
+/// The byte counts only cover the list's own vector, not memory owned by the elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The number of bytes allocated for the underlying vector.
+    pub allocated_bytes: usize,
+    /// The number of bytes taken up by the slots of the elements in the list.
+    pub live_bytes: usize,
+    /// The number of slots in use, either occupied or free.
+    pub slots: usize,
+    /// The number of elements in the list.
+    pub live: usize,
+    /// The number of free slots, waiting to be reused.
+    pub free: usize,
+    /// The number of free slots that are never reused, because their element was removed
+    /// once the generation had saturated.
+    pub quarantined: usize,
+}
 
-impl<T> Default for IndexList<T> {
+impl<T, I: IndexType, A: Allocator + Clone + Default> Default for IndexList<T, I, A> {
     // Note: #[derive(Default)] issue. https://github.com/rust-lang/rust/issues/26925
     fn default() -> Self {
-        IndexList {
-            contents: Default::default(),
-            generation: Default::default(),
-            next_free: Default::default(),
-            head: Default::default(),
-            tail: Default::default(),
-            count: Default::default(),
-        }
+        Self::empty_in(0, A::default())
     }
 }
 
@@ -197,12 +617,136 @@ impl<T> IndexList<T> {
     /// let list: IndexList<i32> = IndexList::with_capacity(10);
     /// ```
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::empty_in(capacity, Global)
+    }
+
+    /// Creates a new, empty `IndexList` with room for `max_capacity` elements, which
+    /// [`IndexList::try_push_back`] and [`IndexList::try_push_front`] refuse to grow beyond.
+    /// The other methods that add elements ignore the bound.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::IndexList;
+    /// let mut list = IndexList::with_max_capacity(1);
+    /// assert!(list.try_push_back(5).is_ok());
+    /// assert_eq!(list.try_push_back(6), Err(6));
+    /// ```
+    pub fn with_max_capacity(max_capacity: usize) -> Self {
         Self {
-            contents: Vec::with_capacity(capacity),
-            ..Self::default()
+            max_capacity: Some(max_capacity),
+            ..Self::with_capacity(max_capacity)
+        }
+    }
+
+    /// Creates a list of the elements of all `lists`, one list after the other.
+    ///
+    /// The storage of the new list is allocated once, for the elements of all lists, and
+    /// filled in list order. Indices into the given lists are not valid for the new one.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::IndexList;
+    /// let mut first = IndexList::new();
+    /// first.push_back(1);
+    /// first.push_back(2);
+    /// let mut second = IndexList::new();
+    /// second.push_back(3);
+    /// let list = IndexList::concat([first, IndexList::new(), second]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn concat(lists: impl IntoIterator<Item = IndexList<T>>) -> Self {
+        let lists: Vec<IndexList<T>> = lists.into_iter().collect();
+        let mut list = Self::with_capacity(lists.iter().map(IndexList::len).sum());
+        for other in lists {
+            list.insert_chain(list.tail, None, other.into_items());
+        }
+        list
+    }
+
+    /// Returns the bound set by [`IndexList::with_max_capacity`], or `None` if the list is
+    /// unbounded.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::IndexList;
+    /// assert_eq!(IndexList::<i32>::with_max_capacity(8).max_capacity(), Some(8));
+    /// assert_eq!(IndexList::<i32>::new().max_capacity(), None);
+    /// ```
+    pub fn max_capacity(&self) -> Option<usize> {
+        self.max_capacity
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> IndexList<T, I, A> {
+    /// Creates a new, empty `IndexList` whose storage is allocated from `alloc`.
+    ///
+    /// Requires the `allocator-api2` feature.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{Global, IndexList};
+    /// let mut list: IndexList<i32> = IndexList::new_in(Global);
+    /// list.push_back(5);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    #[cfg(feature = "allocator-api2")]
+    pub fn new_in(alloc: A) -> Self {
+        Self::empty_in(0, alloc)
+    }
+
+    /// Creates a new, empty `IndexList` with the specified capacity, whose storage is
+    /// allocated from `alloc`.
+    ///
+    /// Requires the `allocator-api2` feature.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{Global, IndexList};
+    /// let list: IndexList<i32> = IndexList::with_capacity_in(10, Global);
+    /// assert!(list.memory_usage().allocated_bytes > 0);
+    /// ```
+    #[cfg(feature = "allocator-api2")]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Self::empty_in(capacity, alloc)
+    }
+
+    /// Returns a reference to the allocator the list's storage is allocated from.
+    ///
+    /// Requires the `allocator-api2` feature.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{Global, IndexList};
+    /// let list: IndexList<i32> = IndexList::new_in(Global);
+    /// let _global: &Global = list.allocator();
+    /// ```
+    #[cfg(feature = "allocator-api2")]
+    pub fn allocator(&self) -> &A {
+        self.contents.allocator()
+    }
+
+    fn empty_in(capacity: usize, alloc: A) -> Self {
+        IndexList {
+            id: ListId::next(),
+            contents: alloc::Vec::with_capacity_in(capacity, alloc),
+            generation: Default::default(),
+            next_free: Default::default(),
+            ordered_free: Default::default(),
+            head: Default::default(),
+            tail: Default::default(),
+            count: Default::default(),
+            max_capacity: Default::default(),
+            detached: Default::default(),
+            finger: Finger::default(),
+            stats: Stats::default(),
         }
     }
 
+    /// Returns a new, empty list with room for `capacity` elements in the same allocator.
+    fn empty_like<U>(&self, capacity: usize) -> IndexList<U, I, A> {
+        IndexList::empty_in(capacity, self.contents.allocator().clone())
+    }
+
     /// Returns a reference to the first element in the list, or `None` if the list is empty.
     ///
     /// # Examples
@@ -249,9 +793,9 @@ impl<T> IndexList<T> {
     /// list.pop_front();
     /// assert!(list.head_index().is_none());
     /// ```
-    pub fn head_index(&self) -> Option<Index<T>> {
+    pub fn head_index(&self) -> Option<Index<T, I>> {
         self.contents.get(self.head?).and_then(|e| match e {
-            Occupied(oc) => Some(Index::new(self.head?, oc.generation)),
+            Occupied(oc) => Some(Index::new(self.head?, oc.generation, self.id)),
             _ => None,
         })
     }
@@ -302,135 +846,184 @@ impl<T> IndexList<T> {
     /// list.pop_front();
     /// assert!(list.tail_index().is_none());
     /// ```
-    pub fn tail_index(&self) -> Option<Index<T>> {
+    pub fn tail_index(&self) -> Option<Index<T, I>> {
         self.contents.get(self.tail?).and_then(|e| match e {
-            Occupied(oc) => Some(Index::new(self.tail?, oc.generation)),
+            Occupied(oc) => Some(Index::new(self.tail?, oc.generation, self.id)),
             _ => None,
         })
     }
 
-    /// Appends an element to the back of the list and returns its index.
+    /// Returns a reference to the first element in the list, or `None` if the list is empty.
+    /// Same as [`IndexList::head`], named like [`std::collections::VecDeque::front`].
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// let index = list.push_back(5);
-    /// assert_eq!(list.get(index), Some(&5));
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// assert_eq!(list.front(), Some(&5));
     /// ```
-    pub fn push_back(&mut self, item: T) -> Index<T> {
-        match self.next_free {
-            Some(index) => {
-                let next_free = match self.contents[index] {
-                    Free { next_free } => next_free,
-                    _ => panic!("Corrupted list"),
-                };
-                self.contents[index] = Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: None,
-                    prev: self.tail,
-                });
-                self.count += 1;
-                self.next_free = next_free;
-                if self.head.is_none() {
-                    self.head = Some(index);
-                }
-
-                if let Some(tail) = self.tail {
-                    if let Occupied(oc) = &mut self.contents[tail] {
-                        oc.next = Some(index);
-                    }
-                }
-
-                self.tail = Some(index);
-                Index::new(index, self.generation)
-            }
-            None => {
-                self.contents.push(Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: None,
-                    prev: self.tail,
-                }));
-                self.count += 1;
-                self.next_free = None;
-                let last = self.contents.len() - 1;
-                if self.head.is_none() {
-                    self.head = Some(0);
-                }
-
-                if let Some(tail) = self.tail {
-                    if let Occupied(oc) = &mut self.contents[tail] {
-                        oc.next = Some(last);
-                    }
-                }
-                self.tail = Some(last);
-
-                Index::new(last, self.generation)
-            }
-        }
+    pub fn front(&self) -> Option<&T> {
+        self.head()
     }
 
-    /// Appends an element to the front of the list and returns its index.
+    /// Returns a mutable reference to the first element in the list, or `None` if the list
+    /// is empty. Same as [`IndexList::head_mut`].
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// let index = list.push_front(5);
-    /// assert_eq!(list.get(index), Some(&5));
+    /// list.push_back(5);
+    /// *list.front_mut().unwrap() = 10;
+    /// assert_eq!(list.front(), Some(&10));
     /// ```
-    pub fn push_front(&mut self, item: T) -> Index<T> {
-        match self.next_free {
-            Some(index) => {
-                let next_free = match self.contents[index] {
-                    Free { next_free } => next_free,
-                    _ => panic!("Corrupted list"),
-                };
-                self.contents[index] = Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: self.head,
-                    prev: None,
-                });
-                self.count += 1;
-                self.next_free = next_free;
-                if self.tail.is_none() {
-                    self.tail = Some(index);
-                }
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head_mut()
+    }
 
-                if let Some(head) = self.head {
-                    if let Occupied(oc) = &mut self.contents[head] {
-                        oc.prev = Some(index);
-                    }
-                }
+    /// Returns a reference to the last element in the list, or `None` if the list is empty.
+    /// Same as [`IndexList::tail`], named like [`std::collections::VecDeque::back`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// assert_eq!(list.back(), Some(&6));
+    /// ```
+    pub fn back(&self) -> Option<&T> {
+        self.tail()
+    }
 
-                self.head = Some(index);
-                Index::new(index, self.generation)
-            }
-            None => {
-                self.contents.push(Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: self.head,
-                    prev: None,
-                }));
-                self.count += 1;
-                self.next_free = None;
-                let last = self.contents.len() - 1;
-                if self.tail.is_none() {
-                    self.tail = Some(0);
-                }
+    /// Returns a mutable reference to the last element in the list, or `None` if the list is
+    /// empty. Same as [`IndexList::tail_mut`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// *list.back_mut().unwrap() = 10;
+    /// assert_eq!(list.back(), Some(&10));
+    /// ```
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail_mut()
+    }
 
-                if let Some(head) = self.head {
-                    if let Occupied(oc) = &mut self.contents[head] {
-                        oc.prev = Some(last);
-                    }
-                }
-                self.head = Some(last);
+    /// Appends an element to the back of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let index = list.push_back(5);
+    /// assert_eq!(list.get(index), Some(&5));
+    /// ```
+    pub fn push_back(&mut self, item: T) -> Index<T, I> {
+        let slot = self.allocate(item);
+        match self.tail {
+            Some(tail) => self.link_after(tail, slot),
+            None => {
+                self.head = Some(slot);
+                self.tail = Some(slot);
+            }
+        }
+        self.debug_validate();
+        self.index_at(slot)
+    }
 
-                Index::new(last, self.generation)
+    /// Appends an element to the front of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let index = list.push_front(5);
+    /// assert_eq!(list.get(index), Some(&5));
+    /// ```
+    pub fn push_front(&mut self, item: T) -> Index<T, I> {
+        let slot = self.allocate(item);
+        match self.head {
+            Some(head) => self.link_before(head, slot),
+            None => {
+                self.head = Some(slot);
+                self.tail = Some(slot);
             }
         }
+        self.debug_validate();
+        self.index_at(slot)
+    }
+
+    /// Appends an element to the back of the list and returns its index, or gives the
+    /// element back if the list already holds its [maximum capacity] of elements.
+    ///
+    /// [maximum capacity]: IndexList::with_max_capacity
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::with_max_capacity(2);
+    /// list.try_push_back(1).unwrap();
+    /// list.try_push_back(2).unwrap();
+    /// assert_eq!(list.try_push_back(3), Err(3));
+    /// list.pop_front();
+    /// assert!(list.try_push_back(3).is_ok());
+    /// ```
+    pub fn try_push_back(&mut self, item: T) -> Result<Index<T, I>, T> {
+        if self.is_full() {
+            return Err(item);
+        }
+        Ok(self.push_back(item))
+    }
+
+    /// Adds an element to the front of the list and returns its index, or gives the element
+    /// back if the list already holds its [maximum capacity] of elements.
+    ///
+    /// [maximum capacity]: IndexList::with_max_capacity
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::with_max_capacity(1);
+    /// list.try_push_front(1).unwrap();
+    /// assert_eq!(list.try_push_front(0), Err(0));
+    /// ```
+    pub fn try_push_front(&mut self, item: T) -> Result<Index<T, I>, T> {
+        if self.is_full() {
+            return Err(item);
+        }
+        Ok(self.push_front(item))
+    }
+
+    /// Adds all elements of `items`, in order, to the back of the list and returns their
+    /// indices.
+    ///
+    /// The new elements are chained together first and then linked in after the tail at
+    /// once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let indices = list.push_back_many([2, 3]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// assert_eq!(list.tail_index(), Some(indices[1]));
+    /// ```
+    pub fn push_back_many(&mut self, items: impl IntoIterator<Item = T>) -> Vec<Index<T, I>> {
+        self.insert_chain(self.tail, None, items)
+    }
+
+    /// Adds all elements of `items` to the front of the list, keeping their order, and
+    /// returns their indices.
+    ///
+    /// The new elements are chained together first and then linked in before the head at
+    /// once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(3);
+    /// let indices = list.push_front_many([1, 2]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// assert_eq!(list.head_index(), Some(indices[0]));
+    /// ```
+    pub fn push_front_many(&mut self, items: impl IntoIterator<Item = T>) -> Vec<Index<T, I>> {
+        self.insert_chain(None, self.head, items)
     }
 
     /// Removes the last element from the list and returns it.
@@ -444,7 +1037,7 @@ impl<T> IndexList<T> {
     pub fn pop_back(&mut self) -> Option<T> {
         if let Some(tail) = self.tail {
             let index = self.contents.get(tail).map(|e| match e {
-                Occupied(oc) => Index::new(tail, oc.generation),
+                Occupied(oc) => Index::new(tail, oc.generation, self.id),
                 _ => panic!("Corrupted list"),
             });
             self.remove(index?)
@@ -464,7 +1057,7 @@ impl<T> IndexList<T> {
     pub fn pop_front(&mut self) -> Option<T> {
         if let Some(head) = self.head {
             let index = self.contents.get(head).map(|e| match e {
-                Occupied(oc) => Index::new(head, oc.generation),
+                Occupied(oc) => Index::new(head, oc.generation, self.id),
                 _ => panic!("Corrupted list"),
             });
             self.remove(index?)
@@ -473,28 +1066,113 @@ impl<T> IndexList<T> {
         }
     }
 
+    /// Removes the last element from the list and returns it together with the index it
+    /// had, which is no longer valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.pop_back_with_index(), Some((five, 5)));
+    /// assert_eq!(list.pop_back_with_index(), None);
+    /// ```
+    pub fn pop_back_with_index(&mut self) -> Option<(Index<T, I>, T)> {
+        let index = self.tail_index()?;
+        self.remove(index).map(|item| (index, item))
+    }
+
+    /// Removes the first element from the list and returns it together with the index it
+    /// had, which is no longer valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// list.push_back(10);
+    /// assert_eq!(list.pop_front_with_index(), Some((five, 5)));
+    /// assert!(!list.contains_index(five));
+    /// ```
+    pub fn pop_front_with_index(&mut self) -> Option<(Index<T, I>, T)> {
+        let index = self.head_index()?;
+        self.remove(index).map(|item| (index, item))
+    }
+
+    /// Returns `true` if the index refers to an element that is still in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let index = list.push_back(5);
+    /// assert!(list.contains_index(index));
+    /// list.remove(index);
+    /// assert!(!list.contains_index(index));
+    /// ```
+    pub fn contains_index(&self, index: Index<T, I>) -> bool {
+        self.check_index(index).is_ok()
+    }
+
+    /// Turns the slot and generation returned by [`Index::into_raw`] back into an index of
+    /// this list.
+    ///
+    /// Returns `None` unless they refer to an element that is currently in the list, so
+    /// garbage values and values of removed elements are rejected.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let (slot, generation) = five.into_raw();
+    /// assert_eq!(list.index_from_raw(slot, generation), Some(five));
+    /// assert_eq!(list.index_from_raw(slot + 1, generation), None);
+    /// list.remove(five);
+    /// assert_eq!(list.index_from_raw(slot, generation), None);
+    /// ```
+    pub fn index_from_raw(&self, slot: usize, generation: usize) -> Option<Index<T, I>> {
+        let generation = I::from_usize(generation)?;
+        Slot::<I>::checked(slot)?;
+        let index = Index::new(slot, generation, self.id);
+        self.contains_index(index).then_some(index)
+    }
+
     /// Returns a reference to the element at the given index, if it exists.
     ///
+    /// Returns `None` for an index whose element was removed, whether or not its slot has
+    /// been reused since.
+    ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
     /// let index = list.push_back(5);
     /// assert_eq!(list.get(index), Some(&5));
+    /// list.remove(index);
+    /// assert_eq!(list.get(index), None);
     /// ```
-    pub fn get(&self, index: Index<T>) -> Option<&T> {
-        self.contents.get(index.index).and_then(|e| match e {
-            Occupied(oc) => {
-                if oc.generation != index.generation {
-                    return None;
-                }
-                Some(&oc.item)
-            }
-            _ => panic!("Corrupted list"),
-        })
+    pub fn get(&self, index: Index<T, I>) -> Option<&T> {
+        self.try_get(index).ok()
+    }
+
+    /// Returns a reference to the element at the given index, or the reason the index is not
+    /// valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{AccessError, IndexList};
+    /// let mut list = IndexList::new();
+    /// let index = list.push_back(5);
+    /// assert_eq!(list.try_get(index), Ok(&5));
+    /// list.remove(index);
+    /// assert_eq!(list.try_get(index), Err(AccessError::FreedSlot));
+    /// ```
+    pub fn try_get(&self, index: Index<T, I>) -> Result<&T, AccessError> {
+        self.check_index(index)?;
+        Ok(&self.occupied(index.slot()).item)
     }
 
     /// Returns a mutable reference to the element at the given index, if it exists.
     ///
+    /// Returns `None` for an index whose element was removed, whether or not its slot has
+    /// been reused since.
+    ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
@@ -503,1593 +1181,6762 @@ impl<T> IndexList<T> {
     /// *list.get_mut(index).unwrap() = 10;
     /// assert_eq!(list.get(index), Some(&10));
     /// ```
-    pub fn get_mut(&mut self, index: Index<T>) -> Option<&mut T> {
-        self.contents.get_mut(index.index).and_then(|e| match e {
-            Occupied(oc) => {
-                if oc.generation != index.generation {
-                    return None;
-                }
-                Some(&mut oc.item)
-            }
-            _ => panic!("Corrupted list"),
-        })
+    pub fn get_mut(&mut self, index: Index<T, I>) -> Option<&mut T> {
+        self.try_get_mut(index).ok()
     }
 
-    /// Returns the next index after the given one, or `None` if it is the last element.
+    /// Returns a mutable reference to the element at the given index, or the reason the
+    /// index is not valid.
     ///
     /// # Examples
     /// ```rust
-    /// let mut list = indexlist::IndexList::new();
-    /// let five_index = list.push_back(5);
-    /// let ten_index = list.push_back(10);
-    /// assert_eq!(list.next_index(five_index), Some(ten_index));
-    /// assert!(list.next_index(ten_index).is_none());
+    /// use indexlist::{AccessError, IndexList};
+    /// let mut list = IndexList::new();
+    /// let index = list.push_back(5);
+    /// *list.try_get_mut(index).unwrap() = 10;
+    /// list.remove(index);
+    /// list.push_back(20);
+    /// assert_eq!(list.try_get_mut(index), Err(AccessError::StaleGeneration));
     /// ```
-    pub fn next_index(&self, index: Index<T>) -> Option<Index<T>> {
-        match &self.contents.get(index.index)? {
-            Occupied(oc) => {
-                if index.generation != oc.generation {
-                    return None;
-                }
-                Some(Index::new(oc.next?, oc.generation))
-            }
-            _ => None,
-        }
+    pub fn try_get_mut(&mut self, index: Index<T, I>) -> Result<&mut T, AccessError> {
+        self.check_index(index)?;
+        Ok(&mut self.occupied_mut(index.slot()).item)
     }
 
-    /// Returns the previous index before the given one, or `None` if it is the first element.
+    /// Calls `f` with a mutable reference to the element at the given index, and returns
+    /// `true` if it did, or `false` if the index is not valid.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// let five_index = list.push_back(5);
-    /// let ten_index = list.push_back(10);
-    /// assert_eq!(list.prev_index(ten_index), Some(five_index));
-    /// assert!(list.prev_index(five_index).is_none());
+    /// let five = list.push_back(5);
+    /// assert!(list.update(five, |item| *item += 1));
+    /// assert_eq!(list.get(five), Some(&6));
+    /// list.remove(five);
+    /// assert!(!list.update(five, |item| *item += 1));
     /// ```
-    pub fn prev_index(&self, index: Index<T>) -> Option<Index<T>> {
-        match &self.contents.get(index.index)? {
-            Occupied(oc) => {
-                if index.generation != oc.generation {
-                    return None;
-                }
-                Some(Index::new(oc.prev?, oc.generation))
-            }
-            _ => None,
-        }
+    pub fn update<F>(&mut self, index: Index<T, I>, f: F) -> bool
+    where
+        F: FnOnce(&mut T),
+    {
+        self.get_mut(index).map(f).is_some()
     }
 
-    /// Removes the element at the given index and returns it.
+    /// Returns the index of and a reference to the element at `position` in list order, or
+    /// `None` if `position` is out of bounds.
+    ///
+    /// The list is walked from whichever end is closer to `position`, or from the position
+    /// last reached by [`IndexList::get_mut_by_position`], [`IndexList::insert_at`] or
+    /// [`IndexList::remove_at`] if that is closer still. Other changes to the list forget that
+    /// position. This lookup itself does not move it, see
+    /// [`IndexList::get_mut_by_position`] for stepping through nearby positions in amortized
+    /// O(1) per call.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// let five_index = list.push_back(5);
-    /// assert_eq!(list.remove(five_index), Some(5));
-    /// assert_eq!(list.len(), 0);
+    /// list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.get_by_position(1), Some((ten, &10)));
+    /// assert!(list.get_by_position(2).is_none());
     /// ```
-    pub fn remove(&mut self, index: Index<T>) -> Option<T> {
-        match self.contents.get_mut(index.index)? {
-            Occupied(oc) => {
-                if index.generation != oc.generation {
-                    return None;
-                }
-                let oc_next = oc.next;
-                let oc_prev = oc.prev;
-                if let Some(prev) = oc_prev {
-                    if let Some(e) = self.contents.get_mut(prev) {
-                        match e {
-                            Occupied(oc_prev) => oc_prev.next = oc_next,
-                            _ => panic!("Corrupted list"),
-                        }
-                    }
-                }
-                if let Some(next) = oc_next {
-                    if let Some(e) = self.contents.get_mut(next) {
-                        if let Occupied(oc_next) = e {
-                            oc_next.prev = oc_prev
-                        } else {
-                            panic!("Corrupted list")
-                        }
-                    }
-                }
-            }
-            _ => {
-                return None;
-            }
-        }
-
-        let current = self.contents.get_mut(index.index)?;
-        let mut free = Free {
-            next_free: self.next_free,
-        };
-        self.generation += 1;
-        self.count -= 1;
-        self.next_free = Some(index.index);
-
-        std::mem::swap(current, &mut free);
-        match free {
-            Occupied(oc) => {
-                if let Some(head_index) = self.head {
-                    if head_index == index.index {
-                        self.head = oc.next
-                    }
-                }
-                if let Some(tail_index) = self.tail {
-                    if tail_index == index.index {
-                        self.tail = oc.prev
-                    }
-                }
-
-                Some(oc.item)
-            }
-            _ => None,
-        }
+    pub fn get_by_position(&self, position: usize) -> Option<(Index<T, I>, &T)> {
+        let slot = self.slot_at(position)?;
+        Some((self.index_at(slot), &self.occupied(slot).item))
     }
 
-    /// Inserts an element before the specified index and returns its new index.
+    /// Returns the index of and a mutable reference to the element at `position` in list
+    /// order, or `None` if `position` is out of bounds.
+    ///
+    /// The list is walked like [`IndexList::get_by_position`] does, and the position is
+    /// remembered, so stepping through nearby positions takes amortized O(1) per call.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// let index = list.push_front(2);
-    /// list.insert_before(index, 1);
-    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2]);
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// for position in 0..list.len() {
+    ///     *list.get_mut_by_position(position).unwrap().1 += 1;
+    /// }
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![6, 11]);
     /// ```
-    pub fn insert_before(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
-        let oc_prev: Option<usize>;
-        let result: Option<Index<T>>;
-        let result_index: usize;
-
-        match self.contents.get_mut(index.index)? {
-            Occupied(oc) => {
-                if index.generation != oc.generation {
-                    return None;
-                }
-                oc_prev = oc.prev;
-            }
-            _ => {
-                return None;
-            }
-        }
-
-        match self.next_free {
-            Some(index_free) => {
-                let next_free = match self.contents[index_free] {
-                    Free { next_free } => next_free,
-                    _ => panic!("Corrupted list"),
-                };
-                self.contents[index_free] = Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: Some(index.index),
-                    prev: oc_prev,
-                });
-                self.count += 1;
-                self.next_free = next_free;
-
-                result_index = index_free;
-                result = Some(Index::new(result_index, self.generation));
-            }
-            None => {
-                self.contents.push(Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: Some(index.index),
-                    prev: oc_prev,
-                }));
-                self.count += 1;
-                self.next_free = None;
-
-                result_index = self.contents.len() - 1;
-                result = Some(Index::new(result_index, self.generation));
-            }
-        }
-
-        match self.contents.get_mut(index.index)? {
-            Occupied(oc) => {
-                oc.prev = Some(result_index);
-                if self.head == Some(index.index) {
-                    self.head = Some(result_index);
-                }
-            }
-            _ => {
-                return None;
-            }
-        }
-
-        match self.contents.get_mut(oc_prev?)? {
-            Occupied(oc) => {
-                oc.next = Some(result_index);
-            }
-            _ => {
-                return None;
-            }
-        }
-
-        result
+    pub fn get_mut_by_position(&mut self, position: usize) -> Option<(Index<T, I>, &mut T)> {
+        let slot = self.slot_at_mut(position)?;
+        Some((self.index_at(slot), &mut self.occupied_mut(slot).item))
     }
 
-    /// Inserts an element after the specified index and returns its new index.
+    /// Returns the position of the element at the given index in list order, or `None` if
+    /// the index is not valid.
+    ///
+    /// The list is walked towards both ends at once, so this takes time proportional to the
+    /// distance to the closer end.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// let index = list.push_front(2);
-    /// list.insert_after(index, 3);
-    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![2, 3]);
+    /// list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.position_of(ten), Some(1));
+    /// list.push_front(0);
+    /// assert_eq!(list.position_of(ten), Some(2));
     /// ```
-    pub fn insert_after(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
-        let oc_next: Option<usize>;
-        let result: Option<Index<T>>;
-        let result_index: usize;
-
-        match self.contents.get_mut(index.index)? {
-            Occupied(oc) => {
-                if index.generation != oc.generation {
-                    return None;
-                }
-                oc_next = oc.next;
-            }
-            _ => {
-                return None;
-            }
-        }
-
-        match self.next_free {
-            Some(index_free) => {
-                let next_free = match self.contents[index_free] {
-                    Free { next_free } => next_free,
-                    _ => panic!("Corrupted list"),
-                };
-                self.contents[index_free] = Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: oc_next,
-                    prev: Some(index.index),
-                });
-                self.count += 1;
-                self.next_free = next_free;
-
-                result_index = index_free;
-                result = Some(Index::new(result_index, self.generation));
-            }
-            None => {
-                self.contents.push(Occupied(OccupiedEntry {
-                    item,
-                    generation: self.generation,
-                    next: oc_next,
-                    prev: Some(index.index),
-                }));
-                self.count += 1;
-                self.next_free = None;
-
-                result_index = self.contents.len() - 1;
-                result = Some(Index::new(result_index, self.generation));
-            }
+    pub fn position_of(&self, index: Index<T, I>) -> Option<usize> {
+        if !self.in_sequence(index) {
+            return None;
         }
-
-        match self.contents.get_mut(index.index)? {
-            Occupied(oc) => {
-                oc.next = Some(result_index);
-                if self.tail == Some(index.index) {
-                    self.tail = Some(result_index);
-                }
+        let mut backward = index.slot();
+        let mut forward = index.slot();
+        for hops in 0..self.count {
+            match self.occupied(backward).prev() {
+                Some(prev) => backward = prev,
+                None => return Some(hops),
             }
-            _ => {
-                return None;
+            match self.occupied(forward).next() {
+                Some(next) => forward = next,
+                None => return Some(self.count - 1 - hops),
             }
         }
+        panic!("Corrupted list")
+    }
 
-        match self.contents.get_mut(oc_next?)? {
-            Occupied(oc) => {
-                oc.prev = Some(result_index);
-            }
-            _ => {
-                return None;
-            }
+    /// Returns `true` if the element at `a` comes before the element at `b` in the list, or
+    /// `None` if either index is not valid. An element does not come before itself.
+    ///
+    /// The list is walked outwards from `a` in both directions at once until `b` is found,
+    /// so this takes time proportional to the number of elements between the two. See
+    /// [`IndexedList::is_before`] for an O(log n) version.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.is_before(five, ten), Some(true));
+    /// assert_eq!(list.is_before(ten, five), Some(false));
+    /// list.remove(ten);
+    /// assert_eq!(list.is_before(five, ten), None);
+    /// ```
+    pub fn is_before(&self, a: Index<T, I>, b: Index<T, I>) -> Option<bool> {
+        if !self.in_sequence(a) || !self.in_sequence(b) {
+            return None;
         }
-
-        result
+        Some(self.hops(a.slot(), b.slot()) > 0)
     }
 
-    /// Returns the number of elements in the list.
+    /// Returns the signed number of hops from `a` to `b` in list order, or `None` if either
+    /// index is not valid. The result is negative if `b` comes before `a`.
+    ///
+    /// Like [`IndexList::is_before`], this walks outwards from `a` in both directions.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// list.push_back(5);
-    /// list.push_back(10);
-    /// assert_eq!(list.len(), 2);
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// let three = list.push_back(3);
+    /// assert_eq!(list.distance(one, three), Some(2));
+    /// assert_eq!(list.distance(three, one), Some(-2));
+    /// assert_eq!(list.distance(one, one), Some(0));
     /// ```
-    pub fn len(&self) -> usize {
-        self.count
+    pub fn distance(&self, a: Index<T, I>, b: Index<T, I>) -> Option<isize> {
+        if !self.in_sequence(a) || !self.in_sequence(b) {
+            return None;
+        }
+        Some(self.hops(a.slot(), b.slot()))
     }
 
-    /// Returns the number of elements in the list.
+    /// Returns the index of and a reference to the first element that satisfies `predicate`.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// let five = list.push_back(5);
-    /// list.remove(five);
-    /// assert_eq!(list.is_empty(), true);
+    /// list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.find(|item| *item > 5), Some((ten, &10)));
+    /// assert!(list.find(|item| *item > 10).is_none());
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.count == 0
+    pub fn find<P>(&self, predicate: P) -> Option<(Index<T, I>, &T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let slot = self.find_slot(predicate)?;
+        Some((self.index_at(slot), &self.occupied(slot).item))
     }
 
-    /// Returns a non-consuming iterator over the elements of the list.
+    /// Returns the index of and a mutable reference to the first element that satisfies
+    /// `predicate`.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
     /// list.push_back(5);
     /// list.push_back(10);
-    /// for item in &list {
-    ///     println!("{}", *item);
+    /// if let Some((_, item)) = list.find_mut(|item| *item > 5) {
+    ///     *item = 20;
     /// }
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![5, 20]);
     /// ```
-    pub fn iter(&self) -> Iter<'_, T> {
-        if let Some(head) = self.head {
-            if let Some(generation) = self.contents.get(head).and_then(|e| match e {
-                Occupied(oc) => Some(oc.generation),
-                _ => None,
-            }) {
-                Iter {
-                    list: self,
-                    index: Some(Index::new(head, generation)),
+    pub fn find_mut<P>(&mut self, predicate: P) -> Option<(Index<T, I>, &mut T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let slot = self.find_slot(predicate)?;
+        let index = self.index_at(slot);
+        Some((index, &mut self.occupied_mut(slot).item))
+    }
+
+    /// Returns the index of and a reference to the last element that satisfies `predicate`.
+    ///
+    /// The list is searched backwards, starting at the tail.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// list.push_back(10);
+    /// assert_eq!(list.rfind(|item| *item < 10), Some((five, &5)));
+    /// assert!(list.rfind(|item| *item < 5).is_none());
+    /// ```
+    pub fn rfind<P>(&self, predicate: P) -> Option<(Index<T, I>, &T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let slot = self.rfind_slot(predicate)?;
+        Some((self.index_at(slot), &self.occupied(slot).item))
+    }
+
+    /// Returns an iterator over the indices of all elements that satisfy `predicate`, in
+    /// list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let two = list.push_back(2);
+    /// let four = list.push_back(4);
+    /// let even: Vec<_> = list.find_all(|item| item % 2 == 0).collect();
+    /// assert_eq!(even, vec![two, four]);
+    /// ```
+    pub fn find_all<'a, P>(&'a self, mut predicate: P) -> impl Iterator<Item = Index<T, I>> + 'a
+    where
+        P: FnMut(&T) -> bool + 'a,
+    {
+        let mut iter = self.head;
+        std::iter::from_fn(move || {
+            while let Some(slot) = iter {
+                let oc = self.occupied(slot);
+                iter = oc.next();
+                if predicate(&oc.item) {
+                    return Some(Index::new(slot, oc.generation, self.id));
                 }
-            } else {
-                panic!("Corrupted list");
             }
+            None
+        })
+    }
+
+    /// Returns the index of and a reference to the element with the smallest key, or `None`
+    /// if the list is empty. Of several smallest elements, the first one is returned, just
+    /// like [`Iterator::min_by_key`] does.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(-3);
+    /// let one = list.push_back(1);
+    /// list.push_back(-1);
+    /// assert_eq!(list.min_by_key(|item: &i32| item.abs()), Some((one, &1)));
+    /// ```
+    pub fn min_by_key<K, F>(&self, f: F) -> Option<(Index<T, I>, &T)>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let slot = self.extreme_slot_by_key(f, Ordering::is_lt)?;
+        Some((self.index_at(slot), &self.occupied(slot).item))
+    }
+
+    /// Returns the index of and a reference to the element with the largest key, or `None`
+    /// if the list is empty. Of several largest elements, the last one is returned, just
+    /// like [`Iterator::max_by_key`] does.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(-3);
+    /// let three = list.push_back(3);
+    /// list.push_back(1);
+    /// assert_eq!(list.max_by_key(|item: &i32| item.abs()), Some((three, &3)));
+    /// ```
+    pub fn max_by_key<K, F>(&self, f: F) -> Option<(Index<T, I>, &T)>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let slot = self.extreme_slot_by_key(f, Ordering::is_ge)?;
+        Some((self.index_at(slot), &self.occupied(slot).item))
+    }
+
+    /// Replaces the element at the given index and returns the old one.
+    ///
+    /// The index stays valid. Returns `None` and drops `item` if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let index = list.push_back(5);
+    /// assert_eq!(list.replace(index, 10), Some(5));
+    /// assert_eq!(list.get(index), Some(&10));
+    /// ```
+    pub fn replace(&mut self, index: Index<T, I>, item: T) -> Option<T> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        let oc = self.occupied_mut(index.slot());
+        Some(std::mem::replace(&mut oc.item, item))
+    }
+
+    /// Returns mutable references to several elements at once.
+    ///
+    /// Returns `None` if any of the indices is not valid or if two of them refer to the
+    /// same element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// if let Some([a, b]) = list.get_disjoint_mut([five, ten]) {
+    ///     std::mem::swap(a, b);
+    /// }
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![10, 5]);
+    /// assert!(list.get_disjoint_mut([five, five]).is_none());
+    /// ```
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        indices: [Index<T, I>; N],
+    ) -> Option<[&mut T; N]> {
+        if !indices.iter().all(|&index| self.contains_index(index)) {
+            return None;
+        }
+        let entries = self
+            .contents
+            .get_disjoint_mut(indices.map(|index| index.slot()))
+            .ok()?;
+        Some(entries.map(|e| match e {
+            Occupied(oc) => &mut oc.item,
+            _ => panic!("Corrupted list"),
+        }))
+    }
+
+    /// Returns the next index after the given one, or `None` if it is the last element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five_index = list.push_back(5);
+    /// let ten_index = list.push_back(10);
+    /// assert_eq!(list.next_index(five_index), Some(ten_index));
+    /// assert!(list.next_index(ten_index).is_none());
+    /// ```
+    pub fn next_index(&self, index: Index<T, I>) -> Option<Index<T, I>> {
+        self.check_index(index).ok()?;
+        let next = self.occupied(index.slot()).next()?;
+        Some(self.index_at(next))
+    }
+
+    /// Returns the previous index before the given one, or `None` if it is the first element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five_index = list.push_back(5);
+    /// let ten_index = list.push_back(10);
+    /// assert_eq!(list.prev_index(ten_index), Some(five_index));
+    /// assert!(list.prev_index(five_index).is_none());
+    /// ```
+    pub fn prev_index(&self, index: Index<T, I>) -> Option<Index<T, I>> {
+        self.check_index(index).ok()?;
+        let prev = self.occupied(index.slot()).prev()?;
+        Some(self.index_at(prev))
+    }
+
+    /// Returns the index `n` elements after the given one, or `None` if the index is not
+    /// valid or the list ends before that. `advance(index, 0)` returns the index itself.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// let three = list.push_back(3);
+    /// assert_eq!(list.advance(one, 2), Some(three));
+    /// assert_eq!(list.advance(one, 3), None);
+    /// ```
+    pub fn advance(&self, index: Index<T, I>, n: usize) -> Option<Index<T, I>> {
+        if !self.in_sequence(index) || n >= self.count {
+            return None;
+        }
+        let mut slot = index.slot();
+        for _ in 0..n {
+            slot = self.occupied(slot).next()?;
+        }
+        Some(self.index_at(slot))
+    }
+
+    /// Returns the index `n` elements before the given one, or `None` if the index is not
+    /// valid or the list starts after that. `retreat(index, 0)` returns the index itself.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// let three = list.push_back(3);
+    /// assert_eq!(list.retreat(three, 2), Some(one));
+    /// assert_eq!(list.retreat(three, 3), None);
+    /// ```
+    pub fn retreat(&self, index: Index<T, I>, n: usize) -> Option<Index<T, I>> {
+        if !self.in_sequence(index) || n >= self.count {
+            return None;
+        }
+        let mut slot = index.slot();
+        for _ in 0..n {
+            slot = self.occupied(slot).prev()?;
+        }
+        Some(self.index_at(slot))
+    }
+
+    /// Returns the elements before and after the given index.
+    ///
+    /// Either side is `None` at the ends of the list, both are `None` if the index is not
+    /// valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// list.push_back(15);
+    /// assert_eq!(list.neighbors(ten), (Some(&5), Some(&15)));
+    /// ```
+    pub fn neighbors(&self, index: Index<T, I>) -> (Option<&T>, Option<&T>) {
+        let (prev, next) = self.neighbor_entries(index);
+        (prev.map(|(_, item)| item), next.map(|(_, item)| item))
+    }
+
+    /// Returns the indices and elements before and after the given index.
+    ///
+    /// Either side is `None` at the ends of the list, both are `None` if the index is not
+    /// valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.neighbor_entries(ten), (Some((five, &5)), None));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn neighbor_entries(
+        &self,
+        index: Index<T, I>,
+    ) -> (Option<(Index<T, I>, &T)>, Option<(Index<T, I>, &T)>) {
+        if self.check_index(index).is_err() {
+            return (None, None);
+        }
+        let oc = self.occupied(index.slot());
+        let entry = |slot| (self.index_at(slot), &self.occupied(slot).item);
+        (oc.prev().map(entry), oc.next().map(entry))
+    }
+
+    /// Returns the next index after the given one, wrapping around from the last element to
+    /// the first. Returns `None` only if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five_index = list.push_back(5);
+    /// let ten_index = list.push_back(10);
+    /// assert_eq!(list.next_index_circular(five_index), Some(ten_index));
+    /// assert_eq!(list.next_index_circular(ten_index), Some(five_index));
+    /// ```
+    pub fn next_index_circular(&self, index: Index<T, I>) -> Option<Index<T, I>> {
+        if !self.in_sequence(index) {
+            return None;
+        }
+        self.next_index(index).or_else(|| self.head_index())
+    }
+
+    /// Returns the previous index before the given one, wrapping around from the first
+    /// element to the last. Returns `None` only if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five_index = list.push_back(5);
+    /// let ten_index = list.push_back(10);
+    /// assert_eq!(list.prev_index_circular(ten_index), Some(five_index));
+    /// assert_eq!(list.prev_index_circular(five_index), Some(ten_index));
+    /// ```
+    pub fn prev_index_circular(&self, index: Index<T, I>) -> Option<Index<T, I>> {
+        if !self.in_sequence(index) {
+            return None;
+        }
+        self.prev_index(index).or_else(|| self.tail_index())
+    }
+
+    /// Returns an iterator that starts at the given index and visits every element of the
+    /// list exactly once, wrapping around from the last element to the first.
+    ///
+    /// The iterator is empty if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
+    /// let ring: Vec<i32> = list.ring_iter_from(two).map(|(_, item)| *item).collect();
+    /// assert_eq!(ring, vec![2, 3, 1]);
+    /// ```
+    pub fn ring_iter_from(
+        &self,
+        index: Index<T, I>,
+    ) -> impl Iterator<Item = (Index<T, I>, &T)> + '_ {
+        let len = if self.in_sequence(index) {
+            self.count
         } else {
-            Iter {
-                list: self,
-                index: None,
-            }
+            0
+        };
+        std::iter::successors(Some(index), move |&index| self.next_index_circular(index))
+            .take(len)
+            .map(move |index| (index, &self.occupied(index.slot()).item))
+    }
+
+    /// Returns an iterator over the elements that starts at the given index, walks to the
+    /// tail, wraps around to the head and stops after visiting every element exactly once.
+    ///
+    /// The iterator is empty if the index is not valid. Use [`IndexList::ring_iter_from`] to
+    /// get the index of each element as well.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// let three = list.push_back(3);
+    /// assert_eq!(list.iter_cyclic(three).copied().collect::<Vec<_>>(), vec![3, 1, 2]);
+    /// ```
+    pub fn iter_cyclic(&self, start: Index<T, I>) -> impl Iterator<Item = &T> + '_ {
+        self.ring_iter_from(start).map(|(_, item)| item)
+    }
+
+    /// Removes the element at the given index and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five_index = list.push_back(5);
+    /// assert_eq!(list.remove(five_index), Some(5));
+    /// assert_eq!(list.len(), 0);
+    /// ```
+    pub fn remove(&mut self, index: Index<T, I>) -> Option<T> {
+        self.try_remove(index).ok()
+    }
+
+    /// Removes the element at the given index and returns it, or the reason the index is not
+    /// valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{AccessError, IndexList};
+    /// let mut list = IndexList::new();
+    /// let index = list.push_back(5);
+    /// assert_eq!(list.try_remove(index), Ok(5));
+    /// assert_eq!(list.try_remove(index), Err(AccessError::FreedSlot));
+    /// ```
+    pub fn try_remove(&mut self, index: Index<T, I>) -> Result<T, AccessError> {
+        self.check_index(index)?;
+        if self.is_unlinked(index.slot()) {
+            // `free` takes the element off `count`.
+            self.detached -= 1;
+            self.count += 1;
+        } else {
+            self.check_links(index.slot())?;
+            self.unlink_range(index.slot(), index.slot());
         }
+        self.generation = self.generation.saturating_inc();
+        let item = self.free(index.slot());
+        self.debug_validate();
+        Ok(item)
     }
 
-    /// Returns a non-consuming mutable iterator over the elements of the list.
+    /// Removes the element at `position` in list order and returns it, or `None` if
+    /// `position` is out of bounds.
+    ///
+    /// The list is walked like [`IndexList::get_mut_by_position`] does, and the position is
+    /// remembered.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert_eq!(list.remove_at(1), Some(2));
+    /// assert_eq!(list.remove_at(2), None);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 3]);
+    /// ```
+    pub fn remove_at(&mut self, position: usize) -> Option<T> {
+        let slot = self.slot_at(position)?;
+        let next = self.occupied(slot).next();
+        let item = self.remove(self.index_at(slot))?;
+        if let Some(next) = next {
+            self.finger.set(next, position);
+        }
+        Some(item)
+    }
+
+    /// Removes all elements from `start` to `end`, both inclusive, and returns how many
+    /// elements were removed.
+    ///
+    /// The range is unlinked from the list in one splice. Returns `0` and leaves the list
+    /// unchanged if either index is not valid or `end` does not come after `start`.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
+    /// let four = list.push_back(4);
     /// list.push_back(5);
-    /// for item in &mut list {
-    ///     *item *= 2;
-    /// }
-    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![10]);
+    /// assert_eq!(list.remove_range(two, four), 3);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 5]);
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
-        if let Some(head) = self.head {
-            if let Some(generation) = self.contents.get(head).and_then(|e| match e {
-                Occupied(oc) => Some(oc.generation),
-                _ => None,
-            }) {
-                IterMut {
-                    list: self,
-                    index: Some(Index::new(head, generation)),
-                    ptr: std::ptr::null_mut(),
-                }
-            } else {
-                panic!("Corrupted list");
+    pub fn remove_range(&mut self, start: Index<T, I>, end: Index<T, I>) -> usize {
+        let Some(removed) = self.range_len(start, end) else {
+            return 0;
+        };
+        self.unlink_range(start.slot(), end.slot());
+        self.generation = self.generation.saturating_inc();
+        let mut iter = Some(start.slot());
+        for _ in 0..removed {
+            let slot = iter.expect("Corrupted list");
+            iter = self.occupied(slot).next();
+            self.free(slot);
+        }
+        self.debug_validate();
+        removed
+    }
+
+    /// Removes the elements at the given indices and returns them in the order of `indices`.
+    ///
+    /// Indices that are not valid, and repeats of an index, are skipped. Runs of adjacent
+    /// elements are unlinked from the list in one splice each. Takes O(k) time for k indices.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
+    /// let four = list.push_back(4);
+    /// assert_eq!(list.remove_many(&[four, one, two, one]), vec![4, 1, 2]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![3]);
+    /// ```
+    pub fn remove_many(&mut self, indices: &[Index<T, I>]) -> Vec<T> {
+        let mut removed = HashSet::with_capacity(indices.len());
+        let slots: Vec<usize> = indices
+            .iter()
+            .filter(|&&index| self.contains_index(index) && removed.insert(index.slot()))
+            .map(|index| index.slot())
+            .collect();
+        for &slot in &slots {
+            if self.is_unlinked(slot) {
+                // `free` takes the element off `count`.
+                self.detached -= 1;
+                self.count += 1;
+                continue;
             }
-        } else {
-            IterMut {
-                list: self,
-                index: None,
-                ptr: std::ptr::null_mut(),
+            if self
+                .occupied(slot)
+                .prev()
+                .is_some_and(|prev| removed.contains(&prev))
+            {
+                continue;
+            }
+            let mut last = slot;
+            while let Some(next) = self.occupied(last).next().filter(|n| removed.contains(n)) {
+                last = next;
             }
+            self.unlink_range(slot, last);
         }
+        if !slots.is_empty() {
+            self.generation = self.generation.saturating_inc();
+        }
+        let items = slots.into_iter().map(|slot| self.free(slot)).collect();
+        self.debug_validate();
+        items
     }
 
-    /// Returns an consuming iterator over the elements of the list.
+    /// Removes every element that satisfies `predicate` and returns how many were removed.
+    ///
+    /// The list is walked once, and each run of adjacent matching elements is unlinked from
+    /// the list in one splice.
     ///
     /// # Examples
     /// ```rust
     /// let mut list = indexlist::IndexList::new();
-    /// list.push_back(5);
-    /// list.push_back(10);
-    /// for item in list.into_iter() {
-    ///     println!("{}", item);
+    /// for item in 1..=6 {
+    ///     list.push_back(item);
     /// }
+    /// assert_eq!(list.remove_all(|item| item % 3 != 0), 4);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![3, 6]);
     /// ```
-    pub fn into_iter(self) -> IntoIter<T> {
-        if let Some(head) = self.head {
-            if let Some(generation) = self.contents.get(head).and_then(|e| match e {
-                Occupied(oc) => Some(oc.generation),
-                _ => None,
-            }) {
-                IntoIter {
-                    list: self,
-                    index: Some(Index::new(head, generation)),
+    pub fn remove_all<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let mut removed = 0;
+        let mut iter = self.head;
+        while let Some(first) = iter {
+            iter = self.occupied(first).next();
+            if !predicate(&self.occupied(first).item) {
+                continue;
+            }
+            let (mut last, mut len) = (first, 1);
+            // The walk moves past the element that ends the run too, so it is tested once.
+            while let Some(slot) = iter {
+                iter = self.occupied(slot).next();
+                if !predicate(&self.occupied(slot).item) {
+                    break;
                 }
-            } else {
-                panic!("Corrupted list");
+                (last, len) = (slot, len + 1);
             }
-        } else {
-            IntoIter {
-                list: self,
-                index: None,
+            if removed == 0 {
+                self.generation = self.generation.saturating_inc();
             }
+            self.unlink_range(first, last);
+            removed += len;
+            let mut run = Some(first);
+            for _ in 0..len {
+                let slot = run.expect("Corrupted list");
+                run = self.occupied(slot).next();
+                self.free(slot);
+            }
+        }
+        self.debug_validate();
+        removed
+    }
+
+    /// Removes all elements from `start` to `end`, both inclusive, and returns an iterator
+    /// that yields them in list order.
+    ///
+    /// Each element is unlinked from the list when it is yielded, the elements not yet
+    /// yielded when the iterator is dropped are removed then. If the iterator is leaked, those
+    /// elements stay in the list. The iterator is empty and the list unchanged if either index
+    /// is not valid or `end` does not come after `start`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
+    /// let four = list.push_back(4);
+    /// list.push_back(5);
+    /// let cut: Vec<i32> = list.drain_range(two, four).collect();
+    /// assert_eq!(cut, vec![2, 3, 4]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 5]);
+    /// ```
+    pub fn drain_range(&mut self, start: Index<T, I>, end: Index<T, I>) -> DrainRange<'_, T, I, A> {
+        let remaining = self.range_len(start, end).unwrap_or(0);
+        if remaining > 0 {
+            self.generation = self.generation.saturating_inc();
+        }
+        DrainRange {
+            list: self,
+            next: Some(start.slot()),
+            remaining,
         }
     }
-}
 
-impl<T> IndexList<T>
-where
-    T: PartialEq,
-{
-    /// Returns the index of the first occurrence of `item` in the list, if it exists.
-    ///
-    /// # Examples
-    /// ```rust
-    /// let mut list = indexlist::IndexList::new();
-    /// let five = list.push_back(5);
-    /// list.push_back(10);
-    /// assert_eq!(list.index_of(&5), Some(five));
-    /// assert!(list.index_of(&20).is_none());
-    /// ```
-    pub fn index_of(&self, item: &T) -> Option<Index<T>> {
-        let mut iter = self.head;
-        while let Some(index) = iter {
-            let entry = &self.contents[index];
-            match entry {
-                Occupied(oc) => {
-                    if &oc.item == item {
-                        return Some(Index::new(index, oc.generation));
-                    }
-                    iter = oc.next;
-                }
-                _ => panic!("Corrupted list"),
-            }
-        }
-        None
+    /// Shortens the list, keeping the first `len` elements and dropping the rest.
+    ///
+    /// Has no effect if `len` is greater than or equal to the length of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// list.truncate(1);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1]);
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        let (Some(first), Some(tail)) = (self.slot_at(len), self.tail) else {
+            return;
+        };
+        self.remove_range(self.index_at(first), self.index_at(tail));
+    }
+
+    /// Splits the list in two at position `n`: this list keeps the first `n` elements, and
+    /// the rest are moved, in order, into a new list that is returned.
+    ///
+    /// Returns an empty list if `n` is greater than or equal to the length of the list.
+    /// Indices of the kept elements remain valid, indices of the moved ones are invalidated.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// let rest = list.split_at_position(1);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1]);
+    /// assert_eq!(rest.iter().copied().collect::<Vec<i32>>(), vec![2, 3]);
+    /// ```
+    pub fn split_at_position(&mut self, n: usize) -> IndexList<T, I, A> {
+        match self.slot_at(n) {
+            Some(first) => self.split_off_from(first, self.count - n),
+            None => self.empty_like(0),
+        }
+    }
+
+    /// Splits the list in two right before the first element that satisfies `predicate`:
+    /// this list keeps the elements before it, and that element and all after it are moved,
+    /// in order, into a new list that is returned.
+    ///
+    /// Returns `None` and leaves the list unchanged if no element satisfies `predicate`. The
+    /// list is walked once, and indices of the kept elements remain valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back("GET / HTTP/1.1");
+    /// list.push_back("Host: example.com");
+    /// list.push_back("");
+    /// list.push_back("body");
+    /// let body = list.split_when(|line| line.is_empty()).unwrap();
+    /// assert_eq!(list.len(), 2);
+    /// assert_eq!(body.iter().copied().collect::<Vec<_>>(), vec!["", "body"]);
+    /// ```
+    pub fn split_when<P>(&mut self, predicate: P) -> Option<IndexList<T, I, A>>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let first = self.find_slot(predicate)?;
+        Some(self.split_off_from(first, 0))
+    }
+
+    /// Keeps the elements that satisfy `predicate` and moves the others, in order, into a
+    /// new list that is returned. Both lists keep the relative order of their elements.
+    ///
+    /// Indices of the kept elements remain valid, indices of the moved ones are invalidated.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// for item in 1..=5 {
+    ///     list.push_back(item);
+    /// }
+    /// let odd = list.partition_in_place(|item| item % 2 == 0);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![2, 4]);
+    /// assert_eq!(odd.iter().copied().collect::<Vec<i32>>(), vec![1, 3, 5]);
+    /// ```
+    pub fn partition_in_place<F>(&mut self, mut predicate: F) -> IndexList<T, I, A>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut rest = self.empty_like(0);
+        let mut iter = self.head;
+        while let Some(slot) = iter {
+            let oc = self.occupied(slot);
+            iter = oc.next();
+            if !predicate(&oc.item) {
+                self.unlink_range(slot, slot);
+                rest.push_back(self.free(slot));
+            }
+        }
+        if !rest.is_empty() {
+            self.generation = self.generation.saturating_inc();
+        }
+        self.debug_validate();
+        rest
+    }
+
+    /// Splits the list into the elements that satisfy `predicate` and those that do not,
+    /// both in their relative order.
+    ///
+    /// The first list is this one, with the other elements removed, so indices of the
+    /// elements that satisfy `predicate` remain valid for it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// let (odd, even) = list.partition(|item| item % 2 == 1);
+    /// assert_eq!(odd.iter().copied().collect::<Vec<i32>>(), vec![1, 3]);
+    /// assert_eq!(even.iter().copied().collect::<Vec<i32>>(), vec![2]);
+    /// assert_eq!(odd.get(one), Some(&1));
+    /// ```
+    pub fn partition<F>(mut self, predicate: F) -> (IndexList<T, I, A>, IndexList<T, I, A>)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let rest = self.partition_in_place(predicate);
+        (self, rest)
+    }
+
+    /// Inserts an element before the specified index and returns its new index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let index = list.push_front(2);
+    /// list.insert_before(index, 1);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2]);
+    /// ```
+    pub fn insert_before(&mut self, index: Index<T, I>, item: T) -> Option<Index<T, I>> {
+        self.try_insert_before(index, item).ok()
+    }
+
+    /// Inserts an element before the specified index and returns its new index, or gives the
+    /// element back together with the reason the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{AccessError, IndexList};
+    /// let mut list = IndexList::new();
+    /// let index = list.push_back(2);
+    /// assert!(list.try_insert_before(index, 1).is_ok());
+    /// list.remove(index);
+    /// let error = list.try_insert_before(index, 0).unwrap_err();
+    /// assert_eq!(error.kind(), AccessError::FreedSlot);
+    /// assert_eq!(error.into_item(), 0);
+    /// ```
+    pub fn try_insert_before(
+        &mut self,
+        index: Index<T, I>,
+        item: T,
+    ) -> Result<Index<T, I>, TryInsertError<T>> {
+        if let Err(kind) = self
+            .check_index(index)
+            .and_then(|()| self.check_links(index.slot()))
+        {
+            return Err(TryInsertError::new(kind, item));
+        }
+        let slot = self.allocate(item);
+        self.link_before(index.slot(), slot);
+        self.debug_validate();
+        Ok(self.index_at(slot))
+    }
+
+    /// Inserts an element after the specified index and returns its new index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let index = list.push_front(2);
+    /// list.insert_after(index, 3);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![2, 3]);
+    /// ```
+    pub fn insert_after(&mut self, index: Index<T, I>, item: T) -> Option<Index<T, I>> {
+        self.try_insert_after(index, item).ok()
+    }
+
+    /// Inserts an element after the specified index and returns its new index, or gives the
+    /// element back together with the reason the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{AccessError, IndexList};
+    /// let mut list = IndexList::new();
+    /// let index = list.push_back(2);
+    /// assert!(list.try_insert_after(index, 3).is_ok());
+    /// list.remove(index);
+    /// let error = list.try_insert_after(index, 4).unwrap_err();
+    /// assert_eq!(error.kind(), AccessError::FreedSlot);
+    /// ```
+    pub fn try_insert_after(
+        &mut self,
+        index: Index<T, I>,
+        item: T,
+    ) -> Result<Index<T, I>, TryInsertError<T>> {
+        if let Err(kind) = self
+            .check_index(index)
+            .and_then(|()| self.check_links(index.slot()))
+        {
+            return Err(TryInsertError::new(kind, item));
+        }
+        let slot = self.allocate(item);
+        self.link_after(index.slot(), slot);
+        self.debug_validate();
+        Ok(self.index_at(slot))
+    }
+
+    /// Inserts all elements of `items`, in order, after the specified index and returns their
+    /// new indices.
+    ///
+    /// The new elements are chained together first and then spliced into the list at once.
+    /// Returns an empty `Vec` and inserts nothing if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(4);
+    /// let indices = list.insert_iter_after(one, [2, 3]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3, 4]);
+    /// assert_eq!(list.get(indices[1]), Some(&3));
+    /// ```
+    pub fn insert_iter_after(
+        &mut self,
+        index: Index<T, I>,
+        items: impl IntoIterator<Item = T>,
+    ) -> Vec<Index<T, I>> {
+        if self.check_index(index).is_err() || self.check_links(index.slot()).is_err() {
+            return Vec::new();
+        }
+        let next = self.occupied(index.slot()).next();
+        self.insert_chain(Some(index.slot()), next, items)
+    }
+
+    /// Inserts all elements of `items`, in order, before the specified index and returns
+    /// their new indices.
+    ///
+    /// The new elements are chained together first and then spliced into the list at once.
+    /// Returns an empty `Vec` and inserts nothing if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let four = list.push_back(4);
+    /// list.insert_iter_before(four, [2, 3]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3, 4]);
+    /// ```
+    pub fn insert_iter_before(
+        &mut self,
+        index: Index<T, I>,
+        items: impl IntoIterator<Item = T>,
+    ) -> Vec<Index<T, I>> {
+        if self.check_index(index).is_err() || self.check_links(index.slot()).is_err() {
+            return Vec::new();
+        }
+        let prev = self.occupied(index.slot()).prev();
+        self.insert_chain(prev, Some(index.slot()), items)
+    }
+
+    /// Inserts an element so that it ends up at `position` in list order, and returns its
+    /// index. A `position` equal to the length of the list appends the element.
+    ///
+    /// The list is walked like [`IndexList::get_mut_by_position`] does, and the position is
+    /// remembered. Returns `None`, and drops the element, if `position` is past the end of
+    /// the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(3);
+    /// let two = list.insert_at(1, 2).unwrap();
+    /// assert_eq!(list.position_of(two), Some(1));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// assert_eq!(list.insert_at(4, 5), None);
+    /// ```
+    pub fn insert_at(&mut self, position: usize, item: T) -> Option<Index<T, I>> {
+        let index = if position == self.count {
+            self.push_back(item)
+        } else {
+            let slot = self.slot_at(position)?;
+            self.insert_before(self.index_at(slot), item)?
+        };
+        self.finger.set(index.slot(), position);
+        Some(index)
+    }
+
+    /// Moves the element at the given index to the back of `dest` and returns its index
+    /// there, or `None` if the index is not valid.
+    ///
+    /// The element goes straight from its slot into a slot of `dest`, a free one if `dest`
+    /// has any. The generation of this list only moves on if the element was inserted in the
+    /// current generation, as nothing else could mistake its slot for the element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let mut other = indexlist::IndexList::new();
+    /// let moved = list.transfer(five, &mut other).unwrap();
+    /// assert_eq!(other.get(moved), Some(&5));
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn transfer(
+        &mut self,
+        index: Index<T, I>,
+        dest: &mut IndexList<T, I, A>,
+    ) -> Option<Index<T, I>> {
+        self.transfer_between(index, dest, dest.tail, None)
+    }
+
+    /// Moves the element at the given index into `dest`, right before `before`, and returns
+    /// its index there.
+    ///
+    /// Returns `None` and leaves both lists unchanged if either index is not valid. The element
+    /// is moved like [`IndexList::transfer`] does.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let two = list.push_back(2);
+    /// let mut other = indexlist::IndexList::new();
+    /// other.push_back(1);
+    /// let three = other.push_back(3);
+    /// list.transfer_before(two, &mut other, three).unwrap();
+    /// assert_eq!(other.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn transfer_before(
+        &mut self,
+        index: Index<T, I>,
+        dest: &mut IndexList<T, I, A>,
+        before: Index<T, I>,
+    ) -> Option<Index<T, I>> {
+        if !dest.in_sequence(before) || dest.check_links(before.slot()).is_err() {
+            return None;
+        }
+        let prev = dest.occupied(before.slot()).prev();
+        self.transfer_between(index, dest, prev, Some(before.slot()))
+    }
+
+    /// Moves the element at the given index into `dest`, right after `after`, and returns
+    /// its index there.
+    ///
+    /// Returns `None` and leaves both lists unchanged if either index is not valid. The element
+    /// is moved like [`IndexList::transfer`] does.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let two = list.push_back(2);
+    /// let mut other = indexlist::IndexList::new();
+    /// let one = other.push_back(1);
+    /// other.push_back(3);
+    /// list.transfer_after(two, &mut other, one).unwrap();
+    /// assert_eq!(other.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn transfer_after(
+        &mut self,
+        index: Index<T, I>,
+        dest: &mut IndexList<T, I, A>,
+        after: Index<T, I>,
+    ) -> Option<Index<T, I>> {
+        if !dest.in_sequence(after) || dest.check_links(after.slot()).is_err() {
+            return None;
+        }
+        let next = dest.occupied(after.slot()).next();
+        self.transfer_between(index, dest, Some(after.slot()), next)
+    }
+
+    /// Takes the element at the given index out of the order of the list without removing
+    /// it, and returns `true` if it was in the order.
+    ///
+    /// The element keeps its slot, so the index stays valid for [`IndexList::get`],
+    /// [`IndexList::get_mut`] and [`IndexList::remove`], and the element can be put back
+    /// with [`IndexList::reattach_before`] or [`IndexList::reattach_after`]. Until then it
+    /// is not visited by iteration and not counted by [`IndexList::len`]. Consuming the
+    /// list, for instance with [`IndexList::into_vec`] or [`IndexList::append`], drops it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// assert!(list.detach(one));
+    /// assert_eq!(list.len(), 1);
+    /// assert_eq!(list.get(one), Some(&1));
+    /// assert!(list.reattach_after(one, two));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![2, 1]);
+    /// ```
+    pub fn detach(&mut self, index: Index<T, I>) -> bool {
+        if self.check_index(index).is_err() || self.check_links(index.slot()).is_err() {
+            return false;
+        }
+        let slot = index.slot();
+        self.unlink_range(slot, slot);
+        let oc = self.occupied_mut(slot);
+        oc.set_prev(None);
+        oc.set_next(None);
+        self.count -= 1;
+        self.detached += 1;
+        self.debug_validate();
+        true
+    }
+
+    /// Returns `true` if the index refers to an element taken out of the order with
+    /// [`IndexList::detach`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.detach(one);
+    /// assert!(list.is_detached(one));
+    /// ```
+    pub fn is_detached(&self, index: Index<T, I>) -> bool {
+        self.contains_index(index) && self.is_unlinked(index.slot())
+    }
+
+    /// Puts the detached element at `index` back into the order right before `before`, and
+    /// returns `true` on success.
+    ///
+    /// Returns `false` and leaves the list unchanged if `index` is not detached or `before`
+    /// is not an element in the order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.detach(two);
+    /// assert!(list.reattach_before(two, one));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![2, 1]);
+    /// ```
+    pub fn reattach_before(&mut self, index: Index<T, I>, before: Index<T, I>) -> bool {
+        if !self.is_detached(index) || !self.in_sequence(before) {
+            return false;
+        }
+        self.link_before(before.slot(), index.slot());
+        self.count += 1;
+        self.detached -= 1;
+        self.debug_validate();
+        true
+    }
+
+    /// Puts the detached element at `index` back into the order right after `after`, and
+    /// returns `true` on success.
+    ///
+    /// Returns `false` and leaves the list unchanged if `index` is not detached or `after`
+    /// is not an element in the order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.detach(one);
+    /// assert!(list.reattach_after(one, two));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![2, 1]);
+    /// ```
+    pub fn reattach_after(&mut self, index: Index<T, I>, after: Index<T, I>) -> bool {
+        if !self.is_detached(index) || !self.in_sequence(after) {
+            return false;
+        }
+        self.link_after(after.slot(), index.slot());
+        self.count += 1;
+        self.detached -= 1;
+        self.debug_validate();
+        true
+    }
+
+    /// Moves all elements of `other` to the back of this list, leaving `other` empty.
+    ///
+    /// The elements of `other` are moved into this list's storage as one chain, which is then
+    /// spliced in after the tail at once. Indices into `self` remain valid, indices into
+    /// `other` are invalidated.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let mut other = indexlist::IndexList::new();
+    /// other.push_back(2);
+    /// other.push_back(3);
+    /// list.append(&mut other);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut IndexList<T, I, A>) {
+        let items = other.take_items();
+        self.insert_chain(self.tail, None, items);
+    }
+
+    /// Moves all elements of `other` to the front of this list, leaving `other` empty.
+    ///
+    /// The elements of `other` are moved into this list's storage as one chain, which is then
+    /// spliced in before the head at once. Indices into `self` remain valid, indices into
+    /// `other` are invalidated.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(3);
+    /// let mut other = indexlist::IndexList::new();
+    /// other.push_back(1);
+    /// other.push_back(2);
+    /// list.prepend(&mut other);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn prepend(&mut self, other: &mut IndexList<T, I, A>) {
+        let items = other.take_items();
+        self.insert_chain(None, self.head, items);
+    }
+
+    /// Moves all elements of `other` into this list right after the specified index, leaving
+    /// `other` empty, and returns their new indices in list order.
+    ///
+    /// The elements of `other` are moved into this list's storage as one chain, which is then
+    /// spliced in at once. Indices into `self` remain valid, indices into `other` are
+    /// invalidated. Returns an empty `Vec` and leaves both lists unchanged if the index is not
+    /// valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(4);
+    /// let mut other = indexlist::IndexList::new();
+    /// other.push_back(2);
+    /// other.push_back(3);
+    /// let indices = list.splice_after(one, &mut other);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3, 4]);
+    /// assert_eq!(list.get(indices[0]), Some(&2));
+    /// ```
+    pub fn splice_after(
+        &mut self,
+        index: Index<T, I>,
+        other: &mut IndexList<T, I, A>,
+    ) -> Vec<Index<T, I>> {
+        if self.check_index(index).is_err() || self.check_links(index.slot()).is_err() {
+            return Vec::new();
+        }
+        let next = self.occupied(index.slot()).next();
+        self.insert_chain(Some(index.slot()), next, other.take_items())
+    }
+
+    /// Moves all elements of `other` into this list right before the specified index, leaving
+    /// `other` empty, and returns their new indices in list order.
+    ///
+    /// The elements of `other` are moved into this list's storage as one chain, which is then
+    /// spliced in at once. Indices into `self` remain valid, indices into `other` are
+    /// invalidated. Returns an empty `Vec` and leaves both lists unchanged if the index is not
+    /// valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let four = list.push_back(4);
+    /// let mut other = indexlist::IndexList::new();
+    /// other.push_back(2);
+    /// other.push_back(3);
+    /// list.splice_before(four, &mut other);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3, 4]);
+    /// ```
+    pub fn splice_before(
+        &mut self,
+        index: Index<T, I>,
+        other: &mut IndexList<T, I, A>,
+    ) -> Vec<Index<T, I>> {
+        if self.check_index(index).is_err() || self.check_links(index.slot()).is_err() {
+            return Vec::new();
+        }
+        let prev = self.occupied(index.slot()).prev();
+        self.insert_chain(prev, Some(index.slot()), other.take_items())
+    }
+
+    /// Returns the number of elements in the list, not counting detached ones.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// list.remove(five);
+    /// assert_eq!(list.is_empty(), true);
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns how much memory the list holds and how much of it is taken up by elements.
+    ///
+    /// The underlying vector never shrinks, so a list that once was much larger than it is
+    /// now keeps holding on to the memory, in free slots and spare capacity. Free slots that
+    /// can never be reused are counted apart from the others, as
+    /// [`MemoryUsage::quarantined`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::with_capacity(8);
+    /// let one = list.push_back(1u64);
+    /// list.push_back(2);
+    /// list.remove(one);
+    /// let usage = list.memory_usage();
+    /// assert_eq!((usage.slots, usage.live, usage.free), (2, 1, 1));
+    /// assert!(usage.allocated_bytes >= 4 * usage.live_bytes);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let entry_size = std::mem::size_of::<Entry<T, I>>();
+        let free = match &self.ordered_free {
+            Some(ordered_free) => ordered_free.len(),
+            None => std::iter::successors(self.next_free, |&slot| match self.contents[slot] {
+                Free { next_free } => next_free.map(Slot::get),
+                _ => panic!("Corrupted list"),
+            })
+            .count(),
+        };
+        MemoryUsage {
+            allocated_bytes: self.contents.capacity() * entry_size,
+            live_bytes: self.count * entry_size,
+            slots: self.slot_count(),
+            live: self.count,
+            free,
+            quarantined: self.free_slots() - free,
+        }
+    }
+
+    /// Returns how many elements were inserted into and removed from the list since it was
+    /// created, how many insertions reused a free slot, and how often the underlying vector
+    /// grew. Elements moved in from another list count as insertions.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::with_capacity(2);
+    /// let one = list.push_back(1);
+    /// list.remove(one);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// list.push_back(4);
+    /// let stats = list.ops_stats();
+    /// assert_eq!((stats.insertions, stats.removals, stats.slot_reuses), (4, 1, 1));
+    /// assert_eq!(stats.reallocations, 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn ops_stats(&self) -> OpsStats {
+        self.stats.counts
+    }
+
+    /// Returns the number of slots in use, either occupied by an element or free.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// list.remove(one);
+    /// assert_eq!(list.slot_count(), 2);
+    /// ```
+    pub fn slot_count(&self) -> usize {
+        self.contents.len()
+    }
+
+    /// Returns the number of free slots, left behind by removed elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// list.remove(one);
+    /// assert_eq!(list.free_slots(), 1);
+    /// ```
+    pub fn free_slots(&self) -> usize {
+        self.contents.len() - self.count - self.detached
+    }
+
+    /// Returns an iterator over the positions of the free slots in the underlying vector, in
+    /// ascending order.
+    ///
+    /// This is meant for measuring fragmentation; the positions cannot be used to access
+    /// the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let indexes: Vec<_> = (0..4).map(|item| list.push_back(item)).collect();
+    /// list.remove(indexes[2]);
+    /// list.remove(indexes[0]);
+    /// assert_eq!(list.free_slot_positions().collect::<Vec<_>>(), vec![0, 2]);
+    /// ```
+    pub fn free_slot_positions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.contents
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| matches!(entry, Free { .. }))
+            .map(|(slot, _)| slot)
+    }
+
+    /// Returns which free slot the list reuses for a new element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{IndexList, ReusePolicy};
+    /// let list: IndexList<i32> = IndexList::new();
+    /// assert_eq!(list.reuse_policy(), ReusePolicy::LastFreedFirst);
+    /// ```
+    pub fn reuse_policy(&self) -> ReusePolicy {
+        match self.ordered_free {
+            Some(_) => ReusePolicy::LowestIndexFirst,
+            None => ReusePolicy::LastFreedFirst,
+        }
+    }
+
+    /// Sets which free slot the list reuses for a new element. The free slots the list
+    /// already has are carried over, so this can be called at any time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{IndexList, ReusePolicy};
+    /// let mut list = IndexList::new();
+    /// list.set_reuse_policy(ReusePolicy::LowestIndexFirst);
+    /// let indexes: Vec<_> = (0..4).map(|item| list.push_back(item)).collect();
+    /// list.remove(indexes[1]);
+    /// list.remove(indexes[3]);
+    /// list.push_back(4);
+    /// assert_eq!(list.free_slot_positions().collect::<Vec<_>>(), vec![3]);
+    /// ```
+    pub fn set_reuse_policy(&mut self, policy: ReusePolicy) {
+        match (policy, self.ordered_free.take()) {
+            (ReusePolicy::LowestIndexFirst, None) => {
+                let mut ordered_free = BTreeSet::new();
+                while let Some(slot) = self.next_free {
+                    self.next_free =
+                        match std::mem::replace(&mut self.contents[slot], Free { next_free: None })
+                        {
+                            Free { next_free } => next_free.map(Slot::get),
+                            _ => panic!("Corrupted list"),
+                        };
+                    ordered_free.insert(slot);
+                }
+                self.ordered_free = Some(ordered_free);
+            }
+            (ReusePolicy::LastFreedFirst, Some(ordered_free)) => {
+                for slot in ordered_free.into_iter().rev() {
+                    self.contents[slot] = Free {
+                        next_free: self.next_free.map(Slot::new),
+                    };
+                    self.next_free = Some(slot);
+                }
+            }
+            (_, ordered_free) => self.ordered_free = ordered_free,
+        }
+    }
+
+    /// Moves the elements to the front of the underlying vector in list order and drops all
+    /// free slots, so that iterating the list walks memory sequentially.
+    ///
+    /// Every element gets a new index and all outstanding indices are invalidated. `remap`
+    /// is called with the old and the new index of each element, in list order, so that
+    /// indices stored elsewhere can be updated. Once the generation counter has saturated,
+    /// old indices could not be told apart from new ones, so the list is left unchanged and
+    /// `remap` is not called.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_front(0);
+    /// list.remove(one);
+    /// let mut new_two = None;
+    /// list.defragment(|old, new| {
+    ///     if old == two {
+    ///         new_two = Some(new);
+    ///     }
+    /// });
+    /// assert_eq!(list.get(two), None);
+    /// assert_eq!(list.get(new_two.unwrap()), Some(&2));
+    /// assert_eq!(list.free_slots(), 0);
+    /// ```
+    pub fn defragment<F>(&mut self, mut remap: F)
+    where
+        F: FnMut(Index<T, I>, Index<T, I>),
+    {
+        if self.generation == I::MAX {
+            return;
+        }
+        self.generation = self.generation.saturating_inc();
+        // Detached elements are kept, after the elements in order.
+        let order: Vec<usize> = self
+            .slots()
+            .into_iter()
+            .chain(self.detached_slots())
+            .collect();
+        let storage = alloc::Vec::with_capacity_in(
+            self.contents.capacity(),
+            self.contents.allocator().clone(),
+        );
+        let mut entries: Vec<Option<Entry<T, I>>> = std::mem::replace(&mut self.contents, storage)
+            .into_iter()
+            .map(Some)
+            .collect();
+        for (position, &slot) in order.iter().enumerate() {
+            let Some(Occupied(mut oc)) = entries[slot].take() else {
+                panic!("Corrupted list");
+            };
+            let old = Index::new(slot, oc.generation, self.id);
+            oc.generation = self.generation;
+            self.contents.push(Occupied(oc));
+            remap(old, Index::new(position, self.generation, self.id));
+        }
+        self.next_free = None;
+        if let Some(ordered_free) = &mut self.ordered_free {
+            ordered_free.clear();
+        }
+        self.relink(&(0..self.count).collect::<Vec<_>>());
+        trace_event!(
+            debug,
+            len = self.count,
+            generation = self.generation.to_usize(),
+            "defragmented list"
+        );
+        self.debug_validate();
+    }
+
+    /// Checks the internal invariants of the list and returns the first one found broken.
+    ///
+    /// A list built only through the public API always validates; this is meant for tests
+    /// and for tracking down memory corruption. Takes O(n) time. With the `debug-invariants`
+    /// feature enabled, debug builds run this check after every mutating operation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// list.remove(one);
+    /// assert_eq!(list.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        let occupied = self
+            .contents
+            .iter()
+            .filter(|e| matches!(e, Occupied(_)))
+            .count();
+        if occupied != self.count + self.detached {
+            return Err(InvariantError::CountMismatch {
+                stored: self.count + self.detached,
+                occupied,
+            });
+        }
+
+        // Free slots are kept either in the free list or in the ordered set, never both.
+        if self.next_free.is_some() && self.ordered_free.is_some() {
+            return Err(InvariantError::BrokenFreeList);
+        }
+        let free = self.contents.len() - occupied;
+        let mut next = self.next_free;
+        let mut free_count = 0;
+        while let Some(slot) = next {
+            if free_count == free {
+                return Err(InvariantError::BrokenFreeList);
+            }
+            match self.contents.get(slot) {
+                Some(Free { next_free }) => next = next_free.map(Slot::get),
+                _ => return Err(InvariantError::BrokenFreeList),
+            }
+            free_count += 1;
+        }
+        for &slot in self.ordered_free.iter().flatten() {
+            if !matches!(self.contents.get(slot), Some(Free { .. })) {
+                return Err(InvariantError::BrokenFreeList);
+            }
+            free_count += 1;
+        }
+        // Slots freed after the generation saturated are left out of the free list for good.
+        if free_count != free && self.generation != I::MAX {
+            return Err(InvariantError::BrokenFreeList);
+        }
+
+        let mut iter = self.head;
+        let mut last = None;
+        let mut visited = 0;
+        while let Some(slot) = iter {
+            if visited == self.count {
+                return Err(InvariantError::BrokenChain);
+            }
+            let Some(Occupied(oc)) = self.contents.get(slot) else {
+                return Err(InvariantError::LinkToFreeSlot { slot });
+            };
+            if oc.prev() != last {
+                return Err(InvariantError::BrokenLink { slot });
+            }
+            if oc.generation > self.generation {
+                return Err(InvariantError::FutureGeneration { slot });
+            }
+            last = iter;
+            iter = oc.next();
+            visited += 1;
+        }
+        if last != self.tail || visited != self.count {
+            return Err(InvariantError::BrokenChain);
+        }
+        if self.detached_slots().count() != self.detached {
+            return Err(InvariantError::BrokenChain);
+        }
+        Ok(())
+    }
+
+    /// Panics if the list is corrupted, with the `debug-invariants` feature in debug builds.
+    #[inline]
+    fn debug_validate(&self) {
+        #[cfg(all(feature = "debug-invariants", debug_assertions))]
+        if let Err(error) = self.validate() {
+            panic!("Corrupted list: {error}");
+        }
+    }
+
+    /// Sorts the list with a comparator function, preserving the order of equal elements.
+    ///
+    /// Only the links between the elements are rewritten, the elements themselves stay in
+    /// place, so all indices into the list remain valid after sorting.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let three = list.push_back(3);
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.sort_by(|a, b| b.cmp(a));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![3, 2, 1]);
+    /// assert_eq!(list.get(three), Some(&3));
+    /// ```
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut order = self.slots();
+        order.sort_by(|&a, &b| compare(&self.occupied(a).item, &self.occupied(b).item));
+        self.relink(&order);
+        self.debug_validate();
+    }
+
+    /// Sorts the list by the key `f` extracts from each element, preserving the order of
+    /// elements with equal keys.
+    ///
+    /// `f` is called only once per element and the keys are kept while sorting, which pays
+    /// off when extracting a key is expensive. Like [`IndexList::sort_by`], only the links are
+    /// rewritten and all indices remain valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back("b");
+    /// let a = list.push_back("A");
+    /// list.push_back("c");
+    /// list.sort_by_cached_key(|s| s.to_lowercase());
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec!["A", "b", "c"]);
+    /// assert_eq!(list.head_index(), Some(a));
+    /// ```
+    pub fn sort_by_cached_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let mut keyed: Vec<(K, usize)> = self
+            .slots()
+            .into_iter()
+            .map(|slot| (f(&self.occupied(slot).item), slot))
+            .collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        let order: Vec<usize> = keyed.into_iter().map(|(_, slot)| slot).collect();
+        self.relink(&order);
+        self.debug_validate();
+    }
+
+    /// Consumes the list and returns its elements in a `Vec`, sorted with a comparator
+    /// function. The order of equal elements is preserved.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(3);
+    /// list.push_back(2);
+    /// assert_eq!(list.into_sorted_vec_by(|a, b| b.cmp(a)), vec![3, 2, 1]);
+    /// ```
+    pub fn into_sorted_vec_by<F>(self, compare: F) -> Vec<T>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut items: Vec<T> = self.into_iter().collect();
+        items.sort_by(compare);
+        items
+    }
+
+    /// Consumes the list and returns its elements in a `Vec`, sorted by the key `f` extracts
+    /// from each element. The order of elements with equal keys is preserved.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back("ccc");
+    /// list.push_back("a");
+    /// list.push_back("bb");
+    /// assert_eq!(list.into_sorted_vec_by_key(|s| s.len()), vec!["a", "bb", "ccc"]);
+    /// ```
+    pub fn into_sorted_vec_by_key<K, F>(self, f: F) -> Vec<T>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let mut items: Vec<T> = self.into_iter().collect();
+        items.sort_by_key(f);
+        items
+    }
+
+    /// Merges the sorted list `other` into this sorted list, keeping the result sorted
+    /// according to `compare`.
+    ///
+    /// The elements of `other` are moved into this list, no element is cloned. When two
+    /// elements compare equal, the one from `self` comes first. Indices into `self` remain
+    /// valid, indices into `other` are invalidated.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(4);
+    /// let mut other = indexlist::IndexList::new();
+    /// other.push_back(2);
+    /// other.push_back(3);
+    /// other.push_back(5);
+    /// list.merge(other, |a, b| a.cmp(b));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3, 4, 5]);
+    /// ```
+    pub fn merge<F>(&mut self, other: IndexList<T, I, A>, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let ours = self.slots();
+        let theirs: Vec<usize> = other
+            .into_items()
+            .into_iter()
+            .map(|item| self.allocate(item))
+            .collect();
+
+        let mut order = Vec::with_capacity(ours.len() + theirs.len());
+        let mut ours = ours.into_iter().peekable();
+        let mut theirs = theirs.into_iter().peekable();
+        while let (Some(&a), Some(&b)) = (ours.peek(), theirs.peek()) {
+            if compare(&self.occupied(b).item, &self.occupied(a).item) == Ordering::Less {
+                order.push(b);
+                theirs.next();
+            } else {
+                order.push(a);
+                ours.next();
+            }
+        }
+        order.extend(ours);
+        order.extend(theirs);
+        self.relink(&order);
+        self.debug_validate();
+    }
+
+    /// Inserts an element into a sorted list, keeping it sorted according to `compare`,
+    /// and returns its index.
+    ///
+    /// The element is inserted after any elements that compare equal to it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(3);
+    /// let two = list.insert_sorted_by(2, |a, b| a.cmp(b));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// assert_eq!(list.get(two), Some(&2));
+    /// ```
+    pub fn insert_sorted_by<F>(&mut self, item: T, compare: F) -> Index<T, I>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        match self.head {
+            Some(head) => self.insert_sorted_from(head, item, compare),
+            None => self.push_back(item),
+        }
+    }
+
+    /// Inserts an element into a sorted list like [`IndexList::insert_sorted_by`], but starts
+    /// searching for the insertion point at `hint` instead of the head of the list.
+    ///
+    /// The search walks backwards or forwards from `hint` as needed, so a hint close to the
+    /// final position makes the insertion cheap. Returns `None` if `hint` is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let four = list.push_back(4);
+    /// list.push_back(6);
+    /// list.insert_sorted_by_hint(four, 5, |a, b| a.cmp(b));
+    /// list.insert_sorted_by_hint(four, 2, |a, b| a.cmp(b));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 4, 5, 6]);
+    /// ```
+    pub fn insert_sorted_by_hint<F>(
+        &mut self,
+        hint: Index<T, I>,
+        item: T,
+        compare: F,
+    ) -> Option<Index<T, I>>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if !self.in_sequence(hint) {
+            return None;
+        }
+        Some(self.insert_sorted_from(hint.slot(), item, compare))
+    }
+
+    /// Removes all but the first of consecutive elements for which `same` returns `true`.
+    ///
+    /// `same` is passed the element under consideration and the last retained element
+    /// before it, in that order, just like [`Vec::dedup_by`]. Each removal is O(1).
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back("a");
+    /// list.push_back("A");
+    /// list.push_back("b");
+    /// list.push_back("a");
+    /// list.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    /// assert_eq!(list.iter().copied().collect::<Vec<&str>>(), vec!["a", "b", "a"]);
+    /// ```
+    pub fn dedup_by<F>(&mut self, mut same: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let Some(mut kept) = self.head else {
+            return;
+        };
+        while let Some(current) = self.occupied(kept).next() {
+            let (current_entry, kept_entry) = self.occupied_pair_mut(current, kept);
+            if same(&mut current_entry.item, &mut kept_entry.item) {
+                self.remove(self.index_at(current));
+            } else {
+                kept = current;
+            }
+        }
+    }
+
+    /// Binary searches a sorted list with a comparator function.
+    ///
+    /// `f` should return whether the element is `Less`, `Equal` or `Greater` than the
+    /// target. Returns `Ok` with the index of a matching element, or `Err` with the index
+    /// of the element the target would have to be inserted before to keep the list sorted,
+    /// which is `None` if it belongs at the back. If several elements match, any one of
+    /// them may be returned.
+    ///
+    /// This makes O(log n) comparisons, but still walks O(n) links; see
+    /// [`IndexedList::binary_search_by`] for a search in O(log n) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// let three = list.push_back(3);
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.binary_search_by(|item| item.cmp(&3)), Ok(three));
+    /// assert_eq!(list.binary_search_by(|item| item.cmp(&4)), Err(Some(five)));
+    /// assert_eq!(list.binary_search_by(|item| item.cmp(&6)), Err(None));
+    /// ```
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<Index<T, I>, Option<Index<T, I>>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let mut base = self.head;
+        let mut size = self.count;
+        while size > 0 {
+            let half = size / 2;
+            let mut mid = base.expect("Corrupted list");
+            for _ in 0..half {
+                mid = self.occupied(mid).next().expect("Corrupted list");
+            }
+            match f(&self.occupied(mid).item) {
+                Ordering::Less => {
+                    base = self.occupied(mid).next();
+                    size -= half + 1;
+                }
+                Ordering::Greater => size = half,
+                Ordering::Equal => return Ok(self.index_at(mid)),
+            }
+        }
+        Err(base.map(|slot| self.index_at(slot)))
+    }
+
+    /// Reverses the order of the list in place.
+    ///
+    /// Elements are not moved and all indices remain valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// list.reverse();
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![3, 2, 1]);
+    /// assert_eq!(list.tail_index(), Some(one));
+    /// ```
+    pub fn reverse(&mut self) {
+        self.finger.clear();
+        let mut iter = self.head;
+        while let Some(slot) = iter {
+            let oc = self.occupied_mut(slot);
+            std::mem::swap(&mut oc.next, &mut oc.prev);
+            iter = oc.prev();
+        }
+        std::mem::swap(&mut self.head, &mut self.tail);
+        self.debug_validate();
+    }
+
+    /// Rotates the list `n` places to the left, so that the element at position `n` becomes
+    /// the new head. `n` wraps around the length of the list.
+    ///
+    /// Only the head and tail links are rewritten, elements are not moved and all indices
+    /// remain valid. Takes O(min(n, len - n)) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// list.rotate_left(1);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![2, 3, 1]);
+    /// ```
+    pub fn rotate_left(&mut self, n: usize) {
+        if self.count == 0 {
+            return;
+        }
+        let n = n % self.count;
+        if n != 0 {
+            let new_head = self.slot_at(n).expect("Corrupted list");
+            self.rotate_to(new_head);
+        }
+        self.debug_validate();
+    }
+
+    /// Rotates the list `n` places to the right, so that the element `n` places before the
+    /// tail becomes the new tail. `n` wraps around the length of the list.
+    ///
+    /// Only the head and tail links are rewritten, elements are not moved and all indices
+    /// remain valid. Takes O(min(n, len - n)) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// list.rotate_right(1);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![3, 1, 2]);
+    /// ```
+    pub fn rotate_right(&mut self, n: usize) {
+        if self.count == 0 {
+            return;
+        }
+        self.rotate_left(self.count - n % self.count);
+    }
+
+    /// Moves the elements from `first` to `last`, both inclusive, right before `target`,
+    /// and returns `true` on success. All indices stay valid.
+    ///
+    /// Returns `false` and leaves the list unchanged if an index is not valid, `last` does
+    /// not come after `first`, or `target` is within the range. The range is walked once to
+    /// check this, moving it takes O(1) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// let three = list.push_back(3);
+    /// let four = list.push_back(4);
+    /// assert!(list.move_range_before(three, four, one));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![3, 4, 1, 2]);
+    /// ```
+    pub fn move_range_before(
+        &mut self,
+        first: Index<T, I>,
+        last: Index<T, I>,
+        target: Index<T, I>,
+    ) -> bool {
+        if !self.can_move_range(first, last, target) {
+            return false;
+        }
+        self.unlink_range(first.slot(), last.slot());
+        let prev = self.occupied(target.slot()).prev();
+        self.splice(prev, Some(target.slot()), first.slot(), last.slot());
+        self.debug_validate();
+        true
+    }
+
+    /// Moves the elements from `first` to `last`, both inclusive, right after `target`, and
+    /// returns `true` on success. All indices stay valid.
+    ///
+    /// Returns `false` and leaves the list unchanged if an index is not valid, `last` does
+    /// not come after `first`, or `target` is within the range. The range is walked once to
+    /// check this, moving it takes O(1) time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
+    /// let four = list.push_back(4);
+    /// assert!(list.move_range_after(one, two, four));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![3, 4, 1, 2]);
+    /// ```
+    pub fn move_range_after(
+        &mut self,
+        first: Index<T, I>,
+        last: Index<T, I>,
+        target: Index<T, I>,
+    ) -> bool {
+        if !self.can_move_range(first, last, target) {
+            return false;
+        }
+        self.unlink_range(first.slot(), last.slot());
+        let next = self.occupied(target.slot()).next();
+        self.splice(Some(target.slot()), next, first.slot(), last.slot());
+        self.debug_validate();
+        true
+    }
+
+    /// Exchanges the places of the elements from `a_start` to `a_end` and those from
+    /// `b_start` to `b_end`, all inclusive, and returns `true` on success. All indices stay
+    /// valid.
+    ///
+    /// Returns `false` and leaves the list unchanged if an index is not valid, a range end
+    /// does not come after its start, or the ranges overlap. The ranges are walked to check
+    /// this, swapping them only relinks their boundaries.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
+    /// let four = list.push_back(4);
+    /// assert!(list.swap_ranges(one, two, four, four));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![4, 3, 1, 2]);
+    /// ```
+    pub fn swap_ranges(
+        &mut self,
+        a_start: Index<T, I>,
+        a_end: Index<T, I>,
+        b_start: Index<T, I>,
+        b_end: Index<T, I>,
+    ) -> bool {
+        if self.range_len(a_start, a_end).is_none()
+            || self.range_len(b_start, b_end).is_none()
+            || self.range_holds(a_start.slot(), a_end.slot(), b_start.slot())
+            || self.range_holds(b_start.slot(), b_end.slot(), a_start.slot())
+        {
+            return false;
+        }
+        let (a_start, a_end) = (a_start.slot(), a_end.slot());
+        let (b_start, b_end) = (b_start.slot(), b_end.slot());
+        let (a_prev, a_next) = (self.occupied(a_start).prev(), self.occupied(a_end).next());
+        let (b_prev, b_next) = (self.occupied(b_start).prev(), self.occupied(b_end).next());
+        if a_next == Some(b_start) {
+            self.unlink_range(a_start, a_end);
+            self.splice(Some(b_end), b_next, a_start, a_end);
+        } else if b_next == Some(a_start) {
+            self.unlink_range(b_start, b_end);
+            self.splice(Some(a_end), a_next, b_start, b_end);
+        } else {
+            self.unlink_range(a_start, a_end);
+            self.unlink_range(b_start, b_end);
+            self.splice(a_prev, a_next, b_start, b_end);
+            self.splice(b_prev, b_next, a_start, a_end);
+        }
+        self.debug_validate();
+        true
+    }
+
+    /// Returns a non-consuming iterator over the elements of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// for item in &list {
+    ///     println!("{}", *item);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, I, A> {
+        if let Some(head) = self.head {
+            if let Some(generation) = self.contents.get(head).and_then(|e| match e {
+                Occupied(oc) => Some(oc.generation),
+                _ => None,
+            }) {
+                Iter {
+                    list: self,
+                    index: Some(Index::new(head, generation, self.id)),
+                }
+            } else {
+                panic!("Corrupted list");
+            }
+        } else {
+            Iter {
+                list: self,
+                index: None,
+            }
+        }
+    }
+
+    /// Returns a non-consuming mutable iterator over the elements of the list.
+    ///
+    /// Creating the iterator scans the underlying vector once and keeps a reference to each
+    /// slot, so it allocates memory proportional to the capacity of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// for item in &mut list {
+    ///     *item *= 2;
+    /// }
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![10]);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, I> {
+        let next = self.head;
+        let entries = self
+            .contents
+            .iter_mut()
+            .map(|entry| match entry {
+                Occupied(oc) => Some(oc),
+                Free { .. } => None,
+            })
+            .collect();
+        IterMut { entries, next }
+    }
+
+    /// Returns an iterator over runs of `n` consecutive elements, in list order. The last
+    /// run is shorter if the length of the list is not a multiple of `n`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// for item in 1..=5 {
+    ///     list.push_back(item);
+    /// }
+    /// let chunks: Vec<Vec<&i32>> = list.chunks(2).collect();
+    /// assert_eq!(chunks, vec![vec![&1, &2], vec![&3, &4], vec![&5]]);
+    /// ```
+    pub fn chunks(&self, n: usize) -> impl Iterator<Item = Vec<&T>> + '_ {
+        assert!(n != 0, "chunk size must be non-zero");
+        let mut iter = self.iter();
+        std::iter::from_fn(move || {
+            let chunk: Vec<&T> = iter.by_ref().take(n).collect();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
+    /// Returns an iterator over all runs of `n` consecutive elements, in list order. The runs
+    /// overlap, each starts one element after the previous one. Yields nothing if the list
+    /// has fewer than `n` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// for item in 1..=4 {
+    ///     list.push_back(item);
+    /// }
+    /// let windows: Vec<Vec<&i32>> = list.windows(3).collect();
+    /// assert_eq!(windows, vec![vec![&1, &2, &3], vec![&2, &3, &4]]);
+    /// ```
+    pub fn windows(&self, n: usize) -> impl Iterator<Item = Vec<&T>> + '_ {
+        assert!(n != 0, "window size must be non-zero");
+        let mut iter = self.iter();
+        let mut window: VecDeque<&T> = iter.by_ref().take(n - 1).collect();
+        iter.map(move |item| {
+            window.push_back(item);
+            let run = window.iter().copied().collect();
+            window.pop_front();
+            run
+        })
+    }
+
+    /// Consumes the list and returns an iterator over its elements together with their
+    /// indices, in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// let entries: Vec<_> = list.into_entries().collect();
+    /// assert_eq!(entries, vec![(five, 5), (ten, 10)]);
+    /// ```
+    pub fn into_entries(self) -> impl Iterator<Item = (Index<T, I>, T)> {
+        let mut iter = self.into_iter();
+        std::iter::from_fn(move || {
+            let index = iter.list.head_index()?;
+            iter.next().map(|item| (index, item))
+        })
+    }
+
+    /// Consumes the list and returns its elements in a `Vec`, in list order. Detached
+    /// elements are not part of the order and are dropped.
+    ///
+    /// A list whose elements are stored in list order, such as one that was only ever
+    /// pushed to the back or was just defragmented, is converted without following its
+    /// links.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(2);
+    /// list.push_front(1);
+    /// assert_eq!(list.into_vec(), vec![1, 2]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        self.into_items()
+    }
+
+    /// Returns a new list with `f` applied to every element, laid out exactly like this list.
+    ///
+    /// Every element keeps its slot and generation, so [`Index::cast`] turns an index into
+    /// this list into the index of the corresponding element in the new one. The new list
+    /// shares this list's identity for that purpose. `f` is called in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// let doubled = list.map(|item| item * 2);
+    /// assert_eq!(doubled.iter().copied().collect::<Vec<i32>>(), vec![2, 4]);
+    /// assert_eq!(doubled.get(one.cast()), Some(&2));
+    /// ```
+    pub fn map<U, F>(&self, mut f: F) -> IndexList<U, I, A>
+    where
+        F: FnMut(&T) -> U,
+    {
+        match self.try_map(|item| Ok::<U, std::convert::Infallible>(f(item))) {
+            Ok(list) => list,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Returns a new list with the fallible `f` applied to every element, laid out exactly
+    /// like this list, like [`IndexList::map`]. Stops at the first error and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back("1");
+    /// list.push_back("2");
+    /// let numbers = list.try_map(|item| item.parse::<i32>()).unwrap();
+    /// assert_eq!(numbers.get(one.cast()), Some(&1));
+    /// list.push_back("three");
+    /// assert!(list.try_map(|item| item.parse::<i32>()).is_err());
+    /// ```
+    pub fn try_map<U, E, F>(&self, mut f: F) -> Result<IndexList<U, I, A>, E>
+    where
+        F: FnMut(&T) -> Result<U, E>,
+    {
+        let mut items: Vec<Option<U>> = self.contents.iter().map(|_| None).collect();
+        for slot in self.slots().into_iter().chain(self.detached_slots()) {
+            items[slot] = Some(f(&self.occupied(slot).item)?);
+        }
+        let mut contents =
+            alloc::Vec::with_capacity_in(self.contents.len(), self.contents.allocator().clone());
+        contents.extend(
+            self.contents
+                .iter()
+                .zip(items)
+                .map(|(entry, item)| match (entry, item) {
+                    (Free { next_free }, None) => Free {
+                        next_free: *next_free,
+                    },
+                    (Occupied(oc), Some(item)) => Occupied(OccupiedEntry {
+                        item,
+                        generation: oc.generation,
+                        next: oc.next,
+                        prev: oc.prev,
+                    }),
+                    _ => panic!("Corrupted list"),
+                }),
+        );
+        Ok(IndexList {
+            id: self.id,
+            contents,
+            generation: self.generation,
+            next_free: self.next_free,
+            ordered_free: self.ordered_free.clone(),
+            head: self.head,
+            tail: self.tail,
+            count: self.count,
+            max_capacity: self.max_capacity,
+            detached: self.detached,
+            finger: Finger::default(),
+            stats: Stats::default(),
+        })
+    }
+
+    /// Returns a new list of the results of `f` that are `Some`, in list order.
+    ///
+    /// Unlike [`IndexList::map`], the new list is packed densely and does not share the
+    /// layout of this list, so indices into this list cannot be used with it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back("1");
+    /// list.push_back("two");
+    /// list.push_back("3");
+    /// let numbers = list.filter_map(|item| item.parse::<i32>().ok());
+    /// assert_eq!(numbers.iter().copied().collect::<Vec<i32>>(), vec![1, 3]);
+    /// ```
+    pub fn filter_map<U, F>(&self, f: F) -> IndexList<U, I, A>
+    where
+        F: FnMut(&T) -> Option<U>,
+    {
+        let mut list = self.empty_like(self.count);
+        for item in self.iter().filter_map(f) {
+            list.push_back(item);
+        }
+        list
+    }
+
+    fn occupied(&self, slot: usize) -> &OccupiedEntry<T, I> {
+        self.contents.occupied(slot)
+    }
+
+    fn occupied_mut(&mut self, slot: usize) -> &mut OccupiedEntry<T, I> {
+        self.contents.occupied_mut(slot)
+    }
+
+    fn index_at(&self, slot: usize) -> Index<T, I> {
+        self.contents.index_at(slot, self.id)
+    }
+
+    fn insert_sorted_from<F>(&mut self, start: usize, item: T, mut compare: F) -> Index<T, I>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut current = start;
+        if compare(&item, &self.occupied(current).item) == Ordering::Less {
+            while let Some(prev) = self.occupied(current).prev() {
+                if compare(&item, &self.occupied(prev).item) != Ordering::Less {
+                    break;
+                }
+                current = prev;
+            }
+            self.insert_before(self.index_at(current), item)
+        } else {
+            while let Some(next) = self.occupied(current).next() {
+                if compare(&item, &self.occupied(next).item) == Ordering::Less {
+                    break;
+                }
+                current = next;
+            }
+            self.insert_after(self.index_at(current), item)
+        }
+        .expect("Corrupted list")
+    }
+
+    fn occupied_pair_mut(
+        &mut self,
+        a: usize,
+        b: usize,
+    ) -> (&mut OccupiedEntry<T, I>, &mut OccupiedEntry<T, I>) {
+        assert_ne!(a, b);
+        let (low, high) = self.contents.split_at_mut(a.max(b));
+        let (first, second) = (&mut low[a.min(b)], &mut high[0]);
+        let (a_entry, b_entry) = if a < b {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        match (a_entry, b_entry) {
+            (Occupied(a_oc), Occupied(b_oc)) => (a_oc, b_oc),
+            _ => panic!("Corrupted list"),
+        }
+    }
+
+    /// Returns the slot at `position`, walking from whichever of the two ends and the
+    /// finger is closest.
+    fn slot_at(&self, position: usize) -> Option<usize> {
+        if position >= self.count {
+            return None;
+        }
+        let mut start = (self.head?, 0);
+        if self.count - 1 - position < position {
+            start = (self.tail?, self.count - 1);
+        }
+        if let Some(finger) = self.finger.get() {
+            if finger.1.abs_diff(position) < start.1.abs_diff(position) {
+                start = finger;
+            }
+        }
+        let (mut slot, mut at) = start;
+        while at < position {
+            slot = self.occupied(slot).next()?;
+            at += 1;
+        }
+        while at > position {
+            slot = self.occupied(slot).prev()?;
+            at -= 1;
+        }
+        Some(slot)
+    }
+
+    /// Returns the slot at `position` like `slot_at` does, and moves the finger there.
+    fn slot_at_mut(&mut self, position: usize) -> Option<usize> {
+        let slot = self.slot_at(position)?;
+        self.finger.set(slot, position);
+        Some(slot)
+    }
+
+    /// Closes the list into a ring and cuts it again right before `new_head`.
+    fn rotate_to(&mut self, new_head: usize) {
+        self.finger.clear();
+        let (Some(head), Some(tail)) = (self.head, self.tail) else {
+            return;
+        };
+        let Some(new_tail) = self.occupied(new_head).prev() else {
+            return;
+        };
+        self.occupied_mut(tail).set_next(Some(head));
+        self.occupied_mut(head).set_prev(Some(tail));
+        self.occupied_mut(new_tail).set_next(None);
+        self.occupied_mut(new_head).set_prev(None);
+        self.head = Some(new_head);
+        self.tail = Some(new_tail);
+    }
+
+    /// Returns the number of elements from `start` to `end`, both inclusive, or `None` if
+    /// either index is not valid or `end` cannot be reached from `start`.
+    fn range_len(&self, start: Index<T, I>, end: Index<T, I>) -> Option<usize> {
+        if !self.in_sequence(start) || !self.in_sequence(end) {
+            return None;
+        }
+        let mut len = 1;
+        let mut slot = start.slot();
+        while slot != end.slot() {
+            slot = self.occupied(slot).next()?;
+            len += 1;
+        }
+        Some(len)
+    }
+
+    /// Returns `true` if the chain from `start` to `end` is in the list and holds `slot`.
+    /// Stops at the end of the list if `end` cannot be reached from `start`.
+    fn range_holds(&self, start: usize, end: usize, slot: usize) -> bool {
+        let mut iter = Some(start);
+        while let Some(current) = iter {
+            if current == slot {
+                return true;
+            }
+            if current == end {
+                return false;
+            }
+            iter = self.occupied(current).next();
+        }
+        false
+    }
+
+    /// Returns `true` if the range from `first` to `last` can be moved next to `target`.
+    fn can_move_range(&self, first: Index<T, I>, last: Index<T, I>, target: Index<T, I>) -> bool {
+        self.in_sequence(target)
+            && self.range_len(first, last).is_some()
+            && !self.range_holds(first.slot(), last.slot(), target.slot())
+    }
+
+    /// Detaches the chain from `first` to `last` from the list by linking its neighbors to
+    /// each other. The outer links of `first` and `last` are left untouched.
+    fn unlink_range(&mut self, first: usize, last: usize) {
+        self.finger.clear();
+        trace_event!(trace, first, last, "unlinked chain");
+        let prev = self.occupied(first).prev();
+        let next = self.occupied(last).next();
+        match prev {
+            Some(prev) => self.occupied_mut(prev).set_next(next),
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.occupied_mut(next).set_prev(prev),
+            None => self.tail = prev,
+        }
+    }
+
+    /// Puts `slot` on the free list and returns its element. The slot must already be
+    /// unlinked from the list.
+    fn free(&mut self, slot: usize) -> T {
+        self.finger.clear();
+        let Occupied(oc) = std::mem::replace(&mut self.contents[slot], Free { next_free: None })
+        else {
+            panic!("Corrupted list");
+        };
+        trace_event!(
+            trace,
+            slot,
+            generation = oc.generation.to_usize(),
+            "freed slot"
+        );
+        // A reused slot would get the saturated generation again and revive stale indices.
+        if oc.generation != I::MAX {
+            match &mut self.ordered_free {
+                Some(ordered_free) => {
+                    ordered_free.insert(slot);
+                }
+                None => {
+                    self.contents[slot] = Free {
+                        next_free: self.next_free.map(Slot::new),
+                    };
+                    self.next_free = Some(slot);
+                }
+            }
+        } else {
+            trace_event!(
+                warn,
+                slot,
+                "generation saturated, the slot will not be reused"
+            );
+        }
+        self.stats.removed();
+        self.count -= 1;
+        oc.item
+    }
+
+    /// Moves the element at `index` from this list into a slot of `dest`, linked in between
+    /// the adjacent `prev` and `next` there, and returns its index in `dest`.
+    fn transfer_between(
+        &mut self,
+        index: Index<T, I>,
+        dest: &mut IndexList<T, I, A>,
+        prev: Option<usize>,
+        next: Option<usize>,
+    ) -> Option<Index<T, I>> {
+        self.check_index(index).ok()?;
+        let slot = index.slot();
+        if self.is_unlinked(slot) {
+            // `free` takes the element off `count`.
+            self.detached -= 1;
+            self.count += 1;
+        } else {
+            self.check_links(slot).ok()?;
+            self.unlink_range(slot, slot);
+        }
+        if index.generation == self.generation {
+            self.generation = self.generation.saturating_inc();
+        }
+        let moved = dest.allocate(self.free(slot));
+        dest.splice(prev, next, moved, moved);
+        self.debug_validate();
+        dest.debug_validate();
+        Some(dest.index_at(moved))
+    }
+
+    /// Moves the elements from `first` to the tail, in order, into a new list with room for
+    /// `capacity` elements.
+    fn split_off_from(&mut self, first: usize, capacity: usize) -> IndexList<T, I, A> {
+        let mut rest = self.empty_like(capacity);
+        let tail = self.tail.expect("Corrupted list");
+        self.unlink_range(first, tail);
+        self.generation = self.generation.saturating_inc();
+        let mut iter = Some(first);
+        while let Some(slot) = iter {
+            iter = self.occupied(slot).next();
+            rest.push_back(self.free(slot));
+        }
+        self.debug_validate();
+        rest
+    }
+
+    /// Returns the slot of the first element, in list order, that satisfies `predicate`.
+    fn find_slot<P>(&self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let mut iter = self.head;
+        while let Some(slot) = iter {
+            let oc = self.occupied(slot);
+            if predicate(&oc.item) {
+                return Some(slot);
+            }
+            iter = oc.next();
+        }
+        None
+    }
+
+    /// Returns the slot of the last element, in list order, that satisfies `predicate`.
+    fn rfind_slot<P>(&self, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let mut iter = self.tail;
+        while let Some(slot) = iter {
+            let oc = self.occupied(slot);
+            if predicate(&oc.item) {
+                return Some(slot);
+            }
+            iter = oc.prev();
+        }
+        None
+    }
+
+    /// Returns the slot of the element with the best key, where a key replaces the best one
+    /// so far if `better` holds for how it compares to it.
+    fn extreme_slot_by_key<K, F>(&self, mut f: F, better: fn(Ordering) -> bool) -> Option<usize>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let mut best: Option<(usize, K)> = None;
+        let mut iter = self.head;
+        while let Some(slot) = iter {
+            let oc = self.occupied(slot);
+            let key = f(&oc.item);
+            if best.as_ref().is_none_or(|(_, best)| better(key.cmp(best))) {
+                best = Some((slot, key));
+            }
+            iter = oc.next();
+        }
+        best.map(|(slot, _)| slot)
+    }
+
+    /// Returns the signed number of hops from slot `from` to slot `to`, searching in both
+    /// directions at once.
+    fn hops(&self, from: usize, to: usize) -> isize {
+        let mut forward = Some(from);
+        let mut backward = Some(from);
+        let mut hops = 0;
+        loop {
+            if forward == Some(to) {
+                return hops;
+            }
+            if backward == Some(to) {
+                return -hops;
+            }
+            if forward.is_none() && backward.is_none() {
+                panic!("Corrupted list");
+            }
+            forward = forward.and_then(|slot| self.occupied(slot).next());
+            backward = backward.and_then(|slot| self.occupied(slot).prev());
+            hops += 1;
+        }
+    }
+
+    fn check_index(&self, index: Index<T, I>) -> Result<(), AccessError> {
+        if index.list != self.id {
+            return Err(AccessError::ForeignIndex);
+        }
+        match self.contents.get(index.slot()) {
+            Some(Occupied(oc)) if oc.generation == index.generation => Ok(()),
+            Some(Occupied(_)) => Err(AccessError::StaleGeneration),
+            Some(Free { .. }) => Err(AccessError::FreedSlot),
+            None => Err(AccessError::ForeignIndex),
+        }
+    }
+
+    /// Returns `true` if the occupied `slot` is detached: it has no neighbors and is not
+    /// the only element of the list either.
+    fn is_unlinked(&self, slot: usize) -> bool {
+        let oc = self.occupied(slot);
+        oc.prev().is_none() && oc.next().is_none() && self.head != Some(slot)
+    }
+
+    /// Returns the slots of the detached elements, in storage order.
+    fn detached_slots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.contents
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| matches!(entry, Occupied(_)))
+            .map(|(slot, _)| slot)
+            .filter(|&slot| self.is_unlinked(slot))
+    }
+
+    /// Returns `true` if the index is valid and its element is in the order of the list.
+    fn in_sequence(&self, index: Index<T, I>) -> bool {
+        self.contains_index(index) && !self.is_unlinked(index.slot())
+    }
+
+    /// Checks that the neighbors of the occupied `slot` link back to it.
+    fn check_links(&self, slot: usize) -> Result<(), AccessError> {
+        if self.is_unlinked(slot) {
+            return Err(AccessError::Detached);
+        }
+        let oc = self.occupied(slot);
+        let prev_links_back = match oc.prev() {
+            Some(prev) => matches!(
+                self.contents.get(prev),
+                Some(Occupied(prev)) if prev.next() == Some(slot)
+            ),
+            None => self.head == Some(slot),
+        };
+        let next_links_back = match oc.next() {
+            Some(next) => matches!(
+                self.contents.get(next),
+                Some(Occupied(next)) if next.prev() == Some(slot)
+            ),
+            None => self.tail == Some(slot),
+        };
+        if prev_links_back && next_links_back {
+            Ok(())
+        } else {
+            Err(AccessError::CorruptedLinks)
+        }
+    }
+
+    /// Links the unlinked `slot` into the list right before `before`.
+    fn link_before(&mut self, before: usize, slot: usize) {
+        self.finger.clear();
+        self.contents.link_before(&mut self.head, before, slot);
+    }
+
+    /// Links the unlinked `slot` into the list right after `after`.
+    fn link_after(&mut self, after: usize, slot: usize) {
+        self.finger.clear();
+        self.contents.link_after(&mut self.tail, after, slot);
+    }
+
+    /// Links the unlinked chain from `first` to `last` into the list between `prev` and
+    /// `next`, which must be adjacent. `None` stands for the respective end of the list.
+    fn splice(&mut self, prev: Option<usize>, next: Option<usize>, first: usize, last: usize) {
+        self.finger.clear();
+        trace_event!(trace, ?prev, ?next, first, last, "spliced chain");
+        self.occupied_mut(first).set_prev(prev);
+        self.occupied_mut(last).set_next(next);
+        match prev {
+            Some(prev) => self.occupied_mut(prev).set_next(Some(first)),
+            None => self.head = Some(first),
+        }
+        match next {
+            Some(next) => self.occupied_mut(next).set_prev(Some(last)),
+            None => self.tail = Some(last),
+        }
+    }
+
+    /// Stores `items` in a chain of their own and splices it in between the adjacent `prev`
+    /// and `next`.
+    fn insert_chain(
+        &mut self,
+        prev: Option<usize>,
+        next: Option<usize>,
+        items: impl IntoIterator<Item = T>,
+    ) -> Vec<Index<T, I>> {
+        // Collected first, so that a panicking iterator cannot leave unlinked elements behind.
+        let items: Vec<T> = items.into_iter().collect();
+        let capacity = self.contents.capacity();
+        self.contents
+            .reserve(items.len().saturating_sub(self.free_slots()));
+        if self.contents.capacity() != capacity {
+            self.stats.grew();
+        }
+        let slots: Vec<usize> = items.into_iter().map(|item| self.allocate(item)).collect();
+        let (Some(&first), Some(&last)) = (slots.first(), slots.last()) else {
+            return Vec::new();
+        };
+        for pair in slots.windows(2) {
+            self.occupied_mut(pair[0]).set_next(Some(pair[1]));
+            self.occupied_mut(pair[1]).set_prev(Some(pair[0]));
+        }
+        self.splice(prev, next, first, last);
+        self.debug_validate();
+        slots.into_iter().map(|slot| self.index_at(slot)).collect()
+    }
+
+    /// Empties the list and returns its elements in list order. The generation moves past
+    /// every element taken, so that no index into the list is revived by later insertions.
+    fn take_items(&mut self) -> Vec<T> {
+        let taken = std::mem::replace(self, self.empty_like(0));
+        self.generation = taken.generation.saturating_inc();
+        self.set_reuse_policy(taken.reuse_policy());
+        self.max_capacity = taken.max_capacity;
+        self.stats = taken.stats;
+        taken.into_items()
+    }
+
+    /// Returns `true` if the list holds its maximum capacity of elements.
+    fn is_full(&self) -> bool {
+        self.max_capacity.is_some_and(|max| self.count >= max)
+    }
+
+    /// Stores `item` in a free slot, or a new one, without linking it into the list.
+    fn allocate(&mut self, item: T) -> usize {
+        self.finger.clear();
+        self.count += 1;
+        let (len, capacity) = (self.contents.len(), self.contents.capacity());
+        // The ordered free slots are not chained, so the lowest one makes a free list of its own.
+        let next_free = match &mut self.ordered_free {
+            Some(ordered_free) => &mut ordered_free.pop_first(),
+            None => &mut self.next_free,
+        };
+        let Ok(slot) = self.contents.allocate(next_free, item, self.generation) else {
+            unreachable!("the storage of an IndexList grows as needed");
+        };
+        if self.contents.capacity() != capacity {
+            self.stats.grew();
+        }
+        let reused = slot < len;
+        self.stats.inserted(reused);
+        trace_event!(
+            trace,
+            slot,
+            generation = self.generation.to_usize(),
+            reused,
+            "allocated slot"
+        );
+        slot
+    }
+
+    /// Consumes the list and returns its elements in list order, dropping detached elements.
+    fn into_items(self) -> Vec<T> {
+        // Detached elements have no links either, so only a list without them can be taken
+        // as a whole.
+        let in_storage_order = self.detached == 0
+            && self.head.is_none_or(|head| head == 0)
+            && self.contents.iter().enumerate().all(|(slot, e)| match e {
+                Occupied(oc) => oc.next() == Some(slot + 1).filter(|&next| next < self.count),
+                Free { .. } => false,
+            });
+        if in_storage_order {
+            return self
+                .contents
+                .into_iter()
+                .map(|e| match e {
+                    Occupied(oc) => oc.item,
+                    Free { .. } => panic!("Corrupted list"),
+                })
+                .collect();
+        }
+        let order = self.slots();
+        self.into_items_at(&order)
+    }
+
+    /// Consumes the list and returns the elements in `order`, dropping all others.
+    fn into_items_at(self, order: &[usize]) -> Vec<T> {
+        let mut items: Vec<Option<T>> = self
+            .contents
+            .into_iter()
+            .map(|e| match e {
+                Occupied(oc) => Some(oc.item),
+                Free { .. } => None,
+            })
+            .collect();
+        order
+            .iter()
+            .map(|&slot| items[slot].take().expect("Corrupted list"))
+            .collect()
+    }
+
+    /// Returns the slots of the list in list order.
+    fn slots(&self) -> Vec<usize> {
+        let mut slots = Vec::with_capacity(self.count);
+        let mut iter = self.head;
+        while let Some(slot) = iter {
+            slots.push(slot);
+            iter = self.occupied(slot).next();
+        }
+        slots
+    }
+
+    /// Rewrites `head`, `tail` and all links so that the list follows `order`.
+    fn relink(&mut self, order: &[usize]) {
+        self.finger.clear();
+        self.head = order.first().copied();
+        self.tail = order.last().copied();
+        for (position, &slot) in order.iter().enumerate() {
+            let oc = self.occupied_mut(slot);
+            oc.set_prev(position.checked_sub(1).map(|p| order[p]));
+            oc.set_next(order.get(position + 1).copied());
+        }
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> IndexList<T, I, A>
+where
+    T: PartialEq,
+{
+    /// Returns the index of the first occurrence of `item` in the list, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// list.push_back(10);
+    /// assert_eq!(list.index_of(&5), Some(five));
+    /// assert!(list.index_of(&20).is_none());
+    /// ```
+    pub fn index_of(&self, item: &T) -> Option<Index<T, I>> {
+        let mut iter = self.head;
+        while let Some(index) = iter {
+            let entry = &self.contents[index];
+            match entry {
+                Occupied(oc) => {
+                    if &oc.item == item {
+                        return Some(Index::new(index, oc.generation, self.id));
+                    }
+                    iter = oc.next();
+                }
+                _ => panic!("Corrupted list"),
+            }
+        }
+        None
+    }
+
+    /// Returns the index of the last occurrence of `item` in the list, if it exists.
+    ///
+    /// The list is searched backwards, starting at the tail.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// let second_five = list.push_back(5);
+    /// assert_eq!(list.last_index_of(&5), Some(second_five));
+    /// assert!(list.last_index_of(&20).is_none());
+    /// ```
+    pub fn last_index_of(&self, item: &T) -> Option<Index<T, I>> {
+        let slot = self.rfind_slot(|probe| probe == item)?;
+        Some(self.index_at(slot))
+    }
+
+    /// Returns an iterator over the indices of all occurrences of `item`, in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let first = list.push_back(5);
+    /// list.push_back(10);
+    /// let second = list.push_back(5);
+    /// assert_eq!(list.indices_of(&5).collect::<Vec<_>>(), vec![first, second]);
+    /// ```
+    pub fn indices_of<'a>(&'a self, item: &'a T) -> impl Iterator<Item = Index<T, I>> + 'a {
+        self.find_all(move |probe| probe == item)
+    }
+
+    /// Returns `true` if the list contains the specified value.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// assert!(list.contains(&5));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        self.iter().any(|e| e == value)
+    }
+
+    /// Removes the first element equal to `item` and returns it, or `None` if there is no
+    /// such element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// list.push_back(5);
+    /// assert_eq!(list.remove_item(&5), Some(5));
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![10, 5]);
+    /// assert_eq!(list.remove_item(&15), None);
+    /// ```
+    pub fn remove_item(&mut self, item: &T) -> Option<T> {
+        let slot = self.find_slot(|probe| probe == item)?;
+        self.remove(self.index_at(slot))
+    }
+
+    /// Returns `true` if the first elements of the list are equal to the elements of
+    /// `needle`, in order. An empty `needle` is a prefix of every list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert!(list.starts_with(&[1, 2]));
+    /// assert!(!list.starts_with(&[2, 3]));
+    /// ```
+    pub fn starts_with<'a, N>(&self, needle: N) -> bool
+    where
+        N: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        let mut iter = self.iter();
+        needle.into_iter().all(|item| iter.next() == Some(item))
+    }
+
+    /// Returns `true` if the last elements of the list are equal to the elements of
+    /// `needle`, in order. An empty `needle` is a suffix of every list.
+    ///
+    /// Both the list and `needle` are walked backwards from their ends, so only as many
+    /// elements are compared as `needle` holds.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert!(list.ends_with(&[2, 3]));
+    /// assert!(!list.ends_with(&[1, 2]));
+    /// ```
+    pub fn ends_with<'a, N>(&self, needle: N) -> bool
+    where
+        N: IntoIterator<Item = &'a T>,
+        N::IntoIter: DoubleEndedIterator,
+        T: 'a,
+    {
+        let mut iter = self.tail;
+        needle.into_iter().rev().all(|item| match iter {
+            Some(slot) => {
+                let oc = self.occupied(slot);
+                iter = oc.prev();
+                oc.item == *item
+            }
+            None => false,
+        })
+    }
+
+    /// Removes consecutive repeated elements from the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(1);
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(1);
+    /// list.dedup();
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 1]);
+    /// ```
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b);
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> IndexList<T, I, A>
+where
+    T: Ord,
+{
+    /// Sorts the list, preserving the order of equal elements.
+    ///
+    /// Indices into the list remain valid after sorting.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(3);
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.sort();
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn sort(&mut self) {
+        self.sort_by(Ord::cmp);
+    }
+
+    /// Consumes the list and returns its elements in a sorted `Vec`. The order of equal
+    /// elements is preserved.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(3);
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert_eq!(list.into_sorted_vec(), vec![1, 2, 3]);
+    /// ```
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted_vec_by(Ord::cmp)
+    }
+
+    /// Inserts an element into a sorted list, keeping it sorted, and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.insert_sorted(3);
+    /// list.insert_sorted(1);
+    /// list.insert_sorted(2);
+    /// assert_eq!(list.iter().copied().collect::<Vec<i32>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn insert_sorted(&mut self, item: T) -> Index<T, I> {
+        self.insert_sorted_by(item, Ord::cmp)
+    }
+
+    /// Binary searches a sorted list for `item`. See [`IndexList::binary_search_by`] for the
+    /// meaning of the result.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(3);
+    /// assert_eq!(list.binary_search(&1), Ok(one));
+    /// assert_eq!(list.binary_search(&0), Err(Some(one)));
+    /// ```
+    pub fn binary_search(&self, item: &T) -> Result<Index<T, I>, Option<Index<T, I>>> {
+        self.binary_search_by(|probe| probe.cmp(item))
+    }
+}
+
+/// Formats the elements in list order, like a slice: `[1, 2, 3]`. Formatting options such as
+/// precision are applied to every element.
+///
+/// # Examples
+/// ```rust
+/// let mut list = indexlist::IndexList::new();
+/// list.push_back(1.5);
+/// list.push_front(0.25);
+/// assert_eq!(list.to_string(), "[0.25, 1.5]");
+/// assert_eq!(format!("{:.1}", list), "[0.2, 1.5]");
+/// ```
+impl<T: fmt::Display, I: IndexType, A: Allocator + Clone> fmt::Display for IndexList<T, I, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (position, item) in self.iter().enumerate() {
+            if position > 0 {
+                f.write_str(", ")?;
+            }
+            item.fmt(f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// Accesses the element at the given index.
+///
+/// # Panics
+///
+/// Panics if the index does not refer to an element of the list, use [`IndexList::get`] to
+/// handle that case.
+///
+/// # Examples
+/// ```rust
+/// let mut list = indexlist::IndexList::new();
+/// let five = list.push_back(5);
+/// list[five] += 1;
+/// assert_eq!(list[five], 6);
+/// ```
+impl<T, I: IndexType, A: Allocator + Clone> std::ops::Index<Index<T, I>> for IndexList<T, I, A> {
+    type Output = T;
+
+    fn index(&self, index: Index<T, I>) -> &T {
+        match self.try_get(index) {
+            Ok(item) => item,
+            Err(error) => panic!("invalid index into IndexList: {error}"),
+        }
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> std::ops::IndexMut<Index<T, I>> for IndexList<T, I, A> {
+    fn index_mut(&mut self, index: Index<T, I>) -> &mut T {
+        match self.try_get_mut(index) {
+            Ok(item) => item,
+            Err(error) => panic!("invalid index into IndexList: {error}"),
+        }
+    }
+}
+
+/// Builds a list of the elements in order, with the n-th element in slot n.
+impl<T> From<LinkedList<T>> for IndexList<T> {
+    fn from(items: LinkedList<T>) -> Self {
+        let mut list = IndexList::with_capacity(items.len());
+        list.insert_chain(None, None, items);
+        list
+    }
+}
+
+/// Builds a list of the elements in order, with the n-th element in slot n.
+impl<T> From<VecDeque<T>> for IndexList<T> {
+    fn from(items: VecDeque<T>) -> Self {
+        let mut list = IndexList::with_capacity(items.len());
+        list.insert_chain(None, None, items);
+        list
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> From<IndexList<T, I, A>> for LinkedList<T> {
+    fn from(list: IndexList<T, I, A>) -> Self {
+        list.into_items().into_iter().collect()
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> From<IndexList<T, I, A>> for VecDeque<T> {
+    fn from(list: IndexList<T, I, A>) -> Self {
+        list.into_items().into()
+    }
+}
+
+impl<'a, T, I: IndexType, A: Allocator + Clone> IntoIterator for &'a IndexList<T, I, A> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T, I, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T: 'a, I: IndexType = usize, A: Allocator + Clone = Global> {
+    list: &'a IndexList<T, I, A>,
+    index: Option<Index<T, I>>,
+}
+
+impl<'a, T, I: IndexType, A: Allocator + Clone> Iterator for Iter<'a, T, I, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.index?;
+        self.index = self.list.next_index(index);
+        self.list.get(index)
+    }
+}
+
+impl<'a, T, I: IndexType, A: Allocator + Clone> IntoIterator for &'a mut IndexList<T, I, A> {
+    type Item = &'a mut T;
+
+    type IntoIter = IterMut<'a, T, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Holds a mutable borrow of every occupied entry, split out of the storage up front, and
+/// hands each one out exactly once while following the links.
+pub struct IterMut<'a, T: 'a, I: IndexType = usize> {
+    /// The entries indexed by slot, `None` for free slots and entries already handed out.
+    entries: Vec<Option<&'a mut OccupiedEntry<T, I>>>,
+    next: Option<usize>,
+}
+
+impl<'a, T, I: IndexType> Iterator for IterMut<'a, T, I> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.next?;
+        let oc = self.entries[slot].take().expect("Corrupted list");
+        self.next = oc.next();
+        Some(&mut oc.item)
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> IntoIterator for IndexList<T, I, A> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T, I, A>;
+
+    /// Returns an consuming iterator over the elements of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// for item in list.into_iter() {
+    ///     println!("{}", item);
+    /// }
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+/// The consuming iterator returned by [`IndexList::into_iter`].
+///
+/// Each element is popped off the front of the list as it is yielded, so the list left
+/// behind is valid at every step. Dropping the iterator drops the elements not yet yielded.
+pub struct IntoIter<T, I: IndexType = usize, A: Allocator + Clone = Global> {
+    list: IndexList<T, I, A>,
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> Iterator for IntoIter<T, I, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len(), Some(self.list.len()))
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> DoubleEndedIterator for IntoIter<T, I, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> ExactSizeIterator for IntoIter<T, I, A> {}
+
+/// The iterator returned by [`IndexList::drain_range`].
+pub struct DrainRange<'a, T, I: IndexType = usize, A: Allocator + Clone = Global> {
+    list: &'a mut IndexList<T, I, A>,
+    next: Option<usize>,
+    remaining: usize,
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> Iterator for DrainRange<'_, T, I, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let slot = self.next.expect("Corrupted list");
+        self.next = self.list.occupied(slot).next();
+        self.remaining -= 1;
+        self.list.unlink_range(slot, slot);
+        let item = self.list.free(slot);
+        self.list.debug_validate();
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> Drop for DrainRange<'_, T, I, A> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(slot: usize) -> Option<Slot> {
+        Some(Slot::new(slot))
+    }
+
+    fn to_vec_forward<T: Copy>(list: &IndexList<T>) -> Vec<T> {
+        let mut result = vec![];
+        let mut iter = list.head;
+        while let Some(next) = iter {
+            match &list.contents[next] {
+                Occupied(oc) => {
+                    iter = oc.next();
+                    result.push(oc.item);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        result
+    }
+
+    fn check_invariants<T>(list: &IndexList<T>) {
+        assert_eq!(list.validate(), Ok(()));
+
+        if list.contents.is_empty() {
+            assert_eq!(list.head, None);
+            assert_eq!(list.tail, None);
+            assert_eq!(list.next_free, None);
+            assert_eq!(list.count, 0);
+
+            return;
+        }
+
+        if list.contents.len() == 1 {
+            match &list.contents[0] {
+                Free { next_free } => {
+                    assert_eq!(*next_free, None);
+                    assert_eq!(list.next_free, Some(0));
+                    assert_eq!(list.head, None);
+                    assert_eq!(list.tail, None);
+                    assert_eq!(list.count, 0);
+                }
+                Occupied(oc) => {
+                    assert_eq!(list.next_free, None);
+                    assert_eq!(list.head, Some(0));
+                    assert_eq!(list.tail, Some(0));
+                    assert_eq!(list.generation, oc.generation);
+                    assert_eq!(oc.prev, None);
+                    assert_eq!(oc.next, None);
+                    assert_eq!(list.count, 1);
+                }
+            }
+
+            return;
+        }
+
+        let count = list
+            .contents
+            .iter()
+            .filter(|e| matches!(e, Occupied(_)))
+            .count();
+        assert_eq!(list.count + list.detached, count);
+
+        let mut indexes = vec![];
+        let mut next = list.next_free;
+        let mut free_count = 0;
+        while let Some(index) = next {
+            assert!(!indexes.contains(&index));
+            indexes.push(index);
+            let entry = &list.contents[index];
+            match entry {
+                Free { next_free } => {
+                    next = next_free.map(Slot::get);
+                }
+                _ => unreachable!(),
+            }
+            free_count += 1;
+        }
+        for &index in list.ordered_free.iter().flatten() {
+            assert!(matches!(list.contents[index], Free { next_free: None }));
+            free_count += 1;
+        }
+        assert_eq!(free_count, list.contents.len() - count);
+
+        let mut iter = list.head;
+        let mut last = list.head;
+        let mut occupied_count = 0;
+        while let Some(next) = iter {
+            match &list.contents[next] {
+                Occupied(oc) => {
+                    last = iter;
+                    iter = oc.next();
+                    occupied_count += 1;
+                }
+                _ => unreachable!(),
+            }
+        }
+        assert_eq!(last, list.tail);
+        assert_eq!(occupied_count, list.count);
+
+        let mut iter = list.tail;
+        let mut last = list.tail;
+        let mut occupied_count = 0;
+        while let Some(prev) = iter {
+            match &list.contents[prev] {
+                Occupied(oc) => {
+                    last = iter;
+                    iter = oc.prev();
+                    occupied_count += 1;
+                }
+                _ => unreachable!(),
+            }
+        }
+        assert_eq!(last, list.head);
+        assert_eq!(occupied_count, list.count);
+    }
+
+    #[test]
+    fn create_index() {
+        let index: Index<i32> = Index::new(1, 2, ListId::next());
+        assert_eq!(index.slot(), 1);
+        assert_eq!(index.generation, 2);
+    }
+
+    #[test]
+    fn create_list() {
+        let _list: IndexList<i32> = IndexList::new();
+    }
+
+    #[test]
+    fn contains() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+
+        assert!(list.contains(&5));
+    }
+
+    #[test]
+    fn get() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+
+        let entry = list.get(five);
+
+        assert!(entry.is_some());
+        assert_eq!(entry.unwrap(), &5);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+
+        let entry = list.get_mut(five);
+
+        assert!(entry.is_some());
+
+        assert_eq!(entry.unwrap(), &mut 5);
+    }
+
+    #[test]
+    fn update() {
+        let mut list = IndexList::new();
+        let five = list.push_back(5);
+        let mut calls = 0;
+        assert!(list.update(five, |item| {
+            calls += 1;
+            *item *= 2;
+        }));
+        list.remove(five);
+        assert!(!list.update(five, |_| calls += 1));
+        assert_eq!(calls, 1);
+        let one = list.push_back(1);
+        assert!(!list.update(five, |_| calls += 1));
+        assert_eq!(list.get(one), Some(&1));
+    }
+
+    #[test]
+    fn next_index() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+        let _ten = list.push_back(10);
+
+        let ten_index = list.next_index(five).unwrap();
+
+        let ten_value = list.get(ten_index);
+
+        assert_eq!(ten_value.unwrap(), &10);
+        assert_eq!(None, list.next_index(ten_index));
+    }
+
+    #[test]
+    fn prev_index() {
+        let mut list = IndexList::new();
+
+        let _five = list.push_back(5);
+        let ten = list.push_back(10);
+
+        let five_index = list.prev_index(ten).unwrap();
+
+        let five_value = list.get(five_index);
+
+        assert_eq!(five_value.unwrap(), &5);
+        assert_eq!(None, list.prev_index(five_index));
+    }
+
+    #[test]
+    fn advance_and_retreat() {
+        let mut list = IndexList::new();
+        let indices: Vec<_> = (0..5).map(|item| list.push_back(item)).collect();
+
+        assert_eq!(list.advance(indices[1], 0), Some(indices[1]));
+        assert_eq!(list.advance(indices[1], 3), Some(indices[4]));
+        assert_eq!(list.advance(indices[1], 4), None);
+        assert_eq!(list.retreat(indices[3], 3), Some(indices[0]));
+        assert_eq!(list.retreat(indices[3], 4), None);
+        assert_eq!(list.retreat(indices[3], usize::MAX), None);
+
+        list.remove(indices[2]);
+        assert_eq!(list.advance(indices[1], 1), Some(indices[3]));
+        assert_eq!(list.advance(indices[2], 0), None);
+    }
+
+    #[test]
+    fn neighbors() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
+        let fifteen = list.push_back(15);
+
+        assert_eq!(list.neighbors(five), (None, Some(&10)));
+        assert_eq!(list.neighbors(ten), (Some(&5), Some(&15)));
+        assert_eq!(list.neighbors(fifteen), (Some(&10), None));
+        assert_eq!(
+            list.neighbor_entries(ten),
+            (Some((five, &5)), Some((fifteen, &15)))
+        );
+
+        list.remove(ten);
+        assert_eq!(list.neighbors(ten), (None, None));
+        assert_eq!(list.neighbor_entries(five), (None, Some((fifteen, &15))));
+    }
+
+    #[test]
+    fn raw_index_round_trip() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
+        list.remove(five);
+        let fifteen = list.push_back(15);
+
+        let (slot, generation) = fifteen.into_raw();
+        assert_eq!(slot, five.slot());
+        assert_eq!(list.index_from_raw(slot, generation), Some(fifteen));
+        let (slot, generation) = five.into_raw();
+        assert_eq!(list.index_from_raw(slot, generation), None);
+        let (slot, generation) = ten.into_raw();
+        assert_eq!(list.index_from_raw(slot, generation), Some(ten));
+        assert_eq!(list.index_from_raw(usize::MAX, 0), None);
+        assert_eq!(list.index_from_raw(0, usize::MAX), None);
+
+        let other: IndexList<i32> = IndexList::new();
+        assert_eq!(other.index_from_raw(slot, generation), None);
+    }
+
+    #[test]
+    fn circular_navigation_wraps_around() {
+        let mut list = IndexList::new();
+
+        let one = list.push_back(1);
+        assert_eq!(list.next_index_circular(one), Some(one));
+        assert_eq!(list.prev_index_circular(one), Some(one));
+
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        assert_eq!(list.next_index_circular(three), Some(one));
+        assert_eq!(list.prev_index_circular(one), Some(three));
+        assert_eq!(list.next_index_circular(one), Some(two));
+
+        list.remove(two);
+        assert_eq!(list.next_index_circular(two), None);
+        assert_eq!(list.prev_index_circular(two), None);
+        assert_eq!(list.ring_iter_from(two).count(), 0);
+    }
+
+    #[test]
+    fn ring_iter_visits_every_element_once() {
+        let mut list = IndexList::new();
+        let indexes: Vec<_> = (0..5).map(|item| list.push_back(item)).collect();
+
+        for (start, &index) in indexes.iter().enumerate() {
+            let ring: Vec<_> = list.ring_iter_from(index).collect();
+            let expected: Vec<_> = (0..5).map(|step| (start + step) % 5).collect();
+            assert_eq!(
+                ring.iter().map(|(_, item)| **item).collect::<Vec<_>>(),
+                expected
+            );
+            assert_eq!(ring[0].0, index);
+        }
+    }
+
+    #[test]
+    fn iter_cyclic() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        assert_eq!(
+            list.iter_cyclic(two).copied().collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        list.remove(one);
+        assert_eq!(list.iter_cyclic(one).count(), 0);
+        assert_eq!(list.iter_cyclic(two).copied().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn insert_into_empty_list() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list.contents[0],
+            Occupied(OccupiedEntry {
+                item: 5,
+                next: None,
+                prev: None,
+                generation: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn insert_thrice() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        list.push_back(10);
+        list.push_back(15);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list.contents[0],
+            Occupied(OccupiedEntry {
+                item: 5,
+                next: link(1),
+                prev: None,
+                generation: 0,
+            })
+        );
+
+        assert_eq!(
+            list.contents[1],
+            Occupied(OccupiedEntry {
+                item: 10,
+                next: link(2),
+                prev: link(0),
+                generation: 0,
+            })
+        );
+
+        assert_eq!(
+            list.contents[2],
+            Occupied(OccupiedEntry {
+                item: 15,
+                next: None,
+                prev: link(1),
+                generation: 0,
+            })
+        );
+
+        assert_eq!(list.head, Some(0));
+        assert_eq!(list.tail, Some(2));
+    }
+
+    #[test]
+    fn push_back_remove_push_back() {
+        let mut list = IndexList::new();
+
+        list.push_back(1);
+        let two = list.push_back(2);
+        list.push_back(3);
+        list.remove(two).unwrap();
+
+        check_invariants(&list);
+        assert_eq!(list.next_free.unwrap(), 1);
+
+        list.push_back(4);
+        check_invariants(&list);
+        assert_eq!(list.next_free, None);
+
+        list.push_back(5);
+        list.push_back(6);
+
+        let three = list.index_of(&3).unwrap();
+        list.remove(three);
+        check_invariants(&list);
+        assert_eq!(list.next_free.unwrap(), 2);
+        let five = list.index_of(&5).unwrap();
+        list.remove(five).unwrap();
+        check_invariants(&list);
+        assert_eq!(list.next_free.unwrap(), 3);
+    }
+
+    #[test]
+    fn remove_middle() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        let removed = list.remove(ten).unwrap();
+
+        check_invariants(&list);
+
+        assert_eq!(removed, 10);
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([
+                    Occupied(OccupiedEntry {
+                        item: 5,
+                        next: link(2),
+                        prev: None,
+                        generation: 0,
+                    }),
+                    Free { next_free: None },
+                    Occupied(OccupiedEntry {
+                        item: 15,
+                        next: None,
+                        prev: link(0),
+                        generation: 0,
+                    }),
+                ]),
+                generation: 1,
+                next_free: Some(1),
+                ordered_free: None,
+                head: Some(0),
+                tail: Some(2),
+                count: 2,
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn remove_head() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+        list.push_back(10);
+        list.push_back(15);
+
+        let removed = list.remove(five).unwrap();
+
+        check_invariants(&list);
+        assert_eq!(removed, 5);
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([
+                    Free { next_free: None },
+                    Occupied(OccupiedEntry {
+                        item: 10,
+                        next: link(2),
+                        prev: None,
+                        generation: 0,
+                    }),
+                    Occupied(OccupiedEntry {
+                        item: 15,
+                        next: None,
+                        prev: link(1),
+                        generation: 0,
+                    }),
+                ]),
+                generation: 1,
+                next_free: Some(0),
+                ordered_free: None,
+                head: Some(1),
+                tail: Some(2),
+                count: 2,
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn remove_tail() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        list.push_back(10);
+        let fifteen = list.push_back(15);
+
+        let removed = list.remove(fifteen).unwrap();
+
+        check_invariants(&list);
+        assert_eq!(removed, 15);
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([
+                    Occupied(OccupiedEntry {
+                        item: 5,
+                        next: link(1),
+                        prev: None,
+                        generation: 0,
+                    }),
+                    Occupied(OccupiedEntry {
+                        item: 10,
+                        next: None,
+                        prev: link(0),
+                        generation: 0,
+                    }),
+                    Free { next_free: None },
+                ]),
+                generation: 1,
+                next_free: Some(2),
+                ordered_free: None,
+                head: Some(0),
+                tail: Some(1),
+                count: 2,
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn remove_only() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+
+        let removed = list.remove(five).unwrap();
+
+        check_invariants(&list);
+        assert_eq!(removed, 5);
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([Free { next_free: None },]),
+                generation: 1,
+                next_free: Some(0),
+                ordered_free: None,
+                head: None,
+                tail: None,
+                count: 0,
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn remove_returns_none_when_not_there() {
+        let mut list = IndexList::new();
+
+        let five_index = list.push_back(5);
+
+        let five_entry = list.remove(five_index).unwrap();
+
+        check_invariants(&list);
+
+        assert_eq!(list.contents[0], Free { next_free: None });
+        assert_eq!(five_entry, 5);
+        assert!(list.remove(five_index).is_none());
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        list.remove(ten);
+        check_invariants(&list);
+
+        let ref_list = &list;
+        let mut iter = ref_list.into_iter();
+
+        assert_eq!(iter.next().unwrap(), &5);
+        assert_eq!(iter.next().unwrap(), &15);
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        list.remove(ten);
+        check_invariants(&list);
+
+        let mut iter = list.iter();
+
+        assert_eq!(iter.next().unwrap(), &5);
+        assert_eq!(iter.next().unwrap(), &15);
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn into_iter_mut() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        list.remove(ten);
+        check_invariants(&list);
+
+        let mut_list = &mut list;
+        let mut iter = mut_list.into_iter();
+
+        *iter.next().unwrap() = 50;
+        *iter.next().unwrap() = 150;
+
+        assert_eq!(to_vec_forward(&list), vec![50, 150]);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        list.remove(ten);
+        check_invariants(&list);
+
+        let mut iter = list.iter_mut();
+        *iter.next().unwrap() = 50;
+        *iter.next().unwrap() = 150;
+        assert!(iter.next().is_none());
+
+        assert_eq!(to_vec_forward(&list), vec![50, 150]);
+    }
+
+    #[test]
+    fn into_iter_owm() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        list.remove(ten);
+        check_invariants(&list);
+
+        let mut iter = list.into_iter();
+
+        assert_eq!(iter.next().unwrap(), 5);
+        assert_eq!(iter.next().unwrap(), 15);
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn iter_own() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        list.remove(ten);
+        check_invariants(&list);
+
+        let mut iter = list.into_iter();
+
+        assert_eq!(iter.next().unwrap(), 5);
+        assert_eq!(iter.next().unwrap(), 15);
+
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn into_iter_dropped_early() {
+        let item = std::rc::Rc::new(());
+        let mut list = IndexList::new();
+        for _ in 0..4 {
+            list.push_back(item.clone());
+        }
+        let mut iter = list.into_iter();
+        iter.next();
+        iter.next_back();
+        check_invariants(&iter.list);
+        assert_eq!(iter.len(), 2);
+        assert_eq!(std::rc::Rc::strong_count(&item), 3);
+        drop(iter);
+        assert_eq!(std::rc::Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn into_iter_panic_drops_the_rest() {
+        let item = std::rc::Rc::new(());
+        let mut list = IndexList::new();
+        for _ in 0..4 {
+            list.push_back(item.clone());
+        }
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            for (step, _) in list.into_iter().enumerate() {
+                assert!(step < 1, "stop");
+            }
+        }));
+        assert!(result.is_err());
+        assert_eq!(std::rc::Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn clone_from_reuses_the_storage() {
+        let mut template = IndexList::new();
+        let indices: Vec<_> = (0..4).map(|item| template.push_back(vec![item])).collect();
+        template.remove(indices[1]);
+
+        let mut scratch = IndexList::with_capacity(16);
+        scratch.push_back(vec![7; 10]);
+        let storage = scratch.contents.as_ptr();
+        let first = scratch.head().unwrap().as_ptr();
+        scratch.clone_from(&template);
+        check_invariants(&scratch);
+        assert_eq!(scratch, template);
+        assert_eq!(scratch.contents.as_ptr(), storage);
+        assert_eq!(scratch.head().unwrap().as_ptr(), first);
+        assert_eq!(scratch.get(indices[3]), Some(&vec![3]));
+
+        let clone = template.clone();
+        assert_eq!(clone, template);
+        assert_eq!(clone.get(indices[1]), None);
+    }
+
+    #[test]
+    fn map_keeps_the_layout() {
+        let mut list = IndexList::new();
+
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        list.push_back(3);
+        list.remove(two);
+        let zero = list.push_front(0);
+        list.remove(one);
+
+        let mut order = Vec::new();
+        let mut mapped = list.map(|item| {
+            order.push(*item);
+            item.to_string()
+        });
+        check_invariants(&mapped);
+        assert_eq!(order, vec![0, 3]);
+        assert_eq!(mapped.to_string(), "[0, 3]");
+        assert_eq!(mapped.get(zero.cast()), Some(&"0".to_string()));
+        assert_eq!(mapped.get(one.cast()), None);
+        assert_eq!(mapped.free_slots(), list.free_slots());
+
+        assert_eq!(
+            list.push_back(4).cast::<String>(),
+            mapped.push_back("4".to_string())
+        );
+    }
+
+    #[test]
+    fn try_map_stops_at_the_first_error() {
+        let mut list = IndexList::new();
+
+        list.push_back(1);
+        let two = list.push_back(2);
+        list.push_back(3);
+        list.push_back(4);
+
+        let mut calls = 0;
+        let result = list.try_map(|item| {
+            calls += 1;
+            if item % 2 == 1 {
+                Ok(item * 10)
+            } else {
+                Err(*item)
+            }
+        });
+        assert_eq!(result.unwrap_err(), 2);
+        assert_eq!(calls, 2);
+
+        list.remove(two);
+        let mapped = list.try_map(|item| u8::try_from(*item)).unwrap();
+        check_invariants(&mapped);
+        assert_eq!(to_vec_forward(&mapped), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn filter_map_packs_the_kept_elements() {
+        let mut list = IndexList::new();
+
+        let gone = list.push_back(0);
+        for item in 1..=6 {
+            list.push_back(item);
+        }
+        list.remove(gone);
+
+        let odd = list.filter_map(|item| (item % 2 == 1).then(|| item.to_string()));
+        check_invariants(&odd);
+        assert_eq!(odd.to_string(), "[1, 3, 5]");
+        assert_eq!(odd.free_slots(), 0);
+        assert_eq!(odd.slot_count(), 3);
+    }
+
+    #[test]
+    fn positional_lookups_follow_the_finger() {
+        let mut list = IndexList::new();
+        for i in 0..10 {
+            list.push_back(i);
+        }
+        for position in (0..10).chain((0..10).rev()) {
+            assert_eq!(
+                list.get_mut_by_position(position).map(|(_, &mut i)| i),
+                Some(position)
+            );
+            assert_eq!(list.finger.get().map(|(_, at)| at), Some(position));
+        }
+        assert_eq!(list.get_by_position(7).map(|(_, &i)| i), Some(7));
+        assert_eq!(list.finger.get().map(|(_, at)| at), Some(0));
+
+        assert_eq!(list.remove_at(4), Some(4));
+        assert_eq!(list.finger.get().map(|(_, at)| at), Some(4));
+        assert_eq!(list.get_by_position(4).map(|(_, &i)| i), Some(5));
+        let four = list.insert_at(4, 4).unwrap();
+        assert_eq!(list.finger.get(), Some((four.slot(), 4)));
+        assert_eq!(to_vec_forward(&list), (0..10).collect::<Vec<_>>());
+        check_invariants(&list);
+
+        list.push_front(100);
+        assert_eq!(list.finger.get(), None);
+        assert_eq!(list.get_by_position(5).map(|(_, &i)| i), Some(4));
+        let (four, _) = list.get_by_position(5).unwrap();
+        list.remove(four);
+        assert_eq!(list.get_by_position(5).map(|(_, &i)| i), Some(5));
+        list.reverse();
+        assert_eq!(list.get_by_position(5).map(|(_, &i)| i), Some(3));
+        list.rotate_left(3);
+        let expected = to_vec_forward(&list);
+        for (position, &item) in expected.iter().enumerate().rev() {
+            assert_eq!(list.get_by_position(position).map(|(_, &i)| i), Some(item));
+        }
+    }
+
+    #[test]
+    fn front_and_back() {
+        let mut list = IndexList::new();
+        assert_eq!(list.front(), None);
+        assert_eq!(list.back_mut(), None);
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        *list.front_mut().unwrap() += 10;
+        *list.back_mut().unwrap() += 20;
+        assert_eq!(list.front(), Some(&11));
+        assert_eq!(list.back(), Some(&23));
+        assert_eq!(list.pop_front(), Some(11));
+        assert_eq!(list.pop_back(), Some(23));
+        assert_eq!(list.front(), list.back());
+    }
+
+    #[test]
+    fn index_operator() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        list[two] *= 10;
+        assert_eq!(list[one] + list[two], 21);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid index into IndexList: the element was removed")]
+    fn index_operator_panics_on_stale_index() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        list.remove(one);
+        let _ = list[one];
+    }
+
+    #[test]
+    fn bounded_capacity() {
+        let mut list = IndexList::with_max_capacity(2);
+        assert!(list.try_push_back(2).is_ok());
+        let one = list.try_push_front(1).unwrap();
+        assert_eq!(list.try_push_back(3), Err(3));
+        assert_eq!(list.try_push_front(0), Err(0));
+
+        list.remove(one);
+        assert!(list.try_push_back(3).is_ok());
+        list.push_back(4);
+        assert_eq!(to_vec_forward(&list), vec![2, 3, 4]);
+        assert_eq!(list.try_push_front(1), Err(1));
+        let mut other = IndexList::with_max_capacity(1);
+        other.push_back(5);
+        list.append(&mut other);
+        assert_eq!(other.max_capacity(), Some(1));
+        assert!(other.try_push_back(6).is_ok());
+        check_invariants(&list);
+    }
+
+    #[test]
+    #[cfg(feature = "allocator-api2")]
+    fn list_in_custom_allocator() {
+        let bump = bumpalo::Bump::new();
+        let mut list: IndexList<_, usize, _> = IndexList::with_capacity_in(4, &bump);
+        let one = list.push_back(1);
+        list.push_back(2);
+        list.push_front(0);
+        list.remove(one);
+        list.defragment(|_, _| {});
+        assert!(bump.allocated_bytes() > 0);
+        assert_eq!(list.validate(), Ok(()));
+
+        let doubled = list.map(|item| item * 2);
+        assert!(std::ptr::eq(*doubled.allocator(), &bump));
+        assert_eq!(doubled.into_vec(), vec![0, 4]);
+        let rest = list.split_at_position(1);
+        assert_eq!(rest.into_iter().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn into_vec() {
+        let list: IndexList<i32> = IndexList::new();
+        assert_eq!(list.into_vec(), Vec::<i32>::new());
+
+        let mut list = IndexList::new();
+        for item in 1..=4 {
+            list.push_back(item);
+        }
+        assert_eq!(list.into_vec(), vec![1, 2, 3, 4]);
+
+        let mut list = IndexList::new();
+        for item in 1..=4 {
+            list.push_back(item);
+        }
+        let zero = list.push_front(0);
+        list.remove(zero);
+        list.push_front(0);
+        assert_eq!(list.into_vec(), vec![0, 1, 2, 3, 4]);
+
+        let mut list = IndexList::new();
+        for item in 1..=4 {
+            list.push_back(item);
+        }
+        list.reverse();
+        list.defragment(|_, _| {});
+        assert_eq!(list.into_vec(), vec![4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn indexlist_macro() {
+        let list = indexlist!["a", "b", "c",];
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec!["a", "b", "c"]);
+        assert_eq!((list.slot_count(), list.free_slots()), (3, 0));
+        assert_eq!(
+            list.memory_usage().allocated_bytes,
+            3 * std::mem::size_of::<Entry<&str>>()
+        );
+        assert!(indexlist![] == IndexList::<u8>::new());
+    }
+
+    #[test]
+    fn std_conversions() {
+        let mut deque = VecDeque::from([2, 3]);
+        deque.push_front(1);
+        let mut list = IndexList::from(deque);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3]);
+        assert_eq!(
+            list.get_by_position(2).map(|(index, _)| index.slot()),
+            Some(2)
+        );
+
+        list.push_front(0);
+        let linked = LinkedList::from(list);
+        assert_eq!(linked, LinkedList::from([0, 1, 2, 3]));
+
+        let list = IndexList::from(linked);
+        check_invariants(&list);
+        assert_eq!(VecDeque::from(list), VecDeque::from([0, 1, 2, 3]));
+        assert!(IndexList::from(LinkedList::<i32>::new()).is_empty());
+    }
+
+    #[test]
+    fn chunks() {
+        let mut list = IndexList::new();
+
+        assert_eq!(list.chunks(2).count(), 0);
+
+        let gone = list.push_back(0);
+        for item in 1..=4 {
+            list.push_back(item);
+        }
+        list.remove(gone);
+
+        let chunks: Vec<Vec<i32>> = list
+            .chunks(3)
+            .map(|chunk| chunk.into_iter().copied().collect())
+            .collect();
+        assert_eq!(chunks, vec![vec![1, 2, 3], vec![4]]);
+        assert_eq!(list.chunks(4).count(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn chunks_of_zero_panics() {
+        let list: IndexList<i32> = IndexList::new();
+        let _ = list.chunks(0);
+    }
+
+    #[test]
+    fn windows() {
+        let mut list = IndexList::new();
+
+        assert_eq!(list.windows(1).count(), 0);
+
+        let gone = list.push_back(0);
+        for item in 1..=4 {
+            list.push_back(item);
+        }
+        list.remove(gone);
+
+        let windows: Vec<Vec<i32>> = list
+            .windows(2)
+            .map(|window| window.into_iter().copied().collect())
+            .collect();
+        assert_eq!(windows, vec![vec![1, 2], vec![2, 3], vec![3, 4]]);
+        assert_eq!(list.windows(4).count(), 1);
+        assert_eq!(list.windows(5).count(), 0);
+    }
+
+    #[test]
+    fn into_entries() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
+        let four = list.push_front(4);
+        list.remove(ten);
+        let fifteen = list.push_back(15);
+
+        let entries: Vec<_> = list.into_entries().collect();
+        assert_eq!(entries, vec![(four, 4), (five, 5), (fifteen, 15)]);
+        assert_eq!(IndexList::<i32>::new().into_entries().count(), 0);
+    }
+
+    #[test]
+    fn reallocation() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        let ten = list.remove(ten).unwrap();
+
+        assert_eq!(ten, 10);
+
+        list.push_back(20);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list.contents[0],
+            Occupied(OccupiedEntry {
+                item: 5,
+                next: link(2),
+                prev: None,
+                generation: 0,
+            })
+        );
+
+        assert_eq!(
+            list.contents[1],
+            Occupied(OccupiedEntry {
+                item: 20,
+                next: None,
+                prev: link(2),
+                generation: 1,
+            })
+        );
+
+        assert_eq!(
+            list.contents[2],
+            Occupied(OccupiedEntry {
+                item: 15,
+                next: link(1),
+                prev: link(0),
+                generation: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn generations() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
+        list.push_back(15);
+
+        list.remove(ten);
+
+        let twenty = list.push_back(20);
+
+        check_invariants(&list);
+
+        assert!(list.get(ten).is_none());
+        assert!(list.get(five).is_some());
+        assert!(list.get(twenty).is_some());
+    }
+
+    #[test]
+    fn head() {
+        let mut list = IndexList::new();
+
+        assert!(list.head().is_none());
+
+        let five = list.push_back(5);
+
+        assert_eq!(list.head().unwrap(), &5);
+
+        list.push_back(10);
+
+        list.remove(five);
+
+        check_invariants(&list);
+
+        assert_eq!(list.head().unwrap(), &10);
+        assert_eq!(list.contents[0], Free { next_free: None });
+        assert_eq!(list.head, Some(1));
+        assert_eq!(
+            list.contents[1],
+            Occupied(OccupiedEntry {
+                item: 10,
+                next: None,
+                prev: None,
+                generation: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn head_mut() {
+        let mut list = IndexList::new();
+
+        assert!(list.head_mut().is_none());
+
+        let five = list.push_back(5);
+
+        assert_eq!(list.head_mut().unwrap(), &mut 5);
+
+        list.push_back(10);
+
+        list.remove(five);
+
+        check_invariants(&list);
+
+        assert_eq!(list.head_mut().unwrap(), &mut 10);
+        assert_eq!(list.contents[0], Free { next_free: None });
+        assert_eq!(list.head, Some(1));
+        assert_eq!(
+            list.contents[1],
+            Occupied(OccupiedEntry {
+                item: 10,
+                next: None,
+                prev: None,
+                generation: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn head_index() {
+        let mut list = IndexList::new();
+
+        assert!(list.head_index().is_none());
+
+        let five = list.push_back(5);
+
+        assert_eq!(list.head_index().unwrap(), five);
+    }
+
+    #[test]
+    fn tail_index() {
+        let mut list = IndexList::new();
+
+        assert!(list.tail_index().is_none());
+
+        let _five = list.push_back(5);
+        let ten = list.push_back(10);
+
+        assert_eq!(list.tail_index().unwrap(), ten);
+    }
+
+    #[test]
+    fn push_front() {
+        let mut list = IndexList::new();
+
+        list.push_front(5);
+        list.push_front(10);
+        list.push_front(15);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list.contents[0],
+            Occupied(OccupiedEntry {
+                item: 5,
+                next: None,
+                prev: link(1),
+                generation: 0,
+            })
+        );
+
+        assert_eq!(
+            list.contents[1],
+            Occupied(OccupiedEntry {
+                item: 10,
+                next: link(0),
+                prev: link(2),
+                generation: 0,
+            })
+        );
+
+        assert_eq!(
+            list.contents[2],
+            Occupied(OccupiedEntry {
+                item: 15,
+                next: link(1),
+                prev: None,
+                generation: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn pop_back() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        list.push_back(10);
+        list.push_back(15);
+
+        assert_eq!(list.pop_back().unwrap(), 15);
+        assert_eq!(list.pop_back().unwrap(), 10);
+        assert_eq!(list.pop_back().unwrap(), 5);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([
+                    Entry::Free { next_free: link(1) },
+                    Entry::Free { next_free: link(2) },
+                    Entry::Free { next_free: None },
+                ]),
+                generation: 3,
+                next_free: Some(0),
+                ordered_free: None,
+                head: None,
+                tail: None,
+                count: 0,
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn pop_front() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        list.push_back(10);
+        list.push_back(15);
+
+        assert_eq!(list.pop_front().unwrap(), 5);
+        assert_eq!(list.pop_front().unwrap(), 10);
+        assert_eq!(list.pop_front().unwrap(), 15);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([
+                    Entry::Free { next_free: None },
+                    Entry::Free { next_free: link(0) },
+                    Entry::Free { next_free: link(1) },
+                ]),
+                generation: 3,
+                next_free: Some(2),
+                ordered_free: None,
+                head: None,
+                tail: None,
+                count: 0,
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn pop_with_index() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        assert_eq!(list.pop_front_with_index(), Some((one, 1)));
+        assert_eq!(list.pop_back_with_index(), Some((three, 3)));
+        assert_eq!(list.pop_back_with_index(), Some((two, 2)));
+        assert_eq!(list.pop_front_with_index(), None);
+        assert!(!list.contains_index(two));
+        check_invariants(&list);
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        list.push_back(10);
+        list.push_back(15);
+
+        assert_eq!(list.pop_front().unwrap(), 5);
+        assert_eq!(list.pop_front().unwrap(), 10);
+        assert_eq!(list.pop_front().unwrap(), 15);
+
+        list.push_back(5);
+        list.push_back(10);
+        list.push_back(15);
+
+        assert_eq!(list.pop_front().unwrap(), 5);
+        assert_eq!(list.pop_front().unwrap(), 10);
+        assert_eq!(list.pop_front().unwrap(), 15);
+
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([
+                    Entry::Free { next_free: link(1) },
+                    Entry::Free { next_free: link(2) },
+                    Entry::Free { next_free: None },
+                ]),
+                generation: 6,
+                next_free: Some(0),
+                ordered_free: None,
+                head: None,
+                tail: None,
+                count: 0,
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn push_back_many_and_push_front_many() {
+        let mut list = IndexList::new();
+
+        let back = list.push_back_many(vec![3, 4]);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![3, 4]);
+
+        list.remove(back[0]);
+        let front = list.push_front_many(1..=3);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3, 4]);
+        assert_eq!(front[0].slot(), back[0].slot());
+        assert_eq!(list.get(front[0]), Some(&1));
+        assert_eq!(list.get(back[0]), None);
+
+        assert!(list.push_back_many(Vec::new()).is_empty());
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn push_front_next_free() {
+        let mut list = IndexList::new();
+
+        list.push_front(0);
+        list.push_front(73);
+
+        let index = list.index_of(&73).unwrap();
+        list.remove(index);
+        list.push_front(1);
+        list.push_front(2);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list,
+            IndexList {
+                id: list.id,
+                contents: alloc::Vec::from([
+                    Occupied(OccupiedEntry {
+                        item: 0,
+                        next: None,
+                        prev: link(1),
+                        generation: 0
+                    }),
+                    Occupied(OccupiedEntry {
+                        item: 1,
+                        next: link(0),
+                        prev: link(2),
+                        generation: 1
+                    }),
+                    Occupied(OccupiedEntry {
+                        item: 2,
+                        next: link(1),
+                        prev: None,
+                        generation: 1
+                    })
+                ]),
+                generation: 1,
+                count: 3,
+                next_free: None,
+                ordered_free: None,
+                head: Some(2),
+                tail: Some(0),
+                max_capacity: None,
+                detached: 0,
+                finger: Finger::default(),
+                stats: Stats::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn insert_before() {
+        let mut list = IndexList::new();
+
+        let index = list.push_front(2);
+        list.insert_before(index, 0);
+
+        check_invariants(&list);
+        assert_eq!(*list.get(index).unwrap(), 2);
+        assert_eq!(to_vec_forward(&list), vec![0, 2]);
+
+        let prev_index = list.prev_index(index).unwrap();
+        assert_eq!(*list.get(prev_index).unwrap(), 0);
+
+        list.insert_before(index, 1);
+
+        check_invariants(&list);
+        assert_eq!(*list.get(index).unwrap(), 2);
+        let prev_index = list.prev_index(index).unwrap();
+        assert_eq!(*list.get(prev_index).unwrap(), 1);
+        let prev_index = list.prev_index(prev_index).unwrap();
+        assert_eq!(*list.get(prev_index).unwrap(), 0);
+        assert_eq!(to_vec_forward(&list), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn insert_before_remove() {
+        let mut list = IndexList::new();
+
+        let index = list.push_front(2);
+        list.insert_before(index, 0);
+        list.insert_before(index, 1);
+        list.remove(index);
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![0, 1]);
+
+        let index = list.index_of(&0).unwrap();
+        list.insert_before(index, -1);
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![-1, 0, 1]);
+
+        let index = list.index_of(&-1).unwrap();
+        list.remove(index);
+        let index = list.index_of(&0).unwrap();
+        list.insert_before(index, -2);
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![-2, 0, 1]);
+    }
+
+    #[test]
+    fn insert_after() {
+        let mut list = IndexList::new();
+
+        let index = list.push_front(0);
+        list.insert_after(index, 2);
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![0, 2]);
+        let next_index = list.next_index(index).unwrap();
+        assert_eq!(*list.get(next_index).unwrap(), 2);
+
+        list.insert_after(index, 1);
+
+        check_invariants(&list);
+        assert_eq!(*list.get(index).unwrap(), 0);
+        let next_index = list.next_index(index).unwrap();
+        assert_eq!(*list.get(next_index).unwrap(), 1);
+        let next_index = list.next_index(next_index).unwrap();
+        assert_eq!(*list.get(next_index).unwrap(), 2);
+        assert_eq!(to_vec_forward(&list), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn insert_after_remove() {
+        let mut list = IndexList::new();
+
+        let index = list.push_front(0);
+        list.insert_after(index, 2);
+        list.insert_after(index, 1);
+        list.remove(index);
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2]);
+
+        let index = list.index_of(&2).unwrap();
+        list.insert_after(index, 3);
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3]);
+
+        let index = list.index_of(&3).unwrap();
+        list.remove(index);
+        let index = list.index_of(&2).unwrap();
+        list.insert_after(index, 4);
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn index_of() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        list.push_back(10);
+        list.push_back(15);
+
+        check_invariants(&list);
+
+        assert_eq!(list.index_of(&10).unwrap(), Index::new(1, 0, list.id));
+        assert!(list.index_of(&20).is_none());
+    }
+
+    #[test]
+    fn index_of_get_correct_generation() {
+        let mut list = IndexList::new();
+
+        list.push_back(5);
+        let ten = list.push_back(10);
+        list.remove(ten);
+        list.push_back(15);
+
+        check_invariants(&list);
+
+        assert_eq!(
+            list.index_of(&5).unwrap(),
+            Index {
+                index: Slot::new(0),
+                generation: 0,
+                list: list.id,
+                _marker: PhantomData
+            }
+        );
+    }
+
+    #[test]
+    fn index_of_get_first_occurrence() {
+        let mut list = IndexList::new();
+
+        list.push_back(3);
+        let six = list.push_back(6);
+        let first_nine = list.push_back(9);
+        list.push_back(12);
+
+        list.remove(six);
+
+        let _second_nine = list.push_back(9);
+
+        check_invariants(&list);
+
+        assert_eq!(list.index_of(&9).unwrap(), first_nine);
+    }
+
+    #[test]
+    fn sort() {
+        let mut list = IndexList::new();
+
+        let five = list.push_back(5);
+        let one = list.push_back(1);
+        let four = list.push_back(4);
+        list.push_back(2);
+        list.push_back(3);
+
+        list.sort();
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3, 4, 5]);
+        assert_eq!(list.head_index(), Some(one));
+        assert_eq!(list.tail_index(), Some(five));
+        assert_eq!(list.get(four), Some(&4));
+        assert_eq!(
+            list.contents[0],
+            Occupied(OccupiedEntry {
+                item: 5,
+                next: None,
+                prev: link(2),
+                generation: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn sort_by_is_stable() {
+        let mut list = IndexList::new();
+
+        list.push_back((2, 'a'));
+        list.push_back((1, 'b'));
+        list.push_back((2, 'c'));
+        list.push_back((1, 'd'));
+
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+
+        check_invariants(&list);
+        assert_eq!(
+            to_vec_forward(&list),
+            vec![(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]
+        );
+    }
+
+    #[test]
+    fn sort_by_cached_key_calls_the_key_once_per_element() {
+        let mut list = IndexList::new();
+
+        list.push_back((2, 'a'));
+        let one = list.push_back((1, 'b'));
+        list.push_back((2, 'c'));
+        list.push_back((1, 'd'));
+
+        let mut calls = 0;
+        list.sort_by_cached_key(|item| {
+            calls += 1;
+            item.0
+        });
+
+        assert_eq!(calls, 4);
+        check_invariants(&list);
+        assert_eq!(
+            to_vec_forward(&list),
+            vec![(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]
+        );
+        assert_eq!(list.get(one), Some(&(1, 'b')));
+    }
+
+    #[test]
+    fn sort_empty_and_single() {
+        let mut list: IndexList<i32> = IndexList::new();
+
+        list.sort();
+        check_invariants(&list);
+
+        list.push_back(1);
+        list.sort();
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1]);
+    }
+
+    #[test]
+    fn merge() {
+        let mut list = IndexList::new();
+
+        let one = list.push_back(1);
+        let gone = list.push_back(0);
+        list.push_back(4);
+        list.push_back(6);
+        list.remove(gone);
+
+        let mut other = IndexList::new();
+        other.push_back(2);
+        other.push_back(3);
+        other.push_back(7);
+
+        list.merge(other, |a, b| a.cmp(b));
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3, 4, 6, 7]);
+        assert_eq!(list.get(one), Some(&1));
+        assert_eq!(list.contents.len(), 6);
+    }
+
+    #[test]
+    fn merge_keeps_self_first_on_ties() {
+        let mut list = IndexList::new();
+        list.push_back((1, 'a'));
+        list.push_back((2, 'a'));
+
+        let mut other = IndexList::new();
+        other.push_back((1, 'b'));
+        other.push_back((3, 'b'));
+
+        list.merge(other, |a, b| a.0.cmp(&b.0));
+
+        check_invariants(&list);
+        assert_eq!(
+            to_vec_forward(&list),
+            vec![(1, 'a'), (1, 'b'), (2, 'a'), (3, 'b')]
+        );
+    }
+
+    #[test]
+    fn merge_into_empty() {
+        let mut list = IndexList::new();
+
+        let mut other = IndexList::new();
+        other.push_back(1);
+        other.push_back(2);
+
+        list.merge(other, |a, b| a.cmp(b));
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2]);
     }
 
-    /// Returns `true` if the list contains the specified value.
-    ///
-    /// # Examples
-    /// ```rust
-    /// let mut list = indexlist::IndexList::new();
-    /// list.push_back(5);
-    /// assert!(list.contains(&5));
-    /// ```
-    pub fn contains(&self, value: &T) -> bool {
-        self.iter().any(|e| e == value)
+    #[test]
+    fn transfer() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        let mut other = IndexList::new();
+
+        let moved = list.transfer(two, &mut other).unwrap();
+        assert_eq!(list.transfer(two, &mut other), None);
+        assert_eq!(list.transfer_after(one, &mut other, two), None);
+        assert_eq!(list.len(), 2);
+
+        list.transfer_before(one, &mut other, moved).unwrap();
+        let generation = list.generation;
+        list.transfer_after(three, &mut other, moved).unwrap();
+        assert_eq!(list.generation, generation);
+        check_invariants(&list);
+        check_invariants(&other);
+        assert!(list.is_empty());
+        assert_eq!(to_vec_forward(&other), vec![1, 2, 3]);
+        assert_eq!(other.get(moved), Some(&2));
+
+        let four = list.push_back(4);
+        let (head, _) = other.pop_front_with_index().unwrap();
+        let moved = list.transfer(four, &mut other).unwrap();
+        assert_eq!(moved.slot(), head.slot());
+        assert_eq!(list.get(four), None);
+        assert_eq!(other.slot_count(), 3);
+        assert_eq!(to_vec_forward(&other), vec![2, 3, 4]);
     }
-}
 
-impl<'a, T> IntoIterator for &'a IndexList<T> {
-    type Item = &'a T;
+    #[test]
+    fn detach_and_reattach() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
 
-    type IntoIter = Iter<'a, T>;
+        assert!(list.detach(two));
+        assert!(!list.detach(two));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 3]);
+        assert_eq!((list.len(), list.free_slots()), (2, 0));
+        assert_eq!(list.position_of(two), None);
+        assert_eq!(list.next_index(two), None);
+        assert_eq!(list.insert_after(two, 4), None);
+        assert!(!list.reattach_after(two, two));
+        *list.get_mut(two).unwrap() = 20;
+
+        let mut remapped = Vec::new();
+        list.defragment(|old, new| remapped.push((old, new)));
+        let [one, two, three] = [one, two, three]
+            .map(|index| remapped.iter().find(|(old, _)| *old == index).unwrap().1);
+        assert!(list.is_detached(two));
+        assert!(list.reattach_before(two, one));
+        assert!(!list.is_detached(two));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![20, 1, 3]);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+        list.detach(three);
+        list.detach(one);
+        assert_eq!(list.remove(three), Some(3));
+        assert_eq!(list.remove_many(&[one, two]), vec![1, 20]);
+        check_invariants(&list);
+        assert!(list.is_empty());
+        assert_eq!(list.free_slots(), 3);
     }
-}
-
-pub struct Iter<'a, T: 'a> {
-    list: &'a IndexList<T>,
-    index: Option<Index<T>>,
-}
 
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+    #[test]
+    fn consuming_drops_detached_elements() {
+        let detached = |at: usize| {
+            let mut list = IndexList::new();
+            let indices: Vec<_> = (1..=3).map(|item| list.push_back(item)).collect();
+            list.detach(indices[at]);
+            list
+        };
+        for (at, expected) in [(2, vec![1, 2]), (0, vec![2, 3])] {
+            assert_eq!(detached(at).len(), 2);
+            assert_eq!(detached(at).into_vec(), expected);
+            assert_eq!(detached(at).into_iter().collect::<Vec<_>>(), expected);
+            assert_eq!(Vec::from(VecDeque::from(detached(at))), expected);
+            assert_eq!(
+                LinkedList::from(detached(at))
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+                expected
+            );
+            let mut list = IndexList::new();
+            list.append(&mut detached(at));
+            check_invariants(&list);
+            assert_eq!(to_vec_forward(&list), expected);
+            let list = IndexList::concat([detached(at), IndexList::new()]);
+            assert_eq!(to_vec_forward(&list), expected);
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let index = self.index?;
-        self.index = self.list.next_index(index);
-        self.list.get(index)
+    #[test]
+    fn concat() {
+        let lists = (0..4).map(|worker| {
+            let mut list = IndexList::new();
+            for item in 0..worker {
+                list.push_front(worker * 10 + item);
+            }
+            list
+        });
+        let list = IndexList::concat(lists);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![10, 21, 20, 32, 31, 30]);
+        assert_eq!(
+            list.memory_usage().allocated_bytes,
+            6 * std::mem::size_of::<Entry<i32>>()
+        );
+        assert!(IndexList::<i32>::concat([]).is_empty());
     }
-}
 
-impl<'a, T> IntoIterator for &'a mut IndexList<T> {
-    type Item = &'a mut T;
+    #[test]
+    fn append_and_prepend() {
+        let mut list = IndexList::new();
+        let three = list.push_back(3);
 
-    type IntoIter = IterMut<'a, T>;
+        let mut other = IndexList::new();
+        other.push_back(0);
+        let one = other.push_back(1);
+        other.push_back(2);
+        other.remove(one);
+        let stale = other.push_front(1);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
+        list.prepend(&mut other);
+        check_invariants(&list);
+        check_invariants(&other);
+        assert_eq!(to_vec_forward(&list), vec![1, 0, 2, 3]);
+        assert_eq!(list.get(three), Some(&3));
+        assert!(other.is_empty());
+
+        other.push_back(4);
+        assert_eq!(other.get(stale), None);
+        list.append(&mut other);
+        list.append(&mut IndexList::new());
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 0, 2, 3, 4]);
+        assert!(other.is_empty());
+
+        let mut empty = IndexList::new();
+        empty.prepend(&mut list);
+        check_invariants(&empty);
+        assert_eq!(to_vec_forward(&empty), vec![1, 0, 2, 3, 4]);
     }
-}
 
-pub struct IterMut<'a, T: 'a> {
-    list: &'a mut IndexList<T>,
-    index: Option<Index<T>>,
-    ptr: *mut T,
-}
+    #[test]
+    fn splice_after_and_before() {
+        let mut list = IndexList::new();
+        let one = list.push_back(1);
+        let five = list.push_back(5);
 
-impl<'a, T> Iterator for IterMut<'a, T> {
-    type Item = &'a mut T;
+        let mut other = IndexList::new();
+        other.push_back(2);
+        let gone = other.push_back(0);
+        other.push_back(3);
+        other.remove(gone);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let index = self.index?;
-        self.index = self.list.next_index(index);
-        let item = self.list.get_mut(index)?;
-        self.ptr = item;
+        let spliced = list.splice_after(one, &mut other);
+        check_invariants(&list);
+        check_invariants(&other);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3, 5]);
+        assert_eq!(list.get(spliced[1]), Some(&3));
+        assert!(other.is_empty());
+
+        other.push_back(4);
+        list.splice_before(five, &mut other);
+        assert!(list.splice_before(one, &mut IndexList::new()).is_empty());
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3, 4, 5]);
 
-        // SAFETY: each item would be yielded at most once when `self.list.get_mut` is called
-        let mut_ref = unsafe { &mut *self.ptr };
-        Some(mut_ref)
+        list.remove(five);
+        other.push_back(6);
+        assert!(list.splice_after(five, &mut other).is_empty());
+        assert!(list.splice_before(five, &mut other).is_empty());
+        check_invariants(&list);
+        assert_eq!(list.len(), 4);
+        assert_eq!(to_vec_forward(&other), vec![6]);
     }
-}
 
-impl<T> IntoIterator for IndexList<T> {
-    type Item = T;
+    #[test]
+    fn insert_before_head_and_after_tail_return_index() {
+        let mut list = IndexList::new();
 
-    type IntoIter = IntoIter<T>;
+        let one = list.push_back(1);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.into_iter()
+        let zero = list.insert_before(one, 0).unwrap();
+        let two = list.insert_after(one, 2).unwrap();
+
+        check_invariants(&list);
+        assert_eq!(list.get(zero), Some(&0));
+        assert_eq!(list.get(two), Some(&2));
+        assert_eq!(to_vec_forward(&list), vec![0, 1, 2]);
     }
-}
 
-pub struct IntoIter<T> {
-    list: IndexList<T>,
-    index: Option<Index<T>>,
-}
+    #[test]
+    fn insert_iter_after_and_before() {
+        let mut list = IndexList::new();
 
-impl<T> Iterator for IntoIter<T> {
-    type Item = T;
+        let one = list.push_back(1);
+        let removed = list.push_back(9);
+        list.remove(removed);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let index = self.index?;
-        self.index = self.list.next_index(index);
-        let entry = std::mem::replace(
-            &mut self.list.contents[index.index],
-            Free { next_free: None },
-        );
-        match entry {
-            Occupied(oc) => Some(oc.item),
-            _ => panic!("Corrupted list"),
-        }
+        let tail = list.insert_iter_after(one, [4, 5]);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 4, 5]);
+
+        let middle = list.insert_iter_before(tail[0], [2, 3]);
+        let head = list.insert_iter_before(one, [-1, 0]);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![-1, 0, 1, 2, 3, 4, 5]);
+        assert_eq!(list.head_index(), Some(head[0]));
+        assert_eq!(list.tail_index(), Some(tail[1]));
+        assert_eq!(list.get(middle[1]), Some(&3));
+
+        assert!(list.insert_iter_after(one, []).is_empty());
+        assert!(list.insert_iter_after(removed, [7]).is_empty());
+        assert_eq!(list.len(), 7);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn insert_sorted() {
+        let mut list = IndexList::new();
 
-    fn to_vec_forward<T: Copy>(list: &IndexList<T>) -> Vec<T> {
-        let mut result = vec![];
-        let mut iter = list.head;
-        while let Some(next) = iter {
-            match &list.contents[next] {
-                Occupied(oc) => {
-                    iter = oc.next;
-                    result.push(oc.item);
-                }
-                _ => assert!(false),
-            }
+        for item in [5, 1, 4, 2, 3, 0, 6] {
+            let index = list.insert_sorted(item);
+            assert_eq!(list.get(index), Some(&item));
+            check_invariants(&list);
         }
 
-        result
+        assert_eq!(to_vec_forward(&list), vec![0, 1, 2, 3, 4, 5, 6]);
     }
 
-    fn check_invariants<T>(list: &IndexList<T>) {
-        if list.contents.is_empty() {
-            assert_eq!(list.head, None);
-            assert_eq!(list.tail, None);
-            assert_eq!(list.next_free, None);
-            assert_eq!(list.count, 0);
+    #[test]
+    fn insert_sorted_by_after_equal_elements() {
+        let mut list = IndexList::new();
 
-            return;
-        }
+        list.insert_sorted_by((1, 'a'), |a, b| a.0.cmp(&b.0));
+        list.insert_sorted_by((2, 'a'), |a, b| a.0.cmp(&b.0));
+        list.insert_sorted_by((1, 'b'), |a, b| a.0.cmp(&b.0));
 
-        if list.contents.len() == 1 {
-            match &list.contents[0] {
-                Free { next_free } => {
-                    assert_eq!(*next_free, None);
-                    assert_eq!(list.next_free, Some(0));
-                    assert_eq!(list.head, None);
-                    assert_eq!(list.tail, None);
-                    assert_eq!(list.count, 0);
-                }
-                Occupied(oc) => {
-                    assert_eq!(list.next_free, None);
-                    assert_eq!(list.head, Some(0));
-                    assert_eq!(list.tail, Some(0));
-                    assert_eq!(list.generation, oc.generation);
-                    assert_eq!(oc.prev, None);
-                    assert_eq!(oc.next, None);
-                    assert_eq!(list.count, 1);
-                }
-            }
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![(1, 'a'), (1, 'b'), (2, 'a')]);
+    }
 
-            return;
-        }
+    #[test]
+    fn insert_sorted_by_hint() {
+        let mut list = IndexList::new();
 
-        let count = list
-            .contents
-            .iter()
-            .filter(|e| match e {
-                Free { .. } => false,
-                _ => true,
-            })
-            .count();
-        assert_eq!(list.count, count);
+        list.push_back(10);
+        let twenty = list.push_back(20);
+        list.push_back(30);
 
-        let mut indexes = vec![];
-        let mut next = list.next_free;
-        let mut free_count = 0;
-        while let Some(index) = next {
-            assert!(!indexes.contains(&index));
-            indexes.push(index);
-            let entry = &list.contents[index];
-            match entry {
-                Free { next_free } => {
-                    next = *next_free;
-                }
-                _ => assert!(false),
-            }
-            free_count += 1;
-        }
-        assert_eq!(free_count, list.contents.len() - count);
+        list.insert_sorted_by_hint(twenty, 0, |a, b| a.cmp(b));
+        list.insert_sorted_by_hint(twenty, 40, |a, b| a.cmp(b));
+        list.insert_sorted_by_hint(twenty, 20, |a, b| a.cmp(b));
 
-        let mut iter = list.head;
-        let mut last = list.head;
-        let mut occupied_count = 0;
-        while let Some(next) = iter {
-            match &list.contents[next] {
-                Occupied(oc) => {
-                    last = iter;
-                    iter = oc.next;
-                    occupied_count += 1;
-                }
-                _ => assert!(false),
-            }
-        }
-        assert_eq!(last, list.tail);
-        assert_eq!(occupied_count, list.count);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![0, 10, 20, 20, 30, 40]);
 
-        let mut iter = list.tail;
-        let mut last = list.tail;
-        let mut occupied_count = 0;
-        while let Some(prev) = iter {
-            match &list.contents[prev] {
-                Occupied(oc) => {
-                    last = iter;
-                    iter = oc.prev;
-                    occupied_count += 1;
-                }
-                _ => assert!(false),
-            }
-        }
-        assert_eq!(last, list.head);
-        assert_eq!(occupied_count, list.count);
+        list.remove(twenty);
+        assert!(list
+            .insert_sorted_by_hint(twenty, 1, |a, b| a.cmp(b))
+            .is_none());
     }
 
     #[test]
-    fn create_index() {
-        let index: Index<i32> = Index::new(1, 2);
-        assert_eq!(index.index, 1);
-        assert_eq!(index.generation, 2);
+    fn dedup() {
+        let mut list = IndexList::new();
+
+        let first = list.push_back(1);
+        let second = list.push_back(1);
+        list.push_back(2);
+        list.push_back(2);
+        list.push_back(2);
+        list.push_back(3);
+        list.push_back(1);
+        list.push_back(1);
+
+        list.dedup();
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3, 1]);
+        assert_eq!(list.get(first), Some(&1));
+        assert_eq!(list.contents[second.slot()], Free { next_free: None });
     }
 
     #[test]
-    fn create_list() {
-        let _list: IndexList<i32> = IndexList::new();
+    fn dedup_by_argument_order() {
+        let mut list = IndexList::new();
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(4);
+        list.push_back(3);
+
+        let mut calls = vec![];
+        list.dedup_by(|a, b| {
+            calls.push((*a, *b));
+            *a > *b
+        });
+
+        check_invariants(&list);
+        assert_eq!(calls, vec![(2, 1), (4, 1), (3, 1)]);
+        assert_eq!(to_vec_forward(&list), vec![1]);
     }
 
     #[test]
-    fn contains() {
+    fn dedup_empty() {
+        let mut list: IndexList<i32> = IndexList::new();
+
+        list.dedup();
+
+        check_invariants(&list);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn reverse() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        list.push_back(3);
+        list.remove(two);
+        list.push_front(0);
 
-        assert!(list.contains(&5));
+        list.reverse();
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![3, 1, 0]);
+        assert_eq!(list.get(one), Some(&1));
+
+        list.reverse();
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![0, 1, 3]);
     }
 
     #[test]
-    fn get() {
+    fn reverse_empty_and_single() {
         let mut list = IndexList::new();
 
-        let five = list.push_back(5);
-
-        let entry = list.get(five);
+        list.reverse();
+        check_invariants(&list);
 
-        assert!(entry.is_some());
-        assert_eq!(entry.unwrap(), &5);
+        list.push_back(1);
+        list.reverse();
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1]);
     }
 
     #[test]
-    fn get_mut() {
+    fn rotate_left() {
         let mut list = IndexList::new();
 
-        let five = list.push_back(5);
+        let one = list.push_back(1);
+        for item in 2..=5 {
+            list.push_back(item);
+        }
 
-        let entry = list.get_mut(five);
+        list.rotate_left(2);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![3, 4, 5, 1, 2]);
+        assert_eq!(list.get(one), Some(&1));
 
-        assert!(entry.is_some());
+        list.rotate_left(4);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![2, 3, 4, 5, 1]);
 
-        assert_eq!(entry.unwrap(), &mut 5);
+        list.rotate_left(5);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![2, 3, 4, 5, 1]);
+
+        list.rotate_left(11);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![3, 4, 5, 1, 2]);
     }
 
     #[test]
-    fn next_index() {
+    fn rotate_right() {
         let mut list = IndexList::new();
 
-        let five = list.push_back(5);
-        let _ten = list.push_back(10);
+        for item in 1..=5 {
+            list.push_back(item);
+        }
 
-        let ten_index = list.next_index(five).unwrap();
+        list.rotate_right(2);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![4, 5, 1, 2, 3]);
 
-        let ten_value = list.get(ten_index);
+        list.rotate_right(4);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![5, 1, 2, 3, 4]);
 
-        assert_eq!(ten_value.unwrap(), &10);
-        assert_eq!(None, list.next_index(ten_index));
+        list.rotate_right(0);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![5, 1, 2, 3, 4]);
     }
 
     #[test]
-    fn prev_index() {
+    fn move_range() {
         let mut list = IndexList::new();
+        let indices: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
 
-        let _five = list.push_back(5);
-        let ten = list.push_back(10);
+        assert!(list.move_range_after(indices[0], indices[1], indices[5]));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![2, 3, 4, 5, 0, 1]);
+        assert!(list.move_range_before(indices[4], indices[1], indices[2]));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![4, 5, 0, 1, 2, 3]);
+        assert!(list.move_range_before(indices[2], indices[2], indices[3]));
+        assert_eq!(to_vec_forward(&list), vec![4, 5, 0, 1, 2, 3]);
+
+        assert!(!list.move_range_after(indices[5], indices[1], indices[0]));
+        assert!(!list.move_range_after(indices[1], indices[5], indices[0]));
+        list.remove(indices[3]);
+        assert!(!list.move_range_before(indices[0], indices[1], indices[3]));
+        assert_eq!(to_vec_forward(&list), vec![4, 5, 0, 1, 2]);
+        assert_eq!(list.get(indices[4]), Some(&4));
+    }
 
-        let five_index = list.prev_index(ten).unwrap();
+    #[test]
+    fn swap_ranges() {
+        let mut list = IndexList::new();
+        let i: Vec<_> = (0..7).map(|item| list.push_back(item)).collect();
 
-        let five_value = list.get(five_index);
+        assert!(list.swap_ranges(i[0], i[1], i[5], i[6]));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![5, 6, 2, 3, 4, 0, 1]);
+        assert!(list.swap_ranges(i[3], i[3], i[2], i[2]));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![5, 6, 3, 2, 4, 0, 1]);
+        assert!(list.swap_ranges(i[5], i[3], i[2], i[4]));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![2, 4, 5, 6, 3, 0, 1]);
+        assert!(list.swap_ranges(i[3], i[3], i[4], i[4]));
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![2, 3, 5, 6, 4, 0, 1]);
 
-        assert_eq!(five_value.unwrap(), &5);
-        assert_eq!(None, list.prev_index(five_index));
+        assert!(!list.swap_ranges(i[2], i[6], i[6], i[0]));
+        assert!(!list.swap_ranges(i[5], i[4], i[3], i[6]));
+        assert!(!list.swap_ranges(i[0], i[2], i[4], i[4]));
+        assert_eq!(to_vec_forward(&list), vec![2, 3, 5, 6, 4, 0, 1]);
     }
 
     #[test]
-    fn insert_into_empty_list() {
+    fn rotate_empty_and_single() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-
+        list.rotate_left(3);
+        list.rotate_right(3);
         check_invariants(&list);
 
-        assert_eq!(
-            list.contents[0],
-            Occupied(OccupiedEntry {
-                item: 5,
-                next: None,
-                prev: None,
-                generation: 0,
-            })
-        );
+        list.push_back(1);
+        list.rotate_left(3);
+        list.rotate_right(1);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1]);
     }
 
     #[test]
-    fn insert_thrice() {
+    fn contains_index() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        list.push_back(10);
-        list.push_back(15);
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
 
-        check_invariants(&list);
+        assert!(list.contains_index(five));
+        assert!(list.contains_index(ten));
 
-        assert_eq!(
-            list.contents[0],
-            Occupied(OccupiedEntry {
-                item: 5,
-                next: Some(1),
-                prev: None,
-                generation: 0,
-            })
-        );
+        list.remove(five);
+        assert!(!list.contains_index(five));
 
-        assert_eq!(
-            list.contents[1],
-            Occupied(OccupiedEntry {
-                item: 10,
-                next: Some(2),
-                prev: Some(0),
-                generation: 0,
-            })
-        );
+        let fifteen = list.push_back(15);
+        assert_eq!(fifteen.index, five.index);
+        assert!(!list.contains_index(five));
+        assert!(list.contains_index(fifteen));
 
-        assert_eq!(
-            list.contents[2],
-            Occupied(OccupiedEntry {
-                item: 15,
-                next: None,
-                prev: Some(1),
-                generation: 0,
-            })
-        );
+        assert!(!list.contains_index(Index::new(100, 0, list.id)));
+    }
 
-        assert_eq!(list.head, Some(0));
-        assert_eq!(list.tail, Some(2));
+    #[test]
+    fn is_current() {
+        let mut list = IndexList::new();
+        let five = list.push_back(5);
+        assert!(five.is_current(&list));
+        list.remove(five);
+        let ten = list.push_back(10);
+        assert!(!five.is_current(&list));
+        assert!(ten.is_current(&list));
+        list.truncate(0);
+        assert!(!ten.is_current(&list));
     }
 
     #[test]
-    fn push_back_remove_push_back() {
+    fn get_disjoint_mut() {
         let mut list = IndexList::new();
 
-        list.push_back(1);
+        let one = list.push_back(1);
         let two = list.push_back(2);
-        list.push_back(3);
-        list.remove(two).unwrap();
-
-        check_invariants(&list);
-        assert_eq!(list.next_free.unwrap(), 1);
-
-        list.push_back(4);
-        check_invariants(&list);
-        assert_eq!(list.next_free, None);
-
-        list.push_back(5);
-        list.push_back(6);
-
-        let three = list.index_of(&3).unwrap();
-        list.remove(three);
-        check_invariants(&list);
-        assert_eq!(list.next_free.unwrap(), 2);
-        let five = list.index_of(&5).unwrap();
-        list.remove(five).unwrap();
-        check_invariants(&list);
-        assert_eq!(list.next_free.unwrap(), 3);
+        let three = list.push_back(3);
+
+        let [a, b, c] = list.get_disjoint_mut([three, one, two]).unwrap();
+        *a += 30;
+        *b += 10;
+        *c += 20;
+        assert_eq!(to_vec_forward(&list), vec![11, 22, 33]);
+
+        assert!(list.get_disjoint_mut([one, one]).is_none());
+        assert!(list.get_disjoint_mut::<0>([]).is_some());
+
+        list.remove(two);
+        assert!(list.get_disjoint_mut([one, two]).is_none());
+        assert!(list
+            .get_disjoint_mut([one, Index::new(10, 0, list.id)])
+            .is_none());
     }
 
     #[test]
-    fn remove_middle() {
+    fn replace() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
+        let five = list.push_back(5);
         let ten = list.push_back(10);
-        list.push_back(15);
-
-        let removed = list.remove(ten).unwrap();
 
+        assert_eq!(list.replace(ten, 20), Some(10));
         check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![5, 20]);
+        assert_eq!(list.get(ten), Some(&20));
 
-        assert_eq!(removed, 10);
-        assert_eq!(
-            list,
-            IndexList {
-                contents: vec![
-                    Occupied(OccupiedEntry {
-                        item: 5,
-                        next: Some(2),
-                        prev: None,
-                        generation: 0,
-                    }),
-                    Free { next_free: None },
-                    Occupied(OccupiedEntry {
-                        item: 15,
-                        next: None,
-                        prev: Some(0),
-                        generation: 0,
-                    }),
-                ],
-                generation: 1,
-                next_free: Some(1),
-                head: Some(0),
-                tail: Some(2),
-                count: 2,
-            }
-        );
+        list.remove(five);
+        assert_eq!(list.replace(five, 1), None);
+        assert_eq!(to_vec_forward(&list), vec![20]);
     }
 
+    #[cfg(feature = "tracing")]
     #[test]
-    fn remove_head() {
-        let mut list = IndexList::new();
+    fn structural_changes_are_traced() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
 
-        let five = list.push_back(5);
-        list.push_back(10);
-        list.push_back(15);
+        #[derive(Default)]
+        struct Messages(Arc<Mutex<Vec<String>>>);
 
-        let removed = list.remove(five).unwrap();
+        impl Subscriber for Messages {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = String::new();
+                event.record(
+                    &mut |field: &tracing::field::Field, value: &dyn fmt::Debug| {
+                        fields.push_str(&format!("{}={:?} ", field.name(), value));
+                    },
+                );
+                self.0.lock().unwrap().push(fields.trim_end().to_string());
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
 
-        check_invariants(&list);
-        assert_eq!(removed, 5);
+        let messages = Messages::default();
+        let seen = Arc::clone(&messages.0);
+        tracing::subscriber::with_default(messages, || {
+            let mut list = IndexList::new();
+            let one = list.push_back(1);
+            list.remove(one);
+            list.push_back(2);
+        });
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 4);
+        assert!(seen[0].starts_with("message=allocated slot slot=0 generation=0"));
+        assert_eq!(seen[1], "message=unlinked chain first=0 last=0");
+        assert_eq!(seen[2], "message=freed slot slot=0 generation=0");
+        assert!(seen[3].starts_with("message=allocated slot slot=0 generation=1 reused=true"));
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn ops_stats() {
+        let mut list = IndexList::new();
+        let mut other = IndexList::new();
+        other.push_back(1);
+        other.push_back(2);
+        list.append(&mut other);
+        assert_eq!(other.ops_stats().insertions, 2);
+        assert_eq!(list.ops_stats().insertions, 2);
+        let ends = [list.head_index().unwrap(), list.tail_index().unwrap()];
+        list.remove_many(&ends);
+        list.push_front(3);
         assert_eq!(
-            list,
-            IndexList {
-                contents: vec![
-                    Free { next_free: None },
-                    Occupied(OccupiedEntry {
-                        item: 10,
-                        next: Some(2),
-                        prev: None,
-                        generation: 0,
-                    }),
-                    Occupied(OccupiedEntry {
-                        item: 15,
-                        next: None,
-                        prev: Some(1),
-                        generation: 0,
-                    }),
-                ],
-                generation: 1,
-                next_free: Some(0),
-                head: Some(1),
-                tail: Some(2),
-                count: 2,
+            list.ops_stats(),
+            OpsStats {
+                insertions: 3,
+                removals: 2,
+                slot_reuses: 1,
+                reallocations: 1,
             }
         );
     }
 
     #[test]
-    fn remove_tail() {
+    fn remove_many() {
         let mut list = IndexList::new();
+        let indices: Vec<_> = (0..8).map(|item| list.push_back(item)).collect();
 
-        list.push_back(5);
-        list.push_back(10);
-        let fifteen = list.push_back(15);
-
-        let removed = list.remove(fifteen).unwrap();
+        let removed = list.remove_many(&[
+            indices[3], indices[0], indices[7], indices[2], indices[6], indices[3],
+        ]);
+        assert_eq!(removed, vec![3, 0, 7, 2, 6]);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 4, 5]);
+        assert_eq!(list.generation, 1);
 
+        assert_eq!(list.remove_many(&[indices[0], indices[5]]), vec![5]);
+        assert_eq!(list.remove_many(&[]), Vec::<i32>::new());
+        assert_eq!(list.generation, 2);
+        assert_eq!(list.remove_many(&[indices[1], indices[4]]), vec![1, 4]);
         check_invariants(&list);
-        assert_eq!(removed, 15);
-        assert_eq!(
-            list,
-            IndexList {
-                contents: vec![
-                    Occupied(OccupiedEntry {
-                        item: 5,
-                        next: Some(1),
-                        prev: None,
-                        generation: 0,
-                    }),
-                    Occupied(OccupiedEntry {
-                        item: 10,
-                        next: None,
-                        prev: Some(0),
-                        generation: 0,
-                    }),
-                    Free { next_free: None },
-                ],
-                generation: 1,
-                next_free: Some(2),
-                head: Some(0),
-                tail: Some(1),
-                count: 2,
-            }
-        );
+        assert!(list.is_empty());
     }
 
     #[test]
-    fn remove_only() {
+    fn remove_all() {
         let mut list = IndexList::new();
+        let indices: Vec<_> = [1, 2, 2, 3, 2, 2].map(|item| list.push_back(item)).to_vec();
+        assert_eq!(list.remove_all(|item| *item == 4), 0);
+        assert_eq!(list.remove_all(|item| *item == 2), 4);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 3]);
+        assert_eq!(list.get(indices[2]), None);
+        assert_eq!(list.get(indices[3]), Some(&3));
+        assert_eq!(list.remove_all(|_| true), 2);
+        assert!(list.is_empty());
+        check_invariants(&list);
+    }
 
-        let five = list.push_back(5);
-
-        let removed = list.remove(five).unwrap();
-
+    #[test]
+    fn remove_all_tests_each_element_once() {
+        let mut list = IndexList::new();
+        for item in [2, 2, 1, 2, 1, 1, 2] {
+            list.push_back(item);
+        }
+        let len = list.len();
+        let mut calls = 0;
+        let removed = list.remove_all(|item| {
+            calls += 1;
+            *item == 2
+        });
+        assert_eq!(removed, 4);
+        assert_eq!(calls, len);
         check_invariants(&list);
-        assert_eq!(removed, 5);
-        assert_eq!(
-            list,
-            IndexList {
-                contents: vec![Free { next_free: None },],
-                generation: 1,
-                next_free: Some(0),
-                head: None,
-                tail: None,
-                count: 0,
-            }
-        );
+        assert_eq!(to_vec_forward(&list), vec![1, 1, 1]);
     }
 
     #[test]
-    fn remove_returns_none_when_not_there() {
+    fn remove_range() {
         let mut list = IndexList::new();
 
-        let five_index = list.push_back(5);
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        let four = list.push_back(4);
+        let five = list.push_back(5);
 
-        let five_entry = list.remove(five_index).unwrap();
+        assert_eq!(list.remove_range(two, four), 3);
 
         check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 5]);
+        assert!(!list.contains_index(three));
+        assert_eq!(list.generation, 1);
 
-        assert_eq!(list.contents[0], Free { next_free: None });
-        assert_eq!(five_entry, 5);
-        assert!(list.remove(five_index).is_none());
-    }
+        assert_eq!(list.remove_range(five, one), 0);
+        assert_eq!(list.remove_range(two, five), 0);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 5]);
 
-    #[test]
-    fn into_iter() {
-        let mut list = IndexList::new();
+        assert_eq!(list.remove_range(one, one), 1);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![5]);
 
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
+        let six = list.push_back(6);
+        assert_eq!(list.get(six), Some(&6));
+        assert!(!list.contains_index(four));
+    }
 
-        list.remove(ten);
-        check_invariants(&list);
+    #[test]
+    fn remove_range_whole_list() {
+        let mut list = IndexList::new();
 
-        let ref_list = &list;
-        let mut iter = ref_list.into_iter();
+        let one = list.push_back(1);
+        list.push_back(2);
+        let three = list.push_back(3);
 
-        assert_eq!(iter.next().unwrap(), &5);
-        assert_eq!(iter.next().unwrap(), &15);
+        assert_eq!(list.remove_range(one, three), 3);
 
-        assert!(iter.next().is_none());
+        check_invariants(&list);
+        assert!(list.is_empty());
     }
 
     #[test]
-    fn iter() {
+    fn drain_range() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        let four = list.push_back(4);
+        let five = list.push_back(5);
 
-        list.remove(ten);
+        assert_eq!(list.drain_range(five, one).count(), 0);
         check_invariants(&list);
+        assert_eq!(list.len(), 5);
 
-        let mut iter = list.iter();
-
-        assert_eq!(iter.next().unwrap(), &5);
-        assert_eq!(iter.next().unwrap(), &15);
+        let mut drain = list.drain_range(two, four);
+        assert_eq!(drain.size_hint(), (3, Some(3)));
+        assert_eq!(drain.next(), Some(2));
+        drop(drain);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 5]);
+        assert!(!list.contains_index(three));
 
-        assert!(iter.next().is_none());
+        let drained: Vec<_> = list.drain_range(one, five).collect();
+        check_invariants(&list);
+        assert_eq!(drained, vec![1, 5]);
+        assert!(list.is_empty());
     }
 
     #[test]
-    fn into_iter_mut() {
+    fn leaked_drain_range_keeps_undrained_elements() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
-
-        list.remove(ten);
-        check_invariants(&list);
-
-        let mut_list = &mut list;
-        let mut iter = mut_list.into_iter();
+        let one = list.push_back(1);
+        list.push_back(2);
+        let three = list.push_back(3);
 
-        *iter.next().unwrap() = 50;
-        *iter.next().unwrap() = 150;
+        let mut drain = list.drain_range(one, three);
+        assert_eq!(drain.next(), Some(1));
+        std::mem::forget(drain);
 
-        assert_eq!(to_vec_forward(&list), vec![50, 150]);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![2, 3]);
     }
 
     #[test]
-    fn iter_mut() {
+    fn truncate() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
+        let one = list.push_back(1);
+        list.push_back(2);
+        let three = list.push_back(3);
+        list.push_back(4);
 
-        list.remove(ten);
+        list.truncate(4);
         check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3, 4]);
 
-        let mut iter = list.iter_mut();
+        list.truncate(2);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2]);
+        assert!(!list.contains_index(three));
+        assert!(list.contains_index(one));
 
-        *iter.next().unwrap() = 50;
-        *iter.next().unwrap() = 150;
+        list.truncate(0);
+        check_invariants(&list);
+        assert!(list.is_empty());
 
-        assert_eq!(to_vec_forward(&list), vec![50, 150]);
+        list.truncate(0);
+        check_invariants(&list);
     }
 
     #[test]
-    fn into_iter_owm() {
+    fn split_at_position() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
+        let one = list.push_back(1);
+        list.push_back(2);
+        let three = list.push_back(3);
+        list.push_back(4);
 
-        list.remove(ten);
+        let empty = list.split_at_position(4);
         check_invariants(&list);
+        assert!(empty.is_empty());
 
-        let mut iter = list.into_iter();
-
-        assert_eq!(iter.next().unwrap(), 5);
-        assert_eq!(iter.next().unwrap(), 15);
+        let rest = list.split_at_position(2);
+        check_invariants(&list);
+        check_invariants(&rest);
+        assert_eq!(to_vec_forward(&list), vec![1, 2]);
+        assert_eq!(to_vec_forward(&rest), vec![3, 4]);
+        assert!(!list.contains_index(three));
+        assert!(list.contains_index(one));
 
-        assert!(iter.next().is_none());
+        let all = list.split_at_position(0);
+        check_invariants(&list);
+        check_invariants(&all);
+        assert!(list.is_empty());
+        assert_eq!(to_vec_forward(&all), vec![1, 2]);
     }
 
     #[test]
-    fn iter_own() {
+    fn partition() {
         let mut list = IndexList::new();
-
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
-
-        list.remove(ten);
+        let indices: Vec<_> = (0..8).map(|item| list.push_back(item)).collect();
+        let rest = list.partition_in_place(|item| item % 3 == 0);
         check_invariants(&list);
+        check_invariants(&rest);
+        assert_eq!(to_vec_forward(&list), vec![0, 3, 6]);
+        assert_eq!(to_vec_forward(&rest), vec![1, 2, 4, 5, 7]);
+        assert_eq!(list.get(indices[6]), Some(&6));
+        assert_eq!(list.get(indices[7]), None);
+
+        let (all, none) = list.partition(|_| true);
+        assert_eq!((all.len(), none.len()), (3, 0));
+        let (none, all) = all.partition(|_| false);
+        assert_eq!(to_vec_forward(&all), vec![0, 3, 6]);
+        assert!(none.is_empty());
+        check_invariants(&none);
+    }
 
-        let mut iter = list.into_iter();
+    #[test]
+    fn split_when() {
+        let mut list = IndexList::new();
+        let indices: Vec<_> = (0..5).map(|item| list.push_back(item)).collect();
+        assert!(list.split_when(|item| *item > 4).is_none());
+        assert_eq!(list.len(), 5);
 
-        assert_eq!(iter.next().unwrap(), 5);
-        assert_eq!(iter.next().unwrap(), 15);
+        let rest = list.split_when(|item| *item >= 3).unwrap();
+        check_invariants(&list);
+        check_invariants(&rest);
+        assert_eq!(to_vec_forward(&list), vec![0, 1, 2]);
+        assert_eq!(to_vec_forward(&rest), vec![3, 4]);
+        assert_eq!(list.get(indices[2]), Some(&2));
+        assert_eq!(list.get(indices[3]), None);
 
-        assert!(iter.next().is_none());
+        let all = list.split_when(|_| true).unwrap();
+        assert!(list.is_empty());
+        assert_eq!(to_vec_forward(&all), vec![0, 1, 2]);
     }
 
     #[test]
-    fn reallocation() {
+    fn get_by_position() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
-
-        let ten = list.remove(ten).unwrap();
+        let indexes: Vec<_> = (0..7).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[3]);
 
-        assert_eq!(ten, 10);
+        assert_eq!(list.get_by_position(0), Some((indexes[0], &0)));
+        assert_eq!(list.get_by_position(2), Some((indexes[2], &2)));
+        assert_eq!(list.get_by_position(3), Some((indexes[4], &4)));
+        assert_eq!(list.get_by_position(5), Some((indexes[6], &6)));
+        assert!(list.get_by_position(6).is_none());
+        assert!(IndexList::<i32>::new().get_by_position(0).is_none());
+    }
 
-        list.push_back(20);
+    #[test]
+    fn insert_at() {
+        let mut list = IndexList::new();
+        assert_eq!(list.insert_at(1, 0), None);
+        let one = list.insert_at(0, 1).unwrap();
+        list.insert_at(1, 3);
+        list.insert_at(0, 0);
+        list.insert_at(2, 2);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![0, 1, 2, 3]);
+        assert_eq!(list.position_of(one), Some(1));
+        assert_eq!(list.insert_at(5, 5), None);
+        assert_eq!(list.len(), 4);
+    }
 
+    #[test]
+    fn remove_at() {
+        let mut list = IndexList::new();
+        assert_eq!(list.remove_at(0), None);
+        for item in 0..5 {
+            list.push_back(item);
+        }
+        assert_eq!(list.remove_at(3), Some(3));
+        assert_eq!(list.remove_at(0), Some(0));
+        assert_eq!(list.remove_at(2), Some(4));
+        assert_eq!(list.remove_at(2), None);
         check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2]);
+    }
 
-        assert_eq!(
-            list.contents[0],
-            Occupied(OccupiedEntry {
-                item: 5,
-                next: Some(2),
-                prev: None,
-                generation: 0,
-            })
-        );
+    #[test]
+    fn position_of() {
+        let mut list = IndexList::new();
 
-        assert_eq!(
-            list.contents[1],
-            Occupied(OccupiedEntry {
-                item: 20,
-                next: None,
-                prev: Some(2),
-                generation: 1,
-            })
-        );
+        let indexes: Vec<_> = (0..7).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[3]);
 
+        let positions: Vec<_> = indexes
+            .iter()
+            .map(|&index| list.position_of(index))
+            .collect();
         assert_eq!(
-            list.contents[2],
-            Occupied(OccupiedEntry {
-                item: 15,
-                next: Some(1),
-                prev: Some(0),
-                generation: 0,
-            })
+            positions,
+            vec![Some(0), Some(1), Some(2), None, Some(3), Some(4), Some(5)]
         );
+
+        for position in 0..list.len() {
+            let (index, _) = list.get_by_position(position).unwrap();
+            assert_eq!(list.position_of(index), Some(position));
+        }
     }
 
     #[test]
-    fn generations() {
+    fn binary_search_by() {
         let mut list = IndexList::new();
 
-        let five = list.push_back(5);
-        let ten = list.push_back(10);
-        list.push_back(15);
-
-        list.remove(ten);
+        let indexes: Vec<_> = (0..9).map(|item| list.push_back(item * 2 + 1)).collect();
 
-        let twenty = list.push_back(20);
+        for (i, &index) in indexes.iter().enumerate() {
+            let target = i * 2 + 1;
+            assert_eq!(list.binary_search_by(|item| item.cmp(&target)), Ok(index));
+            let missing = target + 1;
+            assert_eq!(
+                list.binary_search_by(|item| item.cmp(&missing)),
+                Err(indexes.get(i + 1).copied())
+            );
+        }
+        assert_eq!(list.binary_search(&0), Err(Some(indexes[0])));
+    }
 
-        check_invariants(&list);
+    #[test]
+    fn binary_search_empty() {
+        let list: IndexList<i32> = IndexList::new();
 
-        assert!(list.get(ten).is_none());
-        assert!(list.get(five).is_some());
-        assert!(list.get(twenty).is_some());
+        assert_eq!(list.binary_search(&1), Err(None));
     }
 
     #[test]
-    fn head() {
+    fn find() {
         let mut list = IndexList::new();
 
-        assert!(list.head().is_none());
-
-        let five = list.push_back(5);
-
-        assert_eq!(list.head().unwrap(), &5);
-
-        list.push_back(10);
+        list.push_back(1);
+        let two = list.push_back(2);
+        list.push_back(3);
+        let four = list.push_back(4);
 
-        list.remove(five);
+        assert_eq!(list.find(|item| item % 2 == 0), Some((two, &2)));
+        list.remove(two);
+        assert_eq!(list.find(|item| item % 2 == 0), Some((four, &4)));
+        assert!(list.find(|item| *item > 4).is_none());
+    }
 
-        check_invariants(&list);
+    #[test]
+    fn min_and_max_by_key() {
+        let mut list = IndexList::new();
+        assert_eq!(list.min_by_key(|item: &i32| *item), None);
+        let first_low = list.push_back(1);
+        list.push_back(5);
+        list.push_back(1);
+        list.push_back(3);
+        let last_high = list.push_back(5);
 
-        assert_eq!(list.head().unwrap(), &10);
-        assert_eq!(list.contents[0], Free { next_free: None });
-        assert_eq!(list.head, Some(1));
+        assert_eq!(list.min_by_key(|item| *item), Some((first_low, &1)));
+        assert_eq!(list.max_by_key(|item| *item), Some((last_high, &5)));
+        let (max, _) = list.max_by_key(|item| *item).unwrap();
+        list.remove(max);
         assert_eq!(
-            list.contents[1],
-            Occupied(OccupiedEntry {
-                item: 10,
-                next: None,
-                prev: None,
-                generation: 0,
-            })
+            list.max_by_key(|item| *item).map(|(_, item)| *item),
+            Some(5)
         );
     }
 
     #[test]
-    fn head_mut() {
+    fn find_mut() {
         let mut list = IndexList::new();
 
-        assert!(list.head_mut().is_none());
+        list.push_back(1);
+        let two = list.push_back(2);
 
-        let five = list.push_back(5);
+        let (index, item) = list.find_mut(|item| *item == 2).unwrap();
+        *item = 20;
 
-        assert_eq!(list.head_mut().unwrap(), &mut 5);
+        assert_eq!(index, two);
+        assert_eq!(to_vec_forward(&list), vec![1, 20]);
+        assert!(list.find_mut(|item| *item == 2).is_none());
+    }
 
-        list.push_back(10);
+    #[test]
+    fn rfind() {
+        let mut list = IndexList::new();
 
-        list.remove(five);
+        let one = list.push_back(1);
+        list.push_back(2);
+        let three = list.push_back(3);
+        list.push_back(4);
+
+        assert_eq!(list.rfind(|item| item % 2 == 1), Some((three, &3)));
+        list.remove(three);
+        assert_eq!(list.rfind(|item| item % 2 == 1), Some((one, &1)));
+        assert!(list.rfind(|item| *item > 4).is_none());
+    }
 
+    #[test]
+    fn remove_item() {
+        let mut list = IndexList::new();
+        let first = list.push_back(1);
+        let second = list.push_back(1);
+        list.push_front(2);
+        assert_eq!(list.remove_item(&1), Some(1));
+        assert_eq!(list.get(first), None);
+        assert_eq!(list.get(second), Some(&1));
+        assert_eq!(list.remove_item(&3), None);
         check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![2, 1]);
+    }
 
-        assert_eq!(list.head_mut().unwrap(), &mut 10);
-        assert_eq!(list.contents[0], Free { next_free: None });
-        assert_eq!(list.head, Some(1));
-        assert_eq!(
-            list.contents[1],
-            Occupied(OccupiedEntry {
-                item: 10,
-                next: None,
-                prev: None,
-                generation: 0,
-            })
-        );
+    #[test]
+    fn last_index_of() {
+        let mut list = IndexList::new();
+
+        let first = list.push_back(9);
+        list.push_back(3);
+        let second = list.push_back(9);
+        list.push_back(3);
+
+        assert_eq!(list.last_index_of(&9), Some(second));
+        list.remove(second);
+        assert_eq!(list.last_index_of(&9), Some(first));
+        assert!(list.last_index_of(&1).is_none());
     }
 
     #[test]
-    fn head_index() {
+    fn starts_and_ends_with() {
         let mut list = IndexList::new();
+        assert!(list.starts_with(&[]));
+        assert!(!list.ends_with(&[1]));
+        list.push_back(2);
+        list.push_back(3);
+        list.push_front(1);
 
-        assert!(list.head_index().is_none());
+        assert!(list.starts_with(&[1, 2, 3]));
+        assert!(!list.starts_with(&[1, 2, 3, 4]));
+        assert!(list.starts_with(vec![&1]));
+        assert!(list.ends_with(&[1, 2, 3]));
+        assert!(list.ends_with(&[3]));
+        assert!(!list.ends_with(&[0, 1, 2, 3]));
+        assert!(!list.ends_with(&[2]));
+    }
 
-        let five = list.push_back(5);
+    #[test]
+    fn indices_of() {
+        let mut list = IndexList::new();
 
-        assert_eq!(list.head_index().unwrap(), five);
+        let first = list.push_back(9);
+        let gone = list.push_back(9);
+        list.push_back(3);
+        let last = list.push_back(9);
+        list.remove(gone);
+
+        assert_eq!(list.indices_of(&9).collect::<Vec<_>>(), vec![first, last]);
+        assert_eq!(list.indices_of(&1).count(), 0);
     }
 
     #[test]
-    fn tail_index() {
+    fn find_all() {
         let mut list = IndexList::new();
 
-        assert!(list.tail_index().is_none());
+        let indexes: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
 
-        let _five = list.push_back(5);
-        let ten = list.push_back(10);
+        assert_eq!(
+            list.find_all(|item| item % 3 == 0).collect::<Vec<_>>(),
+            vec![indexes[0], indexes[3]]
+        );
+        assert_eq!(list.find_all(|_| false).count(), 0);
 
-        assert_eq!(list.tail_index().unwrap(), ten);
+        let threshold = 3;
+        assert_eq!(list.find_all(|item| *item > threshold).count(), 2);
     }
 
     #[test]
-    fn push_front() {
+    fn is_before() {
         let mut list = IndexList::new();
 
-        list.push_front(5);
-        list.push_front(10);
-        list.push_front(15);
+        let indexes: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[2]);
 
-        check_invariants(&list);
+        assert_eq!(list.is_before(indexes[0], indexes[5]), Some(true));
+        assert_eq!(list.is_before(indexes[5], indexes[0]), Some(false));
+        assert_eq!(list.is_before(indexes[3], indexes[4]), Some(true));
+        assert_eq!(list.is_before(indexes[4], indexes[1]), Some(false));
+        assert_eq!(list.is_before(indexes[3], indexes[3]), Some(false));
+        assert_eq!(list.is_before(indexes[2], indexes[3]), None);
+        assert_eq!(list.is_before(indexes[3], indexes[2]), None);
+    }
 
-        assert_eq!(
-            list.contents[0],
-            Occupied(OccupiedEntry {
-                item: 5,
-                next: None,
-                prev: Some(1),
-                generation: 0,
-            })
-        );
+    #[test]
+    fn distance() {
+        let mut list = IndexList::new();
 
-        assert_eq!(
-            list.contents[1],
-            Occupied(OccupiedEntry {
-                item: 10,
-                next: Some(0),
-                prev: Some(2),
-                generation: 0,
-            })
-        );
+        let indexes: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[2]);
 
-        assert_eq!(
-            list.contents[2],
-            Occupied(OccupiedEntry {
-                item: 15,
-                next: Some(1),
-                prev: None,
-                generation: 0,
-            })
-        );
+        assert_eq!(list.distance(indexes[0], indexes[5]), Some(4));
+        assert_eq!(list.distance(indexes[5], indexes[0]), Some(-4));
+        assert_eq!(list.distance(indexes[1], indexes[3]), Some(1));
+        assert_eq!(list.distance(indexes[4], indexes[4]), Some(0));
+        assert_eq!(list.distance(indexes[2], indexes[4]), None);
+
+        list.push_front(-1);
+        assert_eq!(list.distance(indexes[5], indexes[0]), Some(-4));
     }
 
+    #[cfg(feature = "list-id")]
     #[test]
-    fn pop_back() {
+    fn foreign_index_is_rejected() {
         let mut list = IndexList::new();
+        let mut other = IndexList::new();
 
-        list.push_back(5);
-        list.push_back(10);
-        list.push_back(15);
-
-        assert_eq!(list.pop_back().unwrap(), 15);
-        assert_eq!(list.pop_back().unwrap(), 10);
-        assert_eq!(list.pop_back().unwrap(), 5);
+        let five = list.push_back(5);
+        let foreign = other.push_back(10);
+        assert_eq!(five.index, foreign.index);
+        assert_eq!(five.generation, foreign.generation);
+
+        assert!(!list.contains_index(foreign));
+        assert!(list.get(foreign).is_none());
+        assert!(list.get_mut(foreign).is_none());
+        assert!(list.next_index(foreign).is_none());
+        assert!(list.prev_index(foreign).is_none());
+        assert!(list.insert_before(foreign, 1).is_none());
+        assert!(list.insert_after(foreign, 1).is_none());
+        assert!(list.remove(foreign).is_none());
 
         check_invariants(&list);
-
-        assert_eq!(
-            list,
-            IndexList {
-                contents: vec![
-                    Entry::Free { next_free: Some(1) },
-                    Entry::Free { next_free: Some(2) },
-                    Entry::Free { next_free: None },
-                ],
-                generation: 3,
-                next_free: Some(0),
-                head: None,
-                tail: None,
-                count: 0,
-            }
-        );
+        assert_eq!(to_vec_forward(&list), vec![5]);
+        assert_eq!(other.remove(foreign), Some(10));
     }
 
     #[test]
-    fn pop_front() {
+    fn try_get() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        list.push_back(10);
-        list.push_back(15);
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
 
-        assert_eq!(list.pop_front().unwrap(), 5);
-        assert_eq!(list.pop_front().unwrap(), 10);
-        assert_eq!(list.pop_front().unwrap(), 15);
+        assert_eq!(list.try_get(five), Ok(&5));
+        assert_eq!(list.try_get_mut(ten), Ok(&mut 10));
 
-        check_invariants(&list);
+        list.remove(five);
+        assert_eq!(list.try_get(five), Err(AccessError::FreedSlot));
 
-        assert_eq!(
-            list,
-            IndexList {
-                contents: vec![
-                    Entry::Free { next_free: None },
-                    Entry::Free { next_free: Some(0) },
-                    Entry::Free { next_free: Some(1) },
-                ],
-                generation: 3,
-                next_free: Some(2),
-                head: None,
-                tail: None,
-                count: 0,
-            }
-        );
+        list.push_back(15);
+        assert_eq!(list.try_get(five), Err(AccessError::StaleGeneration));
+        assert_eq!(list.try_get_mut(five), Err(AccessError::StaleGeneration));
+
+        let out_of_bounds = Index::new(10, 0, list.id);
+        assert_eq!(list.try_get(out_of_bounds), Err(AccessError::ForeignIndex));
     }
 
     #[test]
-    fn push_and_pop() {
+    fn try_remove() {
         let mut list = IndexList::new();
 
-        list.push_back(5);
-        list.push_back(10);
-        list.push_back(15);
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
 
-        assert_eq!(list.pop_front().unwrap(), 5);
-        assert_eq!(list.pop_front().unwrap(), 10);
-        assert_eq!(list.pop_front().unwrap(), 15);
+        assert_eq!(list.try_remove(five), Ok(5));
+        check_invariants(&list);
+        assert_eq!(list.try_remove(five), Err(AccessError::FreedSlot));
 
-        list.push_back(5);
-        list.push_back(10);
-        list.push_back(15);
+        if let Occupied(oc) = &mut list.contents[ten.slot()] {
+            oc.prev = link(0);
+        }
+        assert_eq!(list.try_remove(ten), Err(AccessError::CorruptedLinks));
+    }
 
-        assert_eq!(list.pop_front().unwrap(), 5);
-        assert_eq!(list.pop_front().unwrap(), 10);
-        assert_eq!(list.pop_front().unwrap(), 15);
+    #[test]
+    fn try_insert() {
+        let mut list = IndexList::new();
+
+        let two = list.push_back(2);
+        let one = list.try_insert_before(two, 1).unwrap();
+        let three = list.try_insert_after(two, 3).unwrap();
 
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 2, 3]);
+        assert_eq!(list.head_index(), Some(one));
+        assert_eq!(list.tail_index(), Some(three));
+
+        list.remove(two);
+        let error = list.try_insert_after(two, 4).unwrap_err();
+        assert_eq!(error.kind(), AccessError::FreedSlot);
+        assert_eq!(error.into_item(), 4);
+        let error = list.try_insert_before(two, 0).unwrap_err();
         assert_eq!(
-            list,
-            IndexList {
-                contents: vec![
-                    Entry::Free { next_free: Some(1) },
-                    Entry::Free { next_free: Some(2) },
-                    Entry::Free { next_free: None },
-                ],
-                generation: 6,
-                next_free: Some(0),
-                head: None,
-                tail: None,
-                count: 0,
-            }
+            error.to_string(),
+            "could not insert the element: the element was removed"
         );
+
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![1, 3]);
     }
 
     #[test]
-    fn push_front_next_free() {
+    fn get_free_slot_returns_none() {
         let mut list = IndexList::new();
 
-        list.push_front(0);
-        list.push_front(73);
+        let five = list.push_back(5);
+        let ten = list.push_back(10);
+        list.remove(five);
+        list.remove(ten);
 
-        let index = list.index_of(&73).unwrap();
-        list.remove(index);
-        list.push_front(1);
-        list.push_front(2);
+        assert_eq!(list.contents[five.slot()], Free { next_free: None });
+        assert!(list.get(five).is_none());
+        assert!(list.get_mut(ten).is_none());
+        assert!(list.next_index(five).is_none());
+        assert!(list.prev_index(ten).is_none());
+        assert!(list.insert_before(five, 1).is_none());
+        assert!(list.insert_after(ten, 1).is_none());
+        assert!(list.remove(ten).is_none());
+        assert!(list.replace(five, 1).is_none());
+        assert!(list.position_of(ten).is_none());
 
         check_invariants(&list);
-
-        assert_eq!(
-            list,
-            IndexList {
-                contents: vec![
-                    Occupied(OccupiedEntry {
-                        item: 0,
-                        next: None,
-                        prev: Some(1),
-                        generation: 0
-                    }),
-                    Occupied(OccupiedEntry {
-                        item: 1,
-                        next: Some(0),
-                        prev: Some(2),
-                        generation: 1
-                    }),
-                    Occupied(OccupiedEntry {
-                        item: 2,
-                        next: Some(1),
-                        prev: None,
-                        generation: 1
-                    })
-                ],
-                generation: 1,
-                count: 3,
-                next_free: None,
-                head: Some(2),
-                tail: Some(0),
-            }
-        );
+        assert!(list.is_empty());
     }
 
     #[test]
-    fn insert_before() {
+    fn next_and_prev_index_use_neighbor_generation() {
         let mut list = IndexList::new();
 
-        let index = list.push_front(2);
-        list.insert_before(index, 0);
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        list.push_back(3);
+        list.remove(two);
+        let four = list.push_back(4);
+        let zero = list.push_front(0);
 
         check_invariants(&list);
-        assert_eq!(*list.get(index).unwrap(), 2);
-        assert_eq!(to_vec_forward(&list), vec![0, 2]);
+        assert_eq!(four.index, two.index);
+        assert_ne!(four.generation, one.generation);
+
+        let three = list.next_index(one).unwrap();
+        assert_eq!(list.get(three), Some(&3));
+        assert_eq!(list.next_index(three), Some(four));
+        assert_eq!(list.prev_index(four), Some(three));
+        assert_eq!(list.prev_index(one), Some(zero));
+        assert_eq!(list.next_index(zero), Some(one));
+        assert!(list.next_index(two).is_none());
+        assert!(list.prev_index(two).is_none());
+    }
 
-        let prev_index = list.prev_index(index).unwrap();
-        assert_eq!(*list.get(prev_index).unwrap(), 0);
+    #[test]
+    fn iter_over_mixed_generations() {
+        let mut list = IndexList::new();
 
-        list.insert_before(index, 1);
+        let indexes: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[1]);
+        list.remove(indexes[4]);
+        list.push_back(6);
+        list.push_front(-1);
+        list.insert_after(indexes[2], 7);
 
         check_invariants(&list);
-        assert_eq!(*list.get(index).unwrap(), 2);
-        let prev_index = list.prev_index(index).unwrap();
-        assert_eq!(*list.get(prev_index).unwrap(), 1);
-        let prev_index = list.prev_index(prev_index).unwrap();
-        assert_eq!(*list.get(prev_index).unwrap(), 0);
-        assert_eq!(to_vec_forward(&list), vec![0, 1, 2]);
+        let expected = vec![-1, 0, 2, 7, 3, 5, 6];
+        assert_eq!(to_vec_forward(&list), expected);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+        for item in list.iter_mut() {
+            *item *= 10;
+        }
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            expected.iter().map(|item| item * 10).collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn insert_before_remove() {
-        let mut list = IndexList::new();
+    fn validate_detects_corruption() {
+        let make = || {
+            let mut list = IndexList::new();
+            let zero = list.push_back(0);
+            list.push_back(1);
+            list.push_back(2);
+            list.remove(zero);
+            list
+        };
+        assert_eq!(make().validate(), Ok(()));
 
-        let index = list.push_front(2);
-        list.insert_before(index, 0);
-        list.insert_before(index, 1);
-        list.remove(index);
+        let mut list = make();
+        list.count = 3;
+        assert_eq!(
+            list.validate(),
+            Err(InvariantError::CountMismatch {
+                stored: 3,
+                occupied: 2
+            })
+        );
 
-        check_invariants(&list);
-        assert_eq!(to_vec_forward(&list), vec![0, 1]);
+        let mut list = make();
+        list.next_free = Some(1);
+        assert_eq!(list.validate(), Err(InvariantError::BrokenFreeList));
 
-        let index = list.index_of(&0).unwrap();
-        list.insert_before(index, -1);
+        let mut list = make();
+        list.head = Some(0);
+        assert_eq!(
+            list.validate(),
+            Err(InvariantError::LinkToFreeSlot { slot: 0 })
+        );
 
-        check_invariants(&list);
-        assert_eq!(to_vec_forward(&list), vec![-1, 0, 1]);
+        let mut list = make();
+        list.occupied_mut(2).prev = None;
+        assert_eq!(list.validate(), Err(InvariantError::BrokenLink { slot: 2 }));
 
-        let index = list.index_of(&-1).unwrap();
-        list.remove(index);
-        let index = list.index_of(&0).unwrap();
-        list.insert_before(index, -2);
+        let mut list = make();
+        list.occupied_mut(2).next = link(1);
+        list.occupied_mut(1).prev = link(2);
+        list.occupied_mut(2).prev = link(1);
+        assert_eq!(list.validate(), Err(InvariantError::BrokenLink { slot: 1 }));
 
-        check_invariants(&list);
-        assert_eq!(to_vec_forward(&list), vec![-2, 0, 1]);
+        let mut list = make();
+        list.tail = Some(1);
+        assert_eq!(list.validate(), Err(InvariantError::BrokenChain));
+
+        let mut list = make();
+        list.occupied_mut(1).generation = 2;
+        assert_eq!(
+            list.validate(),
+            Err(InvariantError::FutureGeneration { slot: 1 })
+        );
     }
 
+    #[cfg(all(feature = "debug-invariants", debug_assertions))]
     #[test]
-    fn insert_after() {
+    #[should_panic(expected = "Corrupted list")]
+    fn debug_invariants_catch_corruption() {
         let mut list = IndexList::new();
+        list.push_back(1);
+        list.count = 5;
+        list.push_front(0);
+    }
 
-        let index = list.push_front(0);
-        list.insert_after(index, 2);
+    #[test]
+    fn option_of_index_has_no_overhead() {
+        use std::mem::size_of;
 
-        check_invariants(&list);
-        assert_eq!(to_vec_forward(&list), vec![0, 2]);
-        let next_index = list.next_index(index).unwrap();
-        assert_eq!(*list.get(next_index).unwrap(), 2);
+        assert_eq!(size_of::<Option<Index<u8>>>(), size_of::<Index<u8>>());
+        assert_eq!(size_of::<Option<Slot>>(), size_of::<Slot>());
+        assert_eq!(Slot::<usize>::new(0).get(), 0);
+        assert_eq!(Slot::<usize>::new(7).get(), 7);
+    }
 
-        list.insert_after(index, 1);
+    #[cfg(not(feature = "list-id"))]
+    #[test]
+    fn u32_index_is_compact() {
+        assert_eq!(std::mem::size_of::<Index<u8, u32>>(), 8);
+        assert_eq!(std::mem::size_of::<Option<Index<u8, u32>>>(), 8);
+        assert_eq!(std::mem::size_of::<Entry<u32, u32>>(), 20);
+    }
 
-        check_invariants(&list);
-        assert_eq!(*list.get(index).unwrap(), 0);
-        let next_index = list.next_index(index).unwrap();
-        assert_eq!(*list.get(next_index).unwrap(), 1);
-        let next_index = list.next_index(next_index).unwrap();
-        assert_eq!(*list.get(next_index).unwrap(), 2);
-        assert_eq!(to_vec_forward(&list), vec![0, 1, 2]);
+    #[test]
+    fn u32_lists_saturate_at_u32_max() {
+        let mut list: IndexList<i32, u32> = IndexList {
+            generation: u32::MAX - 1,
+            ..Default::default()
+        };
+        let old = list.push_back(1);
+        list.remove(old);
+        let reused = list.push_back(2);
+        assert_eq!(reused.slot(), old.slot());
+        list.remove(reused);
+        assert_eq!(list.generation, u32::MAX);
+
+        let fresh = list.push_back(3);
+        assert_ne!(fresh.slot(), reused.slot());
+        assert_eq!(list.get(reused), None);
+        assert_eq!(
+            list.index_from_raw(fresh.slot(), u32::MAX as usize + 1),
+            None
+        );
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![3]);
+        assert_eq!(list.validate(), Ok(()));
     }
 
     #[test]
-    fn insert_after_remove() {
+    fn generation_saturates() {
         let mut list = IndexList::new();
+        list.generation = usize::MAX - 1;
+        let old = list.push_back(1);
+        list.remove(old);
+        assert_eq!(list.generation, usize::MAX);
+
+        let reused = list.push_back(2);
+        assert_eq!(reused.slot(), old.slot());
+        assert_eq!(list.get(old), None);
+        list.remove(reused);
+        assert_eq!(list.generation, usize::MAX);
+
+        let fresh = list.push_back(3);
+        assert_ne!(fresh.slot(), reused.slot());
+        assert_eq!(list.get(reused), None);
+        assert_eq!(list.get(fresh), Some(&3));
+        assert_eq!(list.validate(), Ok(()));
+    }
 
-        let index = list.push_front(0);
-        list.insert_after(index, 2);
-        list.insert_after(index, 1);
-        list.remove(index);
+    #[test]
+    fn memory_usage() {
+        let mut list = IndexList::with_capacity(4);
+        let indexes: Vec<_> = (0..3u32).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[1]);
 
-        check_invariants(&list);
-        assert_eq!(to_vec_forward(&list), vec![1, 2]);
+        let entry_size = std::mem::size_of::<Entry<u32>>();
+        assert_eq!(
+            list.memory_usage(),
+            MemoryUsage {
+                allocated_bytes: list.contents.capacity() * entry_size,
+                live_bytes: 2 * entry_size,
+                slots: 3,
+                live: 2,
+                free: 1,
+                quarantined: 0,
+            }
+        );
 
-        let index = list.index_of(&2).unwrap();
-        list.insert_after(index, 3);
+        list.generation = usize::MAX;
+        let saturated = list.push_back(3);
+        list.remove(saturated);
+        let usage = list.memory_usage();
+        assert_eq!((usage.slots, usage.free, usage.quarantined), (3, 0, 1));
+        list.set_reuse_policy(ReusePolicy::LowestIndexFirst);
+        assert_eq!(list.memory_usage(), usage);
+    }
 
-        check_invariants(&list);
-        assert_eq!(to_vec_forward(&list), vec![1, 2, 3]);
+    #[test]
+    fn free_slot_diagnostics() {
+        let mut list = IndexList::new();
+        assert_eq!(list.free_slot_positions().count(), 0);
 
-        let index = list.index_of(&3).unwrap();
-        list.remove(index);
-        let index = list.index_of(&2).unwrap();
-        list.insert_after(index, 4);
+        let indexes: Vec<_> = (0..5).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[3]);
+        list.remove(indexes[1]);
+        assert_eq!(list.slot_count(), 5);
+        assert_eq!(list.free_slots(), 2);
+        assert_eq!(list.free_slot_positions().collect::<Vec<_>>(), vec![1, 3]);
 
-        check_invariants(&list);
-        assert_eq!(to_vec_forward(&list), vec![1, 2, 4]);
+        list.push_back(5);
+        assert_eq!(list.slot_count(), 5);
+        assert_eq!(list.free_slots(), 1);
+        assert_eq!(list.free_slot_positions().collect::<Vec<_>>(), vec![3]);
     }
 
     #[test]
-    fn index_of() {
+    fn reuse_lowest_index_first() {
         let mut list = IndexList::new();
+        let indexes: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[1]);
+        list.remove(indexes[4]);
+        list.remove(indexes[2]);
 
-        list.push_back(5);
-        list.push_back(10);
-        list.push_back(15);
-
+        list.set_reuse_policy(ReusePolicy::LowestIndexFirst);
+        assert_eq!(list.reuse_policy(), ReusePolicy::LowestIndexFirst);
+        check_invariants(&list);
+        assert_eq!(list.push_back(6).slot(), 1);
+        list.remove(indexes[0]);
+        assert_eq!(list.push_front(7).slot(), 0);
+        assert_eq!(list.push_back(8).slot(), 2);
         check_invariants(&list);
 
-        assert_eq!(list.index_of(&10).unwrap(), Index::new(1, 0));
-        assert!(list.index_of(&20).is_none());
+        list.remove(indexes[5]);
+        list.set_reuse_policy(ReusePolicy::LastFreedFirst);
+        check_invariants(&list);
+        assert_eq!(list.push_back(9).slot(), 4);
+        assert_eq!(list.push_back(10).slot(), 5);
+        assert_eq!(list.push_back(11).slot(), 6);
+        check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), vec![7, 3, 6, 8, 9, 10, 11]);
     }
 
     #[test]
-    fn index_of_get_correct_generation() {
+    fn defragment() {
         let mut list = IndexList::new();
+        let indexes: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[0]);
+        list.remove(indexes[3]);
+        list.push_front(6);
+        list.rotate_left(2);
+        let before = to_vec_forward(&list);
 
-        list.push_back(5);
-        let ten = list.push_back(10);
-        list.remove(ten);
-        list.push_back(15);
+        let mut remapped = Vec::new();
+        list.defragment(|old, new| remapped.push((old, new)));
 
         check_invariants(&list);
+        assert_eq!(to_vec_forward(&list), before);
+        assert_eq!(list.contents.len(), before.len());
+        assert_eq!(list.free_slots(), 0);
+        assert_eq!(remapped.len(), before.len());
+        for (position, (old, new)) in remapped.into_iter().enumerate() {
+            assert_eq!(new.slot(), position);
+            assert_eq!(list.get(old), None);
+            assert_eq!(list.get(new), Some(&before[position]));
+        }
+        assert_eq!(list.get(indexes[1]), None);
+    }
+
+    #[test]
+    fn into_sorted_vec() {
+        let mut list = IndexList::new();
+        let indexes: Vec<_> = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')]
+            .into_iter()
+            .map(|item| list.push_back(item))
+            .collect();
+        list.remove(indexes[1]);
+        list.push_front((1, 'e'));
 
         assert_eq!(
-            list.index_of(&5).unwrap(),
-            Index {
-                index: 0,
-                generation: 0,
-                _marker: PhantomData
-            }
+            list.into_sorted_vec_by_key(|&(key, _)| key),
+            vec![(0, 'd'), (1, 'e'), (2, 'a'), (2, 'c')]
         );
     }
 
     #[test]
-    fn index_of_get_first_occurrence() {
+    fn display() {
         let mut list = IndexList::new();
+        assert_eq!(list.to_string(), "[]");
 
-        list.push_back(3);
-        let six = list.push_back(6);
-        let first_nine = list.push_back(9);
-        list.push_back(12);
-
-        list.remove(six);
-
-        let _second_nine = list.push_back(9);
-
-        check_invariants(&list);
+        let one = list.push_back(1);
+        assert_eq!(list.to_string(), "[1]");
 
-        assert_eq!(list.index_of(&9).unwrap(), first_nine);
+        list.push_back(2);
+        list.push_front(0);
+        list.remove(one);
+        assert_eq!(list.to_string(), "[0, 2]");
+        assert_eq!(format!("{:>2}", list), "[ 0,  2]");
     }
 }
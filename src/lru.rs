@@ -0,0 +1,364 @@
+//! A least recently used cache built on `IndexList`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::{Index, IndexList};
+
+/// `LruCache` is a map with a fixed capacity that evicts its least recently used entry to
+/// make room for a new one.
+///
+/// The entries are kept in an [`IndexList`] ordered from least to most recently used, and a
+/// `HashMap` maps each key to the index of its entry, so looking up, touching and evicting
+/// an entry all take O(1) time.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::lru::LruCache;
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// assert_eq!(cache.get(&"a"), Some(&1));
+/// cache.put("c", 3);
+///
+/// assert_eq!(cache.get(&"b"), None);
+/// assert_eq!(cache.pop_lru(), Some(("a", 1)));
+/// ```
+pub struct LruCache<K, V> {
+    map: HashMap<K, Index<(K, V)>>,
+    list: IndexList<(K, V)>,
+    capacity: usize,
+    on_evict: Option<Box<dyn FnMut(K, V)>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Creates a new, empty `LruCache` that holds at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::lru::LruCache;
+    /// let cache: LruCache<&str, i32> = LruCache::new(10);
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            list: IndexList::with_capacity(capacity),
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    /// Sets a callback that is called with every entry evicted to make room for another one,
+    /// including entries evicted by [`LruCache::set_capacity`]. Entries removed explicitly
+    /// are not passed to it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let evicted = Rc::new(RefCell::new(Vec::new()));
+    /// let mut cache = indexlist::lru::LruCache::new(1);
+    /// let sink = Rc::clone(&evicted);
+    /// cache.set_on_evict(move |key, value| sink.borrow_mut().push((key, value)));
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// assert_eq!(*evicted.borrow(), vec![("a", 1)]);
+    /// ```
+    pub fn set_on_evict(&mut self, on_evict: impl FnMut(K, V) + 'static) {
+        self.on_evict = Some(Box::new(on_evict));
+    }
+
+    /// Returns the maximum number of entries in the cache.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let cache: indexlist::lru::LruCache<&str, i32> = indexlist::lru::LruCache::new(10);
+    /// assert_eq!(cache.capacity(), 10);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the maximum number of entries in the cache, evicting the least recently used
+    /// entries if there are more than `capacity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(3);
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// cache.set_capacity(1);
+    /// assert_eq!(cache.len(), 1);
+    /// assert_eq!(cache.peek(&"b"), Some(&2));
+    /// ```
+    pub fn set_capacity(&mut self, capacity: usize) {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        self.capacity = capacity;
+        while self.list.len() > capacity {
+            self.evict();
+        }
+    }
+
+    /// Returns the number of entries in the cache.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the cache contains no entries.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let cache: indexlist::lru::LruCache<&str, i32> = indexlist::lru::LruCache::new(10);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns `true` if the cache contains an entry for `key`, without marking it as used.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// assert!(cache.contains_key(&"a"));
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Returns a reference to the value for `key` and marks the entry as most recently
+    /// used.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// assert_eq!(cache.get(&"a"), Some(&1));
+    /// assert_eq!(cache.get(&"b"), None);
+    /// ```
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value for `key` and marks the entry as most
+    /// recently used.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// *cache.get_mut(&"a").unwrap() += 1;
+    /// assert_eq!(cache.get(&"a"), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = *self.map.get(key)?;
+        self.touch(index);
+        self.list.get_mut(index).map(|(_, value)| value)
+    }
+
+    /// Returns a reference to the value for `key` without marking the entry as used.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// assert_eq!(cache.peek(&"a"), Some(&1));
+    /// ```
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let index = *self.map.get(key)?;
+        self.list.get(index).map(|(_, value)| value)
+    }
+
+    /// Stores `value` for `key`, marks the entry as most recently used and returns the
+    /// previous value for `key`, if any.
+    ///
+    /// If `key` is new and the cache is full, the least recently used entry is evicted
+    /// first and passed to the eviction callback.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(1);
+    /// assert_eq!(cache.put("a", 1), None);
+    /// assert_eq!(cache.put("a", 2), Some(1));
+    /// cache.put("b", 3);
+    /// assert!(!cache.contains_key(&"a"));
+    /// ```
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&index) = self.map.get(&key) {
+            self.touch(index);
+            let (_, stored) = self.list.get_mut(index).expect("Corrupted cache");
+            return Some(std::mem::replace(stored, value));
+        }
+        if self.list.len() >= self.capacity {
+            self.evict();
+        }
+        let index = self.list.push_back((key.clone(), value));
+        self.map.insert(key, index);
+        None
+    }
+
+    /// Removes the entry for `key` and returns its value, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// assert_eq!(cache.pop(&"a"), Some(1));
+    /// assert_eq!(cache.pop(&"a"), None);
+    /// ```
+    pub fn pop(&mut self, key: &K) -> Option<V> {
+        let index = self.map.remove(key)?;
+        self.list.remove(index).map(|(_, value)| value)
+    }
+
+    /// Removes the least recently used entry and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// cache.get(&"a");
+    /// assert_eq!(cache.pop_lru(), Some(("b", 2)));
+    /// ```
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, value) = self.list.pop_front()?;
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    /// Removes all entries from the cache, without passing them to the eviction callback.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// cache.clear();
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list = IndexList::with_capacity(self.capacity);
+    }
+
+    /// Returns an iterator over the entries, from the least to the most recently used.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut cache = indexlist::lru::LruCache::new(10);
+    /// cache.put("a", 1);
+    /// cache.put("b", 2);
+    /// cache.get(&"a");
+    /// assert_eq!(cache.iter().collect::<Vec<_>>(), vec![(&"b", &2), (&"a", &1)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.list.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Moves the entry at `index` to the most recently used end of the list. Relinking the
+    /// entry instead of removing and pushing it again keeps its index and the generation of
+    /// the list unchanged.
+    fn touch(&mut self, index: Index<(K, V)>) {
+        let slot = index.slot();
+        if self.list.tail == Some(slot) {
+            return;
+        }
+        self.list.unlink_range(slot, slot);
+        let tail = self.list.tail;
+        self.list.splice(tail, None, slot, slot);
+        self.list.debug_validate();
+    }
+
+    fn evict(&mut self) {
+        if let Some((key, value)) = self.pop_lru() {
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, value);
+            }
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruCache")
+            .field("list", &self.list)
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let mut cache = LruCache::new(3);
+        let sink = Rc::clone(&evicted);
+        cache.set_on_evict(move |key, value| sink.borrow_mut().push((key, value)));
+
+        for key in 0..3 {
+            cache.put(key, key * 10);
+        }
+        assert_eq!(cache.get(&0), Some(&0));
+        assert_eq!(cache.put(1, 11), Some(10));
+        cache.put(3, 30);
+        cache.put(4, 40);
+        assert_eq!(*evicted.borrow(), vec![(2, 20), (0, 0)]);
+        assert_eq!(
+            cache.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            vec![1, 3, 4]
+        );
+
+        cache.set_capacity(1);
+        assert_eq!(*evicted.borrow(), vec![(2, 20), (0, 0), (1, 11), (3, 30)]);
+        assert_eq!(cache.pop_lru(), Some((4, 40)));
+        assert!(cache.is_empty());
+        assert_eq!(cache.list.validate(), Ok(()));
+    }
+
+    #[test]
+    fn touching_keeps_indices_and_generation() {
+        let mut cache = LruCache::new(4);
+        for key in 0..4 {
+            cache.put(key, key);
+        }
+        let generation = cache.list.generation;
+        let indices = cache.map.clone();
+        for key in [2, 0, 3, 2, 1] {
+            cache.get(&key);
+        }
+        assert_eq!(cache.list.generation, generation);
+        assert_eq!(cache.map, indices);
+        assert_eq!(
+            cache.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+            vec![0, 3, 2, 1]
+        );
+        assert_eq!(cache.list.validate(), Ok(()));
+    }
+}
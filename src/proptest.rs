@@ -0,0 +1,219 @@
+//! Strategies for generating `IndexList`s and mutation histories with `proptest`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use indexlist::proptest::{indexlist_of, operations};
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn lists_are_valid(list in indexlist_of(any::<u8>(), 0..16)) {
+//!         prop_assert!(list.validate().is_ok());
+//!     }
+//!
+//!     fn histories_are_valid(ops in operations(any::<u8>(), 0..32)) {
+//!         let mut list = indexlist::IndexList::new();
+//!         for op in ops {
+//!             op.apply(&mut list);
+//!         }
+//!         prop_assert!(list.validate().is_ok());
+//!     }
+//! }
+//!
+//! lists_are_valid();
+//! histories_are_valid();
+//! ```
+
+use std::fmt::Debug;
+
+use proptest::collection::{vec, SizeRange};
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample;
+
+use crate::{Index, IndexList};
+
+/// A single mutation of an `IndexList`, as generated by [`operations`].
+///
+/// Elements are chosen by their position in the list, so that a shrunk history still
+/// refers to elements that exist.
+#[derive(Debug, Clone)]
+pub enum Operation<T> {
+    /// Appends an element with [`IndexList::push_back`].
+    PushBack(T),
+    /// Prepends an element with [`IndexList::push_front`].
+    PushFront(T),
+    /// Inserts an element with [`IndexList::insert_before`] the chosen element.
+    InsertBefore(sample::Index, T),
+    /// Inserts an element with [`IndexList::insert_after`] the chosen element.
+    InsertAfter(sample::Index, T),
+    /// Removes the chosen element with [`IndexList::remove`].
+    Remove(sample::Index),
+    /// Removes the first element with [`IndexList::pop_front`].
+    PopFront,
+    /// Removes the last element with [`IndexList::pop_back`].
+    PopBack,
+}
+
+impl<T> Operation<T> {
+    /// Applies the operation to `list` and returns the index of the inserted element, if any.
+    ///
+    /// Insertions relative to an element push to the back of an empty list, removals of an
+    /// element do nothing on an empty list.
+    pub fn apply(self, list: &mut IndexList<T>) -> Option<Index<T>> {
+        match self {
+            Operation::PushBack(item) => Some(list.push_back(item)),
+            Operation::PushFront(item) => Some(list.push_front(item)),
+            Operation::InsertBefore(at, item) => match chosen(list, at) {
+                Some(index) => list.insert_before(index, item),
+                None => Some(list.push_back(item)),
+            },
+            Operation::InsertAfter(at, item) => match chosen(list, at) {
+                Some(index) => list.insert_after(index, item),
+                None => Some(list.push_back(item)),
+            },
+            Operation::Remove(at) => {
+                if let Some(index) = chosen(list, at) {
+                    list.remove(index);
+                }
+                None
+            }
+            Operation::PopFront => {
+                list.pop_front();
+                None
+            }
+            Operation::PopBack => {
+                list.pop_back();
+                None
+            }
+        }
+    }
+}
+
+fn chosen<T>(list: &IndexList<T>, at: sample::Index) -> Option<Index<T>> {
+    if list.is_empty() {
+        return None;
+    }
+    list.get_by_position(at.index(list.len()))
+        .map(|(index, _)| index)
+}
+
+/// Returns a strategy generating a single [`Operation`] with elements from `element`.
+pub fn operation<S>(element: S) -> impl Strategy<Value = Operation<S::Value>>
+where
+    S: Strategy + Clone,
+    S::Value: Debug + Clone,
+{
+    prop_oneof![
+        element.clone().prop_map(Operation::PushBack),
+        element.clone().prop_map(Operation::PushFront),
+        (any::<sample::Index>(), element.clone())
+            .prop_map(|(at, item)| Operation::InsertBefore(at, item)),
+        (any::<sample::Index>(), element).prop_map(|(at, item)| Operation::InsertAfter(at, item)),
+        any::<sample::Index>().prop_map(Operation::Remove),
+        Just(Operation::PopFront),
+        Just(Operation::PopBack),
+    ]
+}
+
+/// Returns a strategy generating a history of [`Operation`]s whose length is in `size`.
+///
+/// Shrinking drops operations from the history and simplifies the remaining ones.
+pub fn operations<S>(
+    element: S,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<Operation<S::Value>>>
+where
+    S: Strategy + Clone,
+    S::Value: Debug + Clone,
+{
+    vec(operation(element), size)
+}
+
+/// Returns a strategy generating `IndexList`s whose length is in `size`, with elements from
+/// `element`.
+///
+/// Every element is inserted at a random place in the list. Some elements take a detour:
+/// they are inserted elsewhere and removed first, so the generated lists have reused slots
+/// and elements of different generations, and their storage order differs from their list
+/// order.
+pub fn indexlist_of<S>(
+    element: S,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = IndexList<S::Value>>
+where
+    S: Strategy,
+    S::Value: Debug,
+{
+    let placement = (0..4u8, any::<sample::Index>());
+    vec((element, placement.clone(), option::of(placement)), size).prop_map(|items| {
+        let mut list = IndexList::new();
+        for (item, placement, detour) in items {
+            let item = match detour {
+                Some(detour) => {
+                    let index = place(&mut list, detour, item);
+                    list.remove(index).expect("Corrupted list")
+                }
+                None => item,
+            };
+            place(&mut list, placement, item);
+        }
+        list
+    })
+}
+
+fn place<T>(list: &mut IndexList<T>, (kind, at): (u8, sample::Index), item: T) -> Index<T> {
+    match (kind, chosen(list, at)) {
+        (0, Some(index)) => list.insert_before(index, item),
+        (1, Some(index)) => list.insert_after(index, item),
+        (2, _) => Some(list.push_front(item)),
+        _ => Some(list.push_back(item)),
+    }
+    .expect("Corrupted list")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn generated_lists_are_valid(list in indexlist_of(any::<u8>(), 0..32)) {
+            prop_assert_eq!(list.validate(), Ok(()));
+            prop_assert!(list.len() < 32);
+            prop_assert_eq!(list.iter().count(), list.len());
+        }
+
+        #[test]
+        fn operations_match_a_vecdeque(ops in operations(any::<u8>(), 0..64)) {
+            let mut list = IndexList::new();
+            let mut model = VecDeque::new();
+            for op in ops {
+                let len = model.len();
+                match &op {
+                    Operation::PushBack(item) => model.push_back(*item),
+                    Operation::PushFront(item) => model.push_front(*item),
+                    Operation::InsertBefore(_, item) if len == 0 => model.push_back(*item),
+                    Operation::InsertBefore(at, item) => model.insert(at.index(len), *item),
+                    Operation::InsertAfter(_, item) if len == 0 => model.push_back(*item),
+                    Operation::InsertAfter(at, item) => model.insert(at.index(len) + 1, *item),
+                    Operation::Remove(_) if len == 0 => {}
+                    Operation::Remove(at) => {
+                        model.remove(at.index(len));
+                    }
+                    Operation::PopFront => {
+                        model.pop_front();
+                    }
+                    Operation::PopBack => {
+                        model.pop_back();
+                    }
+                }
+                op.apply(&mut list);
+                prop_assert_eq!(list.validate(), Ok(()));
+            }
+            prop_assert_eq!(list.iter().copied().collect::<Vec<_>>(), Vec::from(model));
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Taking an `IndexList` apart into plain data and putting it back together.
+
+use crate::Entry::{Free, Occupied};
+use crate::{
+    Allocator, Finger, IndexList, IndexType, InvariantError, ListId, OccupiedEntry, ReusePolicy,
+    Slot, Stats,
+};
+
+/// A slot of the vector behind an `IndexList`, as returned by [`IndexList::into_raw_parts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawEntry<T> {
+    /// A free slot, waiting to be reused.
+    Free {
+        /// The next slot on the free list.
+        next_free: Option<usize>,
+    },
+    /// A slot holding an element of the list.
+    Occupied {
+        /// The element.
+        item: T,
+        /// The generation the element was inserted with.
+        generation: usize,
+        /// The slot of the next element in the list.
+        next: Option<usize>,
+        /// The slot of the previous element in the list.
+        prev: Option<usize>,
+    },
+}
+
+/// The components of an `IndexList`, as returned by [`IndexList::into_raw_parts`] and
+/// accepted by [`IndexList::from_raw_parts`].
+///
+/// Slots are positions in `entries`. An occupied entry without links that is not the head
+/// holds a detached element, see [`IndexList::detach`]. The reuse policy and maximum capacity of the list are
+/// not part of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawParts<T> {
+    /// The id that indices into the list carry with the `list-id` feature, 0 without it.
+    pub list_id: u64,
+    /// The vector behind the list, free slots included.
+    pub entries: Vec<RawEntry<T>>,
+    /// The generation new elements are inserted with.
+    pub generation: usize,
+    /// The first slot on the free list.
+    pub next_free: Option<usize>,
+    /// The slot of the first element.
+    pub head: Option<usize>,
+    /// The slot of the last element.
+    pub tail: Option<usize>,
+    /// The number of elements, not counting detached ones.
+    pub count: usize,
+}
+
+impl<T, I: IndexType, A: Allocator + Clone> IndexList<T, I, A> {
+    /// Consumes the list and returns its components, laid out exactly as in memory so that
+    /// indices into the list stay valid for the list rebuilt by
+    /// [`IndexList::from_raw_parts`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::IndexList;
+    ///
+    /// let mut list = IndexList::new();
+    /// let five = list.push_back(5);
+    /// let parts = list.into_raw_parts();
+    /// assert_eq!(parts.count, 1);
+    ///
+    /// let list = IndexList::from_raw_parts(parts).unwrap();
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn into_raw_parts(mut self) -> RawParts<T> {
+        // A single free list is all the parts can describe.
+        self.set_reuse_policy(ReusePolicy::LastFreedFirst);
+        #[cfg(feature = "list-id")]
+        let list_id = self.id.0;
+        #[cfg(not(feature = "list-id"))]
+        let list_id = 0;
+        RawParts {
+            list_id,
+            entries: self
+                .contents
+                .into_iter()
+                .map(|entry| match entry {
+                    Free { next_free } => RawEntry::Free {
+                        next_free: next_free.map(Slot::get),
+                    },
+                    Occupied(oc) => RawEntry::Occupied {
+                        next: oc.next(),
+                        prev: oc.prev(),
+                        item: oc.item,
+                        generation: oc.generation.to_usize(),
+                    },
+                })
+                .collect(),
+            generation: self.generation.to_usize(),
+            next_free: self.next_free,
+            head: self.head,
+            tail: self.tail,
+            count: self.count,
+        }
+    }
+}
+
+impl<T, I: IndexType> IndexList<T, I> {
+    /// Rebuilds a list from components returned by [`IndexList::into_raw_parts`], or put
+    /// together by hand.
+    ///
+    /// The components are checked like [`IndexList::validate`] does, and rejected if they do
+    /// not describe a valid list. With the `list-id` feature, the list keeps `list_id`, so
+    /// it accepts the indices of the list the components came from.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::{IndexList, InvariantError, RawEntry, RawParts};
+    ///
+    /// let parts = RawParts {
+    ///     list_id: 0,
+    ///     entries: vec![RawEntry::Occupied { item: 5, generation: 0, next: None, prev: None }],
+    ///     generation: 0,
+    ///     next_free: None,
+    ///     head: Some(0),
+    ///     tail: Some(0),
+    ///     count: 1,
+    /// };
+    /// let list = IndexList::<i32>::from_raw_parts(parts.clone()).unwrap();
+    /// assert_eq!(list.head(), Some(&5));
+    ///
+    /// let broken = RawParts { count: 2, ..parts };
+    /// assert_eq!(
+    ///     IndexList::<i32>::from_raw_parts(broken).unwrap_err(),
+    ///     InvariantError::CountMismatch { stored: 2, occupied: 1 },
+    /// );
+    /// ```
+    pub fn from_raw_parts(parts: RawParts<T>) -> Result<Self, InvariantError> {
+        let link = |slot: Option<usize>| {
+            slot.map(|slot| Slot::checked(slot).ok_or(InvariantError::LinkToFreeSlot { slot }))
+                .transpose()
+        };
+        let generation =
+            |generation| I::from_usize(generation).ok_or(InvariantError::GenerationOutOfRange);
+        let mut contents = crate::alloc::Vec::with_capacity(parts.entries.len());
+        for entry in parts.entries {
+            contents.push(match entry {
+                RawEntry::Free { next_free } => Free {
+                    next_free: link(next_free).or(Err(InvariantError::BrokenFreeList))?,
+                },
+                RawEntry::Occupied {
+                    item,
+                    generation: entry_generation,
+                    next,
+                    prev,
+                } => Occupied(OccupiedEntry {
+                    item,
+                    generation: generation(entry_generation)?,
+                    next: link(next)?,
+                    prev: link(prev)?,
+                }),
+            });
+        }
+        #[cfg(feature = "list-id")]
+        let id = ListId::restore(parts.list_id);
+        #[cfg(not(feature = "list-id"))]
+        let id = ListId::next();
+        let mut list = IndexList {
+            id,
+            contents,
+            generation: generation(parts.generation)?,
+            next_free: parts.next_free,
+            ordered_free: None,
+            head: parts.head,
+            tail: parts.tail,
+            count: parts.count,
+            max_capacity: None,
+            detached: 0,
+            finger: Finger::default(),
+            stats: Stats::default(),
+        };
+        list.detached = list.detached_slots().count();
+        list.validate()?;
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_parts_round_trip() {
+        let mut list = IndexList::new();
+        let indices: Vec<_> = (0..6).map(|item| list.push_back(item)).collect();
+        list.set_reuse_policy(ReusePolicy::LowestIndexFirst);
+        list.remove(indices[1]);
+        list.remove(indices[4]);
+        list.push_front(10);
+
+        let parts = list.into_raw_parts();
+        assert_eq!(parts.count, 5);
+        assert_eq!(parts.next_free, Some(4));
+        let mut list = IndexList::from_raw_parts(parts).unwrap();
+        for (item, &index) in indices.iter().enumerate() {
+            assert_eq!(list.get(index), (![1, 4].contains(&item)).then_some(&item));
+        }
+        assert_eq!(list.to_string(), "[10, 0, 2, 3, 5]");
+        list.push_back(6);
+        assert_eq!(list.free_slots(), 0);
+    }
+
+    #[test]
+    fn rejects_broken_parts() {
+        let parts = IndexList::<i32>::new().into_raw_parts();
+        let dangling = RawParts {
+            head: Some(0),
+            tail: Some(0),
+            ..parts.clone()
+        };
+        assert_eq!(
+            IndexList::<i32>::from_raw_parts(dangling).unwrap_err(),
+            InvariantError::BrokenChain
+        );
+        let free_list = RawParts {
+            next_free: Some(3),
+            ..parts.clone()
+        };
+        assert_eq!(
+            IndexList::<i32>::from_raw_parts(free_list).unwrap_err(),
+            InvariantError::BrokenFreeList
+        );
+        let chain = RawParts {
+            entries: vec![RawEntry::Occupied {
+                item: 1,
+                generation: 0,
+                next: Some(usize::MAX),
+                prev: None,
+            }],
+            head: Some(0),
+            tail: Some(0),
+            count: 1,
+            ..parts
+        };
+        assert!(IndexList::<i32>::from_raw_parts(chain).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! Support for zero-copy archiving of `IndexList` and `Index` with `rkyv`.
+
+use rkyv::primitive::ArchivedUsize;
+use rkyv::rancor::{Fallible, Source};
+use rkyv::ser::{Allocator as SerAllocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::with::{ArchiveWith, DeserializeWith, SerializeWith};
+use rkyv::{Archive, Archived, Deserialize, Place, Serialize};
+
+use crate::{
+    Allocator, ArchivedEntry, ArchivedIndexList, Finger, Index, IndexList, IndexType,
+    InvariantError, ListId, Slot, Stats,
+};
+
+/// Archives the storage of a list, which lives in its allocator, like a plain `Vec`. It is
+/// deserialized into a new vector in the default instance of the allocator.
+pub(crate) struct Storage;
+
+impl<E: Archive, A: Allocator> ArchiveWith<crate::alloc::Vec<E, A>> for Storage {
+    type Archived = ArchivedVec<E::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve_with(
+        field: &crate::alloc::Vec<E, A>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedVec::resolve_from_len(field.len(), resolver, out);
+    }
+}
+
+impl<E, A, S> SerializeWith<crate::alloc::Vec<E, A>, S> for Storage
+where
+    E: Serialize<S>,
+    A: Allocator,
+    S: Fallible + SerAllocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &crate::alloc::Vec<E, A>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::serialize_from_slice(field.as_slice(), serializer)
+    }
+}
+
+impl<E, A, D> DeserializeWith<ArchivedVec<E::Archived>, crate::alloc::Vec<E, A>, D> for Storage
+where
+    E: Archive,
+    E::Archived: Deserialize<E, D>,
+    A: Allocator + Default,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedVec<E::Archived>,
+        deserializer: &mut D,
+    ) -> Result<crate::alloc::Vec<E, A>, D::Error> {
+        let mut storage = crate::alloc::Vec::with_capacity_in(field.len(), A::default());
+        for entry in field.iter() {
+            storage.push(entry.deserialize(deserializer)?);
+        }
+        Ok(storage)
+    }
+}
+
+/// Archives a generation as a `usize`, whatever the index type of the list, so that lists of
+/// every index type share one archived layout.
+pub(crate) struct Wide;
+
+impl<I: IndexType> ArchiveWith<I> for Wide {
+    type Archived = ArchivedUsize;
+    type Resolver = ();
+
+    fn resolve_with(field: &I, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        field.to_usize().resolve(resolver, out);
+    }
+}
+
+impl<I: IndexType, S: Fallible + ?Sized> SerializeWith<I, S> for Wide {
+    fn serialize_with(_: &I, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+/// A generation that does not fit into the index type of the list is rejected.
+impl<I: IndexType, D> DeserializeWith<ArchivedUsize, I, D> for Wide
+where
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(field: &ArchivedUsize, _: &mut D) -> Result<I, D::Error> {
+        I::from_usize(slot(field))
+            .ok_or_else(|| D::Error::new(InvariantError::GenerationOutOfRange))
+    }
+}
+
+/// A slot is archived as its plain number, the complement it is stored as in memory would not
+/// survive rkyv narrowing `usize` to 32 bits.
+impl<I: IndexType> Archive for Slot<I> {
+    type Archived = ArchivedUsize;
+    type Resolver = ();
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        self.get().resolve(resolver, out);
+    }
+}
+
+impl<I: IndexType, S: Fallible + ?Sized> Serialize<S> for Slot<I> {
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+/// A slot that does not fit into the slot type of the list is rejected.
+impl<I: IndexType, D: Fallible + ?Sized> Deserialize<Slot<I>, D> for ArchivedUsize
+where
+    D::Error: Source,
+{
+    fn deserialize(&self, _: &mut D) -> Result<Slot<I>, D::Error> {
+        let slot = slot(self);
+        Slot::checked(slot).ok_or_else(|| D::Error::new(InvariantError::LinkToFreeSlot { slot }))
+    }
+}
+
+/// Rejects an archive that does not describe a valid list, see [`IndexList::validate`].
+impl<T, I, A, D> Deserialize<IndexList<T, I, A>, D> for ArchivedIndexList<T, I, A>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    I: IndexType,
+    A: Allocator + Clone + Default,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<IndexList<T, I, A>, D::Error> {
+        let mut list = IndexList {
+            id: self.id.deserialize(deserializer)?,
+            contents: Storage::deserialize_with(&self.contents, deserializer)?,
+            generation: Wide::deserialize_with(&self.generation, deserializer)?,
+            next_free: self.next_free.as_ref().map(slot),
+            ordered_free: self.ordered_free.deserialize(deserializer)?,
+            head: self.head.as_ref().map(slot),
+            tail: self.tail.as_ref().map(slot),
+            count: slot(&self.count),
+            detached: 0,
+            max_capacity: self.max_capacity.as_ref().map(slot),
+            finger: Finger::default(),
+            stats: Stats::default(),
+        };
+        list.detached = list.detached_slots().count();
+        list.validate().map_err(D::Error::new)?;
+        Ok(list)
+    }
+}
+
+/// A deserialized list gets the id it was archived with, so the indices archived alongside
+/// it keep working. Deserializing the same archive twice gives two lists with the same id,
+/// and each of them accepts the other's indices.
+impl<D: Fallible + ?Sized> Deserialize<ListId, D> for Archived<ListId> {
+    fn deserialize(&self, _: &mut D) -> Result<ListId, D::Error> {
+        #[cfg(feature = "list-id")]
+        return Ok(ListId::restore(self.0.to_native()));
+        #[cfg(not(feature = "list-id"))]
+        Ok(ListId)
+    }
+}
+
+fn slot(archived: &ArchivedUsize) -> usize {
+    archived.to_native() as usize
+}
+
+/// Read access to an archived list, without deserializing it.
+///
+/// The archived form only passes `rkyv`'s structural validation, so these methods do not
+/// trust its links: they stop at a broken link instead of panicking.
+impl<T: Archive, I: IndexType, A: Allocator + Clone> ArchivedIndexList<T, I, A> {
+    /// Returns the number of elements in the archived list.
+    pub fn len(&self) -> usize {
+        slot(&self.count)
+    }
+
+    /// Returns `true` if the archived list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the archived element at the given index, or `None` if the
+    /// index is not valid for the list that was archived.
+    pub fn get(&self, index: Index<T, I>) -> Option<&T::Archived> {
+        #[cfg(feature = "list-id")]
+        if self.id.0.to_native() != index.list.0 {
+            return None;
+        }
+        match self.contents.get(index.slot())? {
+            ArchivedEntry::Occupied(oc) if slot(&oc.generation) == index.generation.to_usize() => {
+                Some(&oc.item)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the archived elements in list order.
+    pub fn iter(&self) -> impl Iterator<Item = &T::Archived> + '_ {
+        let mut next = self.head.as_ref().map(slot);
+        let mut remaining = self.len();
+        std::iter::from_fn(move || {
+            let current = next.filter(|_| remaining > 0)?;
+            remaining -= 1;
+            match self.contents.get(current)? {
+                ArchivedEntry::Occupied(oc) => {
+                    next = oc.next.as_ref().map(slot);
+                    Some(&oc.item)
+                }
+                ArchivedEntry::Free { .. } => None,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rkyv::rancor::Error;
+
+    use crate::IndexList;
+
+    use super::*;
+
+    #[test]
+    fn archived_list_is_usable_in_place() {
+        let mut list = IndexList::new();
+        let indexes: Vec<_> = (0..5).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[1]);
+        let reused = list.push_front(5);
+
+        let bytes = rkyv::to_bytes::<Error>(&list).unwrap();
+        let archived = rkyv::access::<ArchivedIndexList<i32>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 5);
+        assert_eq!(
+            archived
+                .iter()
+                .map(|item| item.to_native())
+                .collect::<Vec<_>>(),
+            vec![5, 0, 2, 3, 4]
+        );
+        assert_eq!(archived.get(reused).map(|item| item.to_native()), Some(5));
+        assert_eq!(archived.get(indexes[1]), None);
+        assert_eq!(
+            archived.get(indexes[4]).map(|item| item.to_native()),
+            Some(4)
+        );
+
+        let loaded: IndexList<i32> = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert_eq!(loaded, list);
+        assert_eq!(loaded.validate(), Ok(()));
+
+        let bytes = rkyv::to_bytes::<Error>(&reused).unwrap();
+        let index: Index<i32> = rkyv::from_bytes::<Index<i32>, Error>(&bytes).unwrap();
+        assert_eq!(loaded.get(index), Some(&5));
+    }
+
+    #[test]
+    fn corrupted_archives_are_rejected() {
+        let mut list = IndexList::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.tail = Some(0);
+        let bytes = rkyv::to_bytes::<Error>(&list).unwrap();
+        let archived = rkyv::access::<ArchivedIndexList<i32>, Error>(&bytes).unwrap();
+        assert!(rkyv::deserialize::<IndexList<i32>, Error>(archived).is_err());
+
+        let index = IndexList::new().push_back(1);
+        let mut bytes = rkyv::to_bytes::<Error>(&index).unwrap();
+        let root = bytes.len() - std::mem::size_of::<Archived<Index<i32>>>();
+        bytes[root..root + 4].fill(0xff);
+        assert!(rkyv::from_bytes::<Index<i32, u32>, Error>(&bytes).is_err());
+        let loaded = rkyv::from_bytes::<Index<i32>, Error>(&bytes).unwrap();
+        assert_eq!(loaded.slot(), u32::MAX as usize);
+    }
+}
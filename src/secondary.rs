@@ -0,0 +1,315 @@
+//! A map from the indices of an `IndexList` to auxiliary data.
+
+use crate::{Index, IndexList};
+
+/// `SecondaryMap` stores a value of type `V` for elements of an [`IndexList<T>`], addressed
+/// by their [`Index<T>`].
+///
+/// Values are stored by slot together with the full index, so a stale index never reads the
+/// value of a newer element that reused its slot. The map does not see removals from the
+/// list: the value of a removed element stays in the map until it is removed, replaced by
+/// the value of a newer element in the same slot, or dropped by
+/// [`SecondaryMap::retain_live`].
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::{IndexList, SecondaryMap};
+///
+/// let mut list = IndexList::new();
+/// let mut names = SecondaryMap::new();
+/// let five = list.push_back(5);
+/// names.insert(five, "five");
+/// list.remove(five);
+/// let six = list.push_back(6);
+///
+/// assert_eq!(names.get(five), Some(&"five"));
+/// assert_eq!(names.get(six), None);
+/// names.retain_live(&list);
+/// assert_eq!(names.get(five), None);
+/// ```
+#[derive(Debug)]
+pub struct SecondaryMap<T, V> {
+    slots: Vec<Option<(Index<T>, V)>>,
+    len: usize,
+}
+
+impl<T, V> Default for SecondaryMap<T, V> {
+    fn default() -> Self {
+        SecondaryMap {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+/// Returns `true` if `a` and `b` refer to the same element of the same list.
+fn same<T>(a: Index<T>, b: Index<T>) -> bool {
+    a.index == b.index && a.generation == b.generation && a.list == b.list
+}
+
+impl<T, V> SecondaryMap<T, V> {
+    /// Creates a new, empty `SecondaryMap`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::SecondaryMap;
+    /// let map: SecondaryMap<i32, &str> = SecondaryMap::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new, empty `SecondaryMap` with room for the slots of a list of the specified
+    /// capacity.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::SecondaryMap;
+    /// let map: SecondaryMap<i32, &str> = SecondaryMap::with_capacity(10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        SecondaryMap {
+            slots: Vec::with_capacity(capacity),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of values in the map.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// map.insert(list.push_back(5), "five");
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no values.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let map: indexlist::SecondaryMap<i32, &str> = indexlist::SecondaryMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Stores `value` for the element at `index` and returns the value previously stored for
+    /// that element, if any.
+    ///
+    /// A value stored for an older element in the same slot is dropped. If the slot already
+    /// holds the value of a newer element, `index` is stale: nothing is stored and `None` is
+    /// returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(map.insert(five, "five"), None);
+    /// assert_eq!(map.insert(five, "FIVE"), Some("five"));
+    /// ```
+    pub fn insert(&mut self, index: Index<T>, value: V) -> Option<V> {
+        let slot = index.slot();
+        if slot >= self.slots.len() {
+            self.slots.resize_with(slot + 1, || None);
+        }
+        let entry = &mut self.slots[slot];
+        match entry {
+            Some((stored, _)) if same(*stored, index) => {
+                entry.replace((index, value)).map(|(_, old)| old)
+            }
+            Some((stored, _))
+                if stored.list == index.list && stored.generation > index.generation =>
+            {
+                None
+            }
+            Some(_) => {
+                *entry = Some((index, value));
+                None
+            }
+            None => {
+                *entry = Some((index, value));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the map holds a value for the element at `index`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// let five = list.push_back(5);
+    /// map.insert(five, "five");
+    /// assert!(map.contains_index(five));
+    /// ```
+    pub fn contains_index(&self, index: Index<T>) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Returns a reference to the value stored for the element at `index`, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// let five = list.push_back(5);
+    /// map.insert(five, "five");
+    /// assert_eq!(map.get(five), Some(&"five"));
+    /// ```
+    pub fn get(&self, index: Index<T>) -> Option<&V> {
+        match self.slots.get(index.slot())? {
+            Some((stored, value)) if same(*stored, index) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value stored for the element at `index`, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// let five = list.push_back(5);
+    /// map.insert(five, 1);
+    /// *map.get_mut(five).unwrap() += 1;
+    /// assert_eq!(map.get(five), Some(&2));
+    /// ```
+    pub fn get_mut(&mut self, index: Index<T>) -> Option<&mut V> {
+        match self.slots.get_mut(index.slot())? {
+            Some((stored, value)) if same(*stored, index) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Removes the value stored for the element at `index` and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// let five = list.push_back(5);
+    /// map.insert(five, "five");
+    /// assert_eq!(map.remove(five), Some("five"));
+    /// assert_eq!(map.remove(five), None);
+    /// ```
+    pub fn remove(&mut self, index: Index<T>) -> Option<V> {
+        let entry = self.slots.get_mut(index.slot())?;
+        match entry {
+            Some((stored, _)) if same(*stored, index) => {
+                self.len -= 1;
+                entry.take().map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes all values from the map.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// map.insert(list.push_back(5), "five");
+    /// map.clear();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    /// Removes the values of all elements that are no longer in `list`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// let five = list.push_back(5);
+    /// map.insert(five, "five");
+    /// list.remove(five);
+    /// map.retain_live(&list);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn retain_live(&mut self, list: &IndexList<T>) {
+        for entry in &mut self.slots {
+            if matches!(entry, Some((index, _)) if !list.contains_index(*index)) {
+                *entry = None;
+                self.len -= 1;
+            }
+        }
+    }
+
+    /// Returns an iterator over the indices and values in the map, in slot order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let mut map = indexlist::SecondaryMap::new();
+    /// let five = list.push_back(5);
+    /// map.insert(five, "five");
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(five, &"five")]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Index<T>, &V)> + '_ {
+        self.slots
+            .iter()
+            .filter_map(|entry| entry.as_ref().map(|(index, value)| (*index, value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_indices_do_not_see_newer_values() {
+        let mut list = IndexList::new();
+        let mut map = SecondaryMap::new();
+
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        map.insert(one, "one");
+        map.insert(two, "two");
+        list.remove(one);
+        let three = list.push_back(3);
+        assert_eq!(three.slot(), one.slot());
+
+        assert_eq!(map.insert(three, "three"), None);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(one), None);
+        assert_eq!(map.get(three), Some(&"three"));
+
+        assert_eq!(map.insert(one, "stale"), None);
+        assert_eq!(map.get(three), Some(&"three"));
+        assert_eq!(map.remove(one), None);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn retain_live_drops_removed_elements() {
+        let mut list = IndexList::new();
+        let mut map = SecondaryMap::new();
+
+        let indexes: Vec<_> = (0..4).map(|item| list.push_back(item)).collect();
+        for &index in &indexes {
+            map.insert(index, list.get(index).unwrap() * 10);
+        }
+        list.remove(indexes[1]);
+        list.remove(indexes[2]);
+        map.retain_live(&list);
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.iter().map(|(_, value)| *value).collect::<Vec<_>>(),
+            vec![0, 30]
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Support for serializing `IndexList` and `Index` with `serde`.
+//!
+//! A list is stored as it is laid out in memory, free slots and generations included, so that
+//! indices saved alongside it refer to the same elements after it is loaded again.
+
+use std::collections::BTreeSet;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Entry::{Free, Occupied};
+use crate::{Allocator, Entry, Index, IndexList, IndexType, ListId, OccupiedEntry, Slot};
+
+#[derive(Serialize)]
+#[serde(rename = "Entry")]
+enum EntryRef<'a, T> {
+    Free {
+        next_free: Option<usize>,
+    },
+    Occupied {
+        item: &'a T,
+        generation: usize,
+        next: Option<usize>,
+        prev: Option<usize>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Entry")]
+enum EntryData<T> {
+    Free {
+        next_free: Option<usize>,
+    },
+    Occupied {
+        item: T,
+        generation: usize,
+        next: Option<usize>,
+        prev: Option<usize>,
+    },
+}
+
+struct Entries<'a, T, I: IndexType>(&'a [Entry<T, I>]);
+
+impl<T: Serialize, I: IndexType> Serialize for Entries<'_, T, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|entry| match entry {
+            Free { next_free } => EntryRef::Free {
+                next_free: next_free.map(Slot::get),
+            },
+            Occupied(oc) => EntryRef::Occupied {
+                item: &oc.item,
+                generation: oc.generation.to_usize(),
+                next: oc.next(),
+                prev: oc.prev(),
+            },
+        }))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "IndexList", bound = "T: Serialize")]
+struct ListRef<'a, T, I: IndexType> {
+    #[cfg(feature = "list-id")]
+    id: u64,
+    contents: Entries<'a, T, I>,
+    generation: usize,
+    next_free: Option<usize>,
+    ordered_free: Option<&'a BTreeSet<usize>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_capacity: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "IndexList")]
+struct ListData<T> {
+    #[cfg(feature = "list-id")]
+    id: u64,
+    contents: Vec<EntryData<T>>,
+    generation: usize,
+    next_free: Option<usize>,
+    ordered_free: Option<BTreeSet<usize>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    #[serde(default)]
+    max_capacity: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Index")]
+struct IndexData {
+    slot: usize,
+    generation: usize,
+    #[cfg(feature = "list-id")]
+    list: u64,
+}
+
+impl<T: Serialize, I: IndexType, A: Allocator + Clone> Serialize for IndexList<T, I, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ListRef {
+            #[cfg(feature = "list-id")]
+            id: self.id.0,
+            contents: Entries(&self.contents),
+            generation: self.generation.to_usize(),
+            next_free: self.next_free,
+            ordered_free: self.ordered_free.as_ref(),
+            head: self.head,
+            tail: self.tail,
+            max_capacity: self.max_capacity,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Rejects input that does not describe a valid list, see [`IndexList::validate`].
+///
+/// With the `list-id` feature, the loaded list keeps the id it was saved with, so that the
+/// indices saved alongside it keep working. Loading the same input twice gives two lists with
+/// the same id, and each of them accepts the other's indices.
+impl<'de, T, I, A> Deserialize<'de> for IndexList<T, I, A>
+where
+    T: Deserialize<'de>,
+    I: IndexType,
+    A: Allocator + Clone + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = ListData::<T>::deserialize(deserializer)?;
+        let mut contents = crate::alloc::Vec::with_capacity_in(data.contents.len(), A::default());
+        for entry in data.contents {
+            contents.push(match entry {
+                EntryData::Free { next_free } => Free {
+                    next_free: link(next_free)?,
+                },
+                EntryData::Occupied {
+                    item,
+                    generation: entry_generation,
+                    next,
+                    prev,
+                } => Occupied(OccupiedEntry {
+                    item,
+                    generation: generation(entry_generation)?,
+                    next: link(next)?,
+                    prev: link(prev)?,
+                }),
+            });
+        }
+        let mut list = IndexList {
+            #[cfg(feature = "list-id")]
+            id: ListId::restore(data.id),
+            #[cfg(not(feature = "list-id"))]
+            id: ListId::next(),
+            count: contents.iter().filter(|e| matches!(e, Occupied(_))).count(),
+            contents,
+            generation: generation(data.generation)?,
+            next_free: data.next_free,
+            ordered_free: data.ordered_free,
+            head: data.head,
+            tail: data.tail,
+            max_capacity: data.max_capacity,
+            detached: 0,
+            finger: Default::default(),
+            stats: Default::default(),
+        };
+        list.detached = list.detached_slots().count();
+        list.count -= list.detached;
+        list.validate().map_err(D::Error::custom)?;
+        Ok(list)
+    }
+}
+
+fn link<I: IndexType, E: Error>(slot: Option<usize>) -> Result<Option<Slot<I>>, E> {
+    slot.map(|slot| Slot::checked(slot).ok_or_else(|| E::custom("slot out of range")))
+        .transpose()
+}
+
+fn generation<I: IndexType, E: Error>(generation: usize) -> Result<I, E> {
+    I::from_usize(generation).ok_or_else(|| E::custom("generation out of range"))
+}
+
+impl<T, I: IndexType> Serialize for Index<T, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        IndexData {
+            slot: self.slot(),
+            generation: self.generation.to_usize(),
+            #[cfg(feature = "list-id")]
+            list: self.list.0,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T, I: IndexType> Deserialize<'de> for Index<T, I> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = IndexData::deserialize(deserializer)?;
+        let slot = link::<I, D::Error>(Some(data.slot))?.expect("a slot was given");
+        #[cfg(feature = "list-id")]
+        let list = ListId::restore(data.list);
+        #[cfg(not(feature = "list-id"))]
+        let list = ListId::next();
+        Ok(Index::new(slot.get(), generation(data.generation)?, list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_survive_a_round_trip() {
+        let mut list = IndexList::new();
+        let indexes: Vec<_> = (0..5).map(|item| list.push_back(item)).collect();
+        list.remove(indexes[1]);
+        list.remove(indexes[3]);
+        let reused = list.push_front(5);
+
+        let saved_list = serde_json::to_string(&list).unwrap();
+        let saved_indexes = serde_json::to_string(&(indexes.clone(), reused)).unwrap();
+        let loaded: IndexList<i32> = serde_json::from_str(&saved_list).unwrap();
+        let (loaded_indexes, loaded_reused): (Vec<Index<i32>>, Index<i32>) =
+            serde_json::from_str(&saved_indexes).unwrap();
+
+        assert_eq!(loaded, list);
+        assert_eq!(loaded.to_string(), "[5, 0, 2, 4]");
+        assert_eq!(loaded.get(loaded_reused), Some(&5));
+        assert_eq!(loaded.get(loaded_indexes[1]), None);
+        assert_eq!(loaded.get(loaded_indexes[3]), None);
+        assert_eq!(loaded.get(loaded_indexes[4]), Some(&4));
+        assert_eq!(loaded_indexes[4], indexes[4]);
+    }
+
+    #[cfg(feature = "list-id")]
+    #[test]
+    fn loaded_lists_keep_their_id() {
+        let mut list = IndexList::new();
+        let five = list.push_back(5);
+        let saved = serde_json::to_string(&list).unwrap();
+
+        let first: IndexList<i32> = serde_json::from_str(&saved).unwrap();
+        let second: IndexList<i32> = serde_json::from_str(&saved).unwrap();
+        assert_eq!(first.get(five), Some(&5));
+        assert_eq!(second.get(five), Some(&5));
+        assert_ne!(IndexList::<i32>::new().push_back(5).list, first.id);
+    }
+
+    #[test]
+    fn corrupted_input_is_rejected() {
+        let mut list = IndexList::new();
+        list.push_back(1);
+        list.push_back(2);
+        let saved = serde_json::to_string(&list).unwrap();
+
+        let broken = saved.replace("\"tail\":1", "\"tail\":0");
+        assert!(serde_json::from_str::<IndexList<i32>>(&broken).is_err());
+        let broken = saved.replace("\"next\":1", "\"next\":7");
+        assert!(serde_json::from_str::<IndexList<i32>>(&broken).is_err());
+    }
+
+    #[test]
+    fn input_with_both_free_structures_is_rejected() {
+        let mut list = IndexList::new();
+        let indices: Vec<_> = (1..=3).map(|item| list.push_back(item)).collect();
+        list.remove(indices[0]);
+        list.remove(indices[1]);
+        let saved = serde_json::to_string(&list).unwrap();
+        assert!(saved.contains("\"next_free\":1,\"ordered_free\":null"));
+
+        // Each free slot is counted once, only the two structures are in use together.
+        let broken = saved
+            .replace("{\"next_free\":0}", "{\"next_free\":null}")
+            .replace("\"ordered_free\":null", "\"ordered_free\":[0]");
+        assert!(serde_json::from_str::<IndexList<i32>>(&broken).is_err());
+        let broken = saved.replace(
+            "\"next_free\":1,\"ordered_free\":null",
+            "\"next_free\":0,\"ordered_free\":[0]",
+        );
+        assert!(serde_json::from_str::<IndexList<i32>>(&broken).is_err());
+    }
+}
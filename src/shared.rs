@@ -0,0 +1,353 @@
+//! A single-threaded shared `IndexList` whose elements can be tied to guards.
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use crate::{Index, IndexList};
+
+/// `SharedIndexList` is a reference-counted handle to an [`IndexList`], for registration-style
+/// use where elements are removed when their owner goes away.
+///
+/// Cloning the handle gives another handle to the same list. Elements added with
+/// [`SharedIndexList::push_back_guarded`] or [`SharedIndexList::push_front_guarded`] are
+/// removed from the list when the returned [`IndexGuard`] is dropped.
+///
+/// A guard dropped while the list is borrowed cannot remove its element right away, so the
+/// removal is queued and carried out on the next [`SharedIndexList::borrow_mut`] or guarded
+/// push.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::shared::SharedIndexList;
+///
+/// let subscribers = SharedIndexList::new();
+/// let first = subscribers.push_back_guarded("first");
+/// {
+///     let _second = subscribers.push_back_guarded("second");
+///     assert_eq!(subscribers.borrow().len(), 2);
+/// }
+/// assert_eq!(subscribers.borrow().len(), 1);
+/// drop(first);
+/// assert!(subscribers.borrow().is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct SharedIndexList<T> {
+    inner: Rc<Inner<T>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner<T> {
+    list: RefCell<IndexList<T>>,
+    /// The elements whose guards were dropped while the list was borrowed.
+    pending: RefCell<Vec<Index<T>>>,
+}
+
+impl<T> Clone for SharedIndexList<T> {
+    fn clone(&self) -> Self {
+        SharedIndexList {
+            inner: Rc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> From<IndexList<T>> for SharedIndexList<T> {
+    fn from(list: IndexList<T>) -> Self {
+        SharedIndexList {
+            inner: Rc::new(Inner {
+                list: RefCell::new(list),
+                pending: RefCell::new(Vec::new()),
+            }),
+        }
+    }
+}
+
+impl<T> SharedIndexList<T> {
+    /// Creates a new, empty `SharedIndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::shared::SharedIndexList;
+    /// let list: SharedIndexList<i32> = SharedIndexList::new();
+    /// ```
+    pub fn new() -> Self {
+        IndexList::new().into()
+    }
+
+    /// Immutably borrows the underlying `IndexList`.
+    ///
+    /// Elements whose guards were dropped while the list was borrowed may still be in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list is currently mutably borrowed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::shared::SharedIndexList::new();
+    /// let _five = list.push_back_guarded(5);
+    /// assert!(list.borrow().contains(&5));
+    /// ```
+    pub fn borrow(&self) -> Ref<'_, IndexList<T>> {
+        self.inner.list.borrow()
+    }
+
+    /// Mutably borrows the underlying `IndexList`, after removing the elements whose guards
+    /// were dropped while the list was borrowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list is currently borrowed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::shared::SharedIndexList::new();
+    /// let five = list.push_back_guarded(5);
+    /// *list.borrow_mut().get_mut(five.index()).unwrap() = 6;
+    /// assert!(list.borrow().contains(&6));
+    /// ```
+    pub fn borrow_mut(&self) -> RefMut<'_, IndexList<T>> {
+        self.remove_pending();
+        self.inner.list.borrow_mut()
+    }
+
+    /// Adds an element to the back of the list and returns a guard that removes it again
+    /// when dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list is currently borrowed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::shared::SharedIndexList::new();
+    /// let five = list.push_back_guarded(5);
+    /// drop(five);
+    /// assert!(list.borrow().is_empty());
+    /// ```
+    pub fn push_back_guarded(&self, item: T) -> IndexGuard<T> {
+        let index = self.borrow_mut().push_back(item);
+        self.guard(index)
+    }
+
+    /// Adds an element to the front of the list and returns a guard that removes it again
+    /// when dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list is currently borrowed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::shared::SharedIndexList::new();
+    /// let _five = list.push_back_guarded(5);
+    /// let four = list.push_front_guarded(4);
+    /// assert_eq!(list.borrow().head_index(), Some(four.index()));
+    /// ```
+    pub fn push_front_guarded(&self, item: T) -> IndexGuard<T> {
+        let index = self.borrow_mut().push_front(item);
+        self.guard(index)
+    }
+
+    fn guard(&self, index: Index<T>) -> IndexGuard<T> {
+        IndexGuard {
+            list: Rc::downgrade(&self.inner),
+            index,
+        }
+    }
+
+    /// Carries out the queued removals. The removed elements may hold guards into the same
+    /// list, so they are dropped after the borrow, when those guards can remove theirs.
+    fn remove_pending(&self) {
+        let pending = self.inner.pending.take();
+        if pending.is_empty() {
+            return;
+        }
+        let items: Vec<T> = {
+            let mut list = self.inner.list.borrow_mut();
+            pending
+                .into_iter()
+                .filter_map(|index| list.remove(index))
+                .collect()
+        };
+        drop(items);
+    }
+}
+
+/// A guard that removes an element from a [`SharedIndexList`] when it is dropped.
+///
+/// The guard does not keep the list alive. Dropping it after the list is gone, or after
+/// the element was already removed by other means, does nothing. Dropping it while the list
+/// is borrowed queues the removal for the next [`SharedIndexList::borrow_mut`].
+///
+/// The removed element is dropped after the list is released, so elements may hold guards
+/// into the same list.
+pub struct IndexGuard<T> {
+    list: Weak<Inner<T>>,
+    index: Index<T>,
+}
+
+impl<T> IndexGuard<T> {
+    /// Returns the index of the guarded element.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::shared::SharedIndexList::new();
+    /// let five = list.push_back_guarded(5);
+    /// assert_eq!(list.borrow().get(five.index()), Some(&5));
+    /// ```
+    pub fn index(&self) -> Index<T> {
+        self.index
+    }
+
+    /// Removes the guarded element now and returns it, if it is still in the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list is currently borrowed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::shared::SharedIndexList::new();
+    /// let five = list.push_back_guarded(5);
+    /// assert_eq!(five.remove(), Some(5));
+    /// ```
+    pub fn remove(self) -> Option<T> {
+        let list = self.list.upgrade()?;
+        // Bound first, so that the borrow ends before the caller drops the element.
+        let item = list.list.borrow_mut().remove(self.index);
+        item
+    }
+
+    /// Disarms the guard and returns the index of the element, which then stays in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list = indexlist::shared::SharedIndexList::new();
+    /// let five = list.push_back_guarded(5).into_index();
+    /// assert_eq!(list.borrow().get(five), Some(&5));
+    /// ```
+    pub fn into_index(mut self) -> Index<T> {
+        self.list = Weak::new();
+        self.index
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for IndexGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndexGuard")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for IndexGuard<T> {
+    fn drop(&mut self) {
+        let Some(inner) = self.list.upgrade() else {
+            return;
+        };
+        // The element may hold guards into the same list, so it is dropped after the borrow.
+        let item = match inner.list.try_borrow_mut() {
+            Ok(mut list) => list.remove(self.index),
+            Err(_) => {
+                inner.pending.borrow_mut().push(self.index);
+                None
+            }
+        };
+        drop(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guards_remove_their_element_on_drop() {
+        let list = SharedIndexList::new();
+        let one = list.push_back_guarded(1);
+        let two = list.push_back_guarded(2);
+        let zero = list.push_front_guarded(0);
+        assert_eq!(
+            list.borrow().iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+
+        drop(one);
+        assert_eq!(
+            list.borrow().iter().copied().collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(two.remove(), Some(2));
+        let zero = zero.into_index();
+        assert_eq!(list.borrow().get(zero), Some(&0));
+        assert_eq!(list.borrow().validate(), Ok(()));
+    }
+
+    #[test]
+    fn guards_outliving_the_list_or_element_do_nothing() {
+        let list = SharedIndexList::new();
+        let one = list.push_back_guarded(1);
+        list.borrow_mut().remove(one.index());
+        let reused = list.push_back_guarded(2);
+        drop(one);
+        assert_eq!(list.borrow().get(reused.index()), Some(&2));
+
+        drop(list);
+        assert_eq!(reused.remove(), None);
+    }
+
+    #[test]
+    fn elements_can_hold_guards_into_the_same_list() {
+        struct Node {
+            _child: Option<IndexGuard<Node>>,
+        }
+
+        let list = SharedIndexList::new();
+        let child = list.push_back_guarded(Node { _child: None });
+        let parent = list.push_back_guarded(Node {
+            _child: Some(child),
+        });
+        let other = list.push_back_guarded(Node { _child: None });
+        drop(parent);
+        assert_eq!(list.borrow().len(), 1);
+
+        let borrowed = list.borrow();
+        drop(other);
+        assert_eq!(borrowed.len(), 1);
+        drop(borrowed);
+
+        let child = list.push_back_guarded(Node { _child: None });
+        let parent = list.push_back_guarded(Node {
+            _child: Some(child),
+        });
+        assert_eq!(list.borrow().len(), 2);
+        assert!(parent.remove().is_some());
+        assert!(list.borrow().is_empty());
+    }
+
+    #[test]
+    fn guards_dropped_while_borrowed_remove_their_element_later() {
+        let list = SharedIndexList::new();
+        let one = list.push_back_guarded(1);
+        let two = list.push_back_guarded(2);
+
+        let borrowed = list.borrow();
+        drop(one);
+        drop(borrowed);
+        assert_eq!(list.borrow().len(), 2);
+        assert_eq!(list.borrow_mut().len(), 1);
+
+        let mut borrowed = list.borrow_mut();
+        drop(two);
+        borrowed.push_back(3);
+        drop(borrowed);
+        let four = list.push_front_guarded(4);
+        assert_eq!(
+            list.borrow().iter().copied().collect::<Vec<_>>(),
+            vec![4, 3]
+        );
+        assert_eq!(four.remove(), Some(4));
+    }
+}
@@ -0,0 +1,453 @@
+//! An `IndexList` that keeps up to `N` elements inline before moving them to the heap.
+
+use std::fmt;
+
+use crate::fixed::FixedIndexList;
+use crate::{Index, IndexList};
+
+/// `SmallIndexList` is a doubly linked list that stores up to `N` slots inline, like a
+/// [`FixedIndexList`], and moves to a heap-backed [`IndexList`] once it needs more.
+///
+/// Moving to the heap keeps the layout of the slots, so indices handed out before it keep
+/// referring to the same elements, and stale indices stay stale. The list never moves back
+/// inline.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::small::SmallIndexList;
+///
+/// let mut list: SmallIndexList<i32, 2> = SmallIndexList::new();
+/// let one = list.push_back(1);
+/// list.push_back(2);
+/// assert!(list.is_inline());
+///
+/// list.push_back(3);
+/// assert!(!list.is_inline());
+/// assert_eq!(list.get(one), Some(&1));
+/// ```
+pub struct SmallIndexList<T, const N: usize> {
+    repr: Repr<T, N>,
+}
+
+enum Repr<T, const N: usize> {
+    Inline(FixedIndexList<T, N>),
+    Spilled(IndexList<T>),
+}
+
+impl<T, const N: usize> Default for SmallIndexList<T, N> {
+    fn default() -> Self {
+        SmallIndexList {
+            repr: Repr::Inline(FixedIndexList::new()),
+        }
+    }
+}
+
+impl<T, const N: usize> SmallIndexList<T, N> {
+    /// Creates a new, empty `SmallIndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::small::SmallIndexList;
+    /// let list: SmallIndexList<i32, 8> = SmallIndexList::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the elements are still stored inline.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 1> = Default::default();
+    /// list.push_back(1);
+    /// assert!(list.is_inline());
+    /// list.push_back(2);
+    /// assert!(!list.is_inline());
+    /// ```
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline(_))
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(list) => list.len(),
+            Repr::Spilled(list) => list.len(),
+        }
+    }
+
+    /// Returns `true` if the list contains no elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends an element to the back of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn push_back(&mut self, item: T) -> Index<T> {
+        if let Repr::Inline(list) = &mut self.repr {
+            match list.push_back(item) {
+                Ok(index) => return index,
+                Err(item) => return self.spill().push_back(item),
+            }
+        }
+        self.spill().push_back(item)
+    }
+
+    /// Adds an element to the front of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// list.push_front(4);
+    /// assert_eq!(list.head(), Some(&4));
+    /// ```
+    pub fn push_front(&mut self, item: T) -> Index<T> {
+        if let Repr::Inline(list) = &mut self.repr {
+            match list.push_front(item) {
+                Ok(index) => return index,
+                Err(item) => return self.spill().push_front(item),
+            }
+        }
+        self.spill().push_front(item)
+    }
+
+    /// Inserts an element right after the specified index and returns its index, or `None`
+    /// if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let one = list.push_back(1);
+    /// list.push_back(3);
+    /// list.insert_after(one, 2);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn insert_after(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        if let Repr::Inline(list) = &mut self.repr {
+            match list.insert_after(index, item) {
+                Ok(index) => return Some(index),
+                Err(item) => return self.spill().insert_after(index, item),
+            }
+        }
+        self.spill().insert_after(index, item)
+    }
+
+    /// Inserts an element right before the specified index and returns its index, or `None`
+    /// if the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(1);
+    /// let three = list.push_back(3);
+    /// list.insert_before(three, 2);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn insert_before(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        if !self.contains_index(index) {
+            return None;
+        }
+        if let Repr::Inline(list) = &mut self.repr {
+            match list.insert_before(index, item) {
+                Ok(index) => return Some(index),
+                Err(item) => return self.spill().insert_before(index, item),
+            }
+        }
+        self.spill().insert_before(index, item)
+    }
+
+    /// Removes the first element of the list and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// assert_eq!(list.pop_front(), Some(5));
+    /// assert_eq!(list.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline(list) => list.pop_front(),
+            Repr::Spilled(list) => list.pop_front(),
+        }
+    }
+
+    /// Removes the last element of the list and returns it, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// assert_eq!(list.pop_back(), Some(6));
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline(list) => list.pop_back(),
+            Repr::Spilled(list) => list.pop_back(),
+        }
+    }
+
+    /// Removes the element at the given index and returns it, if it still exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.remove(five), Some(5));
+    /// assert_eq!(list.remove(five), None);
+    /// ```
+    pub fn remove(&mut self, index: Index<T>) -> Option<T> {
+        match &mut self.repr {
+            Repr::Inline(list) => list.remove(index),
+            Repr::Spilled(list) => list.remove(index),
+        }
+    }
+
+    /// Returns `true` if the index refers to an element of the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert!(list.contains_index(five));
+    /// ```
+    pub fn contains_index(&self, index: Index<T>) -> bool {
+        match &self.repr {
+            Repr::Inline(list) => list.contains_index(index),
+            Repr::Spilled(list) => list.contains_index(index),
+        }
+    }
+
+    /// Returns a reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn get(&self, index: Index<T>) -> Option<&T> {
+        match &self.repr {
+            Repr::Inline(list) => list.get(index),
+            Repr::Spilled(list) => list.get(index),
+        }
+    }
+
+    /// Returns a mutable reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// *list.get_mut(five).unwrap() = 6;
+    /// assert_eq!(list.get(five), Some(&6));
+    /// ```
+    pub fn get_mut(&mut self, index: Index<T>) -> Option<&mut T> {
+        match &mut self.repr {
+            Repr::Inline(list) => list.get_mut(index),
+            Repr::Spilled(list) => list.get_mut(index),
+        }
+    }
+
+    /// Returns a reference to the first element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// assert_eq!(list.head(), Some(&5));
+    /// ```
+    pub fn head(&self) -> Option<&T> {
+        match &self.repr {
+            Repr::Inline(list) => list.head(),
+            Repr::Spilled(list) => list.head(),
+        }
+    }
+
+    /// Returns a reference to the last element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// assert_eq!(list.tail(), Some(&6));
+    /// ```
+    pub fn tail(&self) -> Option<&T> {
+        match &self.repr {
+            Repr::Inline(list) => list.tail(),
+            Repr::Spilled(list) => list.tail(),
+        }
+    }
+
+    /// Returns the index of the first element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.head_index(), Some(five));
+    /// ```
+    pub fn head_index(&self) -> Option<Index<T>> {
+        match &self.repr {
+            Repr::Inline(list) => list.head_index(),
+            Repr::Spilled(list) => list.head_index(),
+        }
+    }
+
+    /// Returns the index of the last element in the list, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// let six = list.push_back(6);
+    /// assert_eq!(list.tail_index(), Some(six));
+    /// ```
+    pub fn tail_index(&self) -> Option<Index<T>> {
+        match &self.repr {
+            Repr::Inline(list) => list.tail_index(),
+            Repr::Spilled(list) => list.tail_index(),
+        }
+    }
+
+    /// Returns the index of the element after the given one, or `None` if it is the last
+    /// element or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// let six = list.push_back(6);
+    /// assert_eq!(list.next_index(five), Some(six));
+    /// ```
+    pub fn next_index(&self, index: Index<T>) -> Option<Index<T>> {
+        match &self.repr {
+            Repr::Inline(list) => list.next_index(index),
+            Repr::Spilled(list) => list.next_index(index),
+        }
+    }
+
+    /// Returns the index of the element before the given one, or `None` if it is the first
+    /// element or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// let five = list.push_back(5);
+    /// let six = list.push_back(6);
+    /// assert_eq!(list.prev_index(six), Some(five));
+    /// ```
+    pub fn prev_index(&self, index: Index<T>) -> Option<Index<T>> {
+        match &self.repr {
+            Repr::Inline(list) => list.prev_index(index),
+            Repr::Spilled(list) => list.prev_index(index),
+        }
+    }
+
+    /// Returns an iterator over the elements in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::small::SmallIndexList<i32, 8> = Default::default();
+    /// list.push_back(5);
+    /// list.push_front(4);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![4, 5]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        let (inline, spilled) = match &self.repr {
+            Repr::Inline(list) => (Some(list.iter()), None),
+            Repr::Spilled(list) => (None, Some(list.iter())),
+        };
+        inline
+            .into_iter()
+            .flatten()
+            .chain(spilled.into_iter().flatten())
+    }
+
+    /// Moves the elements to the heap, if they are not there yet, and returns the list that
+    /// holds them.
+    fn spill(&mut self) -> &mut IndexList<T> {
+        if let Repr::Inline(list) = &mut self.repr {
+            let list = std::mem::take(list);
+            self.repr = Repr::Spilled(list.into_list());
+        }
+        match &mut self.repr {
+            Repr::Spilled(list) => list,
+            Repr::Inline(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallIndexList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spilling_keeps_indices() {
+        let mut list: SmallIndexList<i32, 3> = SmallIndexList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        list.remove(two);
+        let zero = list.push_front(0);
+        assert!(list.is_inline());
+
+        let between = list.insert_after(one, 2).unwrap();
+        assert!(!list.is_inline());
+        assert_eq!(list.get(zero), Some(&0));
+        assert_eq!(list.get(three), Some(&3));
+        assert_eq!(list.get(two), None);
+        assert_eq!(list.next_index(one), Some(between));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+
+        list.push_back(4);
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.len(), 4);
+        match &list.repr {
+            Repr::Spilled(list) => assert_eq!(list.validate(), Ok(())),
+            Repr::Inline(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn stale_index_is_rejected_without_spilling() {
+        let mut list: SmallIndexList<i32, 1> = SmallIndexList::new();
+        let one = list.push_back(1);
+        list.remove(one);
+        list.push_back(2);
+        assert_eq!(list.insert_before(one, 0), None);
+        assert!(list.is_inline());
+    }
+}
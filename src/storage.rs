@@ -0,0 +1,125 @@
+//! The slots the entries of a list live in, shared by the growable storage of
+//! [`IndexList`](crate::IndexList) and the inline array of
+//! [`FixedIndexList`](crate::fixed::FixedIndexList).
+
+use crate::alloc::{Allocator, Vec};
+use crate::Entry::{self, Free, Occupied};
+use crate::{Index, IndexType, ListId, OccupiedEntry, Slot};
+
+/// Slots of entries that elements are stored in and linked through.
+///
+/// The ends of the list and the head of the free list belong to the list, so they are
+/// passed in where they change.
+pub(crate) trait Storage<T, I: IndexType> {
+    fn entries(&self) -> &[Entry<T, I>];
+
+    fn entries_mut(&mut self) -> &mut [Entry<T, I>];
+
+    /// Adds a free slot that is on no free list and returns it, or `None` if the storage
+    /// cannot grow.
+    fn grow(&mut self) -> Option<usize>;
+
+    fn occupied(&self, slot: usize) -> &OccupiedEntry<T, I> {
+        match &self.entries()[slot] {
+            Occupied(oc) => oc,
+            _ => panic!("Corrupted list"),
+        }
+    }
+
+    fn occupied_mut(&mut self, slot: usize) -> &mut OccupiedEntry<T, I> {
+        match &mut self.entries_mut()[slot] {
+            Occupied(oc) => oc,
+            _ => panic!("Corrupted list"),
+        }
+    }
+
+    fn index_at(&self, slot: usize, list: ListId) -> Index<T, I> {
+        Index::new(slot, self.occupied(slot).generation, list)
+    }
+
+    /// Stores `item` in the first slot of the free list starting at `next_free`, or a new
+    /// one, without linking it into the list. Gives the element back if there is no slot
+    /// for it.
+    fn allocate(
+        &mut self,
+        next_free: &mut Option<usize>,
+        item: T,
+        generation: I,
+    ) -> Result<usize, T> {
+        let slot = match *next_free {
+            Some(slot) => {
+                let Free { next_free: after } = self.entries()[slot] else {
+                    panic!("Corrupted list");
+                };
+                *next_free = after.map(Slot::get);
+                slot
+            }
+            None => match self.grow() {
+                Some(slot) => slot,
+                None => return Err(item),
+            },
+        };
+        self.entries_mut()[slot] = Occupied(OccupiedEntry {
+            item,
+            generation,
+            next: None,
+            prev: None,
+        });
+        Ok(slot)
+    }
+
+    /// Links the unlinked `slot` into the list right before `before`.
+    fn link_before(&mut self, head: &mut Option<usize>, before: usize, slot: usize) {
+        let prev = self.occupied(before).prev();
+        let oc = self.occupied_mut(slot);
+        oc.set_prev(prev);
+        oc.set_next(Some(before));
+        self.occupied_mut(before).set_prev(Some(slot));
+        match prev {
+            Some(prev) => self.occupied_mut(prev).set_next(Some(slot)),
+            None => *head = Some(slot),
+        }
+    }
+
+    /// Links the unlinked `slot` into the list right after `after`.
+    fn link_after(&mut self, tail: &mut Option<usize>, after: usize, slot: usize) {
+        let next = self.occupied(after).next();
+        let oc = self.occupied_mut(slot);
+        oc.set_prev(Some(after));
+        oc.set_next(next);
+        self.occupied_mut(after).set_next(Some(slot));
+        match next {
+            Some(next) => self.occupied_mut(next).set_prev(Some(slot)),
+            None => *tail = Some(slot),
+        }
+    }
+}
+
+impl<T, I: IndexType, A: Allocator> Storage<T, I> for Vec<Entry<T, I>, A> {
+    fn entries(&self) -> &[Entry<T, I>] {
+        self
+    }
+
+    fn entries_mut(&mut self) -> &mut [Entry<T, I>] {
+        self
+    }
+
+    fn grow(&mut self) -> Option<usize> {
+        self.push(Free { next_free: None });
+        Some(self.len() - 1)
+    }
+}
+
+impl<T, I: IndexType, const N: usize> Storage<T, I> for [Entry<T, I>; N] {
+    fn entries(&self) -> &[Entry<T, I>] {
+        self
+    }
+
+    fn entries_mut(&mut self) -> &mut [Entry<T, I>] {
+        self
+    }
+
+    fn grow(&mut self) -> Option<usize> {
+        None
+    }
+}
@@ -0,0 +1,246 @@
+//! Model-based testing of `IndexList`, and of types built on it.
+//!
+//! An [`Op`] is a list operation that can be applied both to an `IndexList` and to a
+//! [`Model`], a plain `Vec` that is obviously correct. [`apply_and_check`] runs a sequence of
+//! operations against both and panics as soon as they disagree or the list is found
+//! corrupted. Wrappers around `IndexList` can be tested the same way by applying the
+//! operations to the wrapper and comparing it with a `Model` step by step.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use indexlist::test_util::{apply_and_check, Op};
+//!
+//! let list = apply_and_check(vec![
+//!     Op::PushBack(1),
+//!     Op::PushFront(0),
+//!     Op::InsertAfter(1, 2),
+//!     Op::Remove(0),
+//!     Op::Reverse,
+//! ]);
+//! assert_eq!(list.to_string(), "[2, 1]");
+//! ```
+
+use std::fmt::Debug;
+
+use crate::IndexList;
+
+/// A single list operation, applied with [`Op::apply`] to an `IndexList` and with
+/// [`Model::apply`] to a `Model`.
+///
+/// Elements are chosen by their position in the list, taken modulo the length of the list,
+/// so that any sequence of operations is meaningful. On an empty list, insertions relative
+/// to an element push to the back and removals of an element do nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<T> {
+    /// Appends an element with [`IndexList::push_back`].
+    PushBack(T),
+    /// Prepends an element with [`IndexList::push_front`].
+    PushFront(T),
+    /// Inserts an element with [`IndexList::insert_before`] the element at the position.
+    InsertBefore(usize, T),
+    /// Inserts an element with [`IndexList::insert_after`] the element at the position.
+    InsertAfter(usize, T),
+    /// Replaces the element at the position with [`IndexList::replace`].
+    Replace(usize, T),
+    /// Removes the element at the position with [`IndexList::remove`].
+    Remove(usize),
+    /// Removes the first element with [`IndexList::pop_front`].
+    PopFront,
+    /// Removes the last element with [`IndexList::pop_back`].
+    PopBack,
+    /// Reverses the list with [`IndexList::reverse`].
+    Reverse,
+    /// Shortens the list with [`IndexList::truncate`].
+    Truncate(usize),
+}
+
+impl<T> Op<T> {
+    /// Applies the operation to `list` and returns the element it removed or replaced, if
+    /// any.
+    pub fn apply(self, list: &mut IndexList<T>) -> Option<T> {
+        let len = list.len();
+        let at = |list: &IndexList<T>, position: usize| {
+            list.get_by_position(position % len).map(|(index, _)| index)
+        };
+        match self {
+            Op::PushBack(item) => {
+                list.push_back(item);
+                None
+            }
+            Op::PushFront(item) => {
+                list.push_front(item);
+                None
+            }
+            Op::InsertBefore(_, item) | Op::InsertAfter(_, item) if len == 0 => {
+                list.push_back(item);
+                None
+            }
+            Op::InsertBefore(position, item) => {
+                let index = at(list, position).expect("position is in bounds");
+                list.insert_before(index, item);
+                None
+            }
+            Op::InsertAfter(position, item) => {
+                let index = at(list, position).expect("position is in bounds");
+                list.insert_after(index, item);
+                None
+            }
+            Op::Replace(_, _) | Op::Remove(_) if len == 0 => None,
+            Op::Replace(position, item) => {
+                let index = at(list, position).expect("position is in bounds");
+                list.replace(index, item)
+            }
+            Op::Remove(position) => {
+                let index = at(list, position).expect("position is in bounds");
+                list.remove(index)
+            }
+            Op::PopFront => list.pop_front(),
+            Op::PopBack => list.pop_back(),
+            Op::Reverse => {
+                list.reverse();
+                None
+            }
+            Op::Truncate(len) => {
+                list.truncate(len);
+                None
+            }
+        }
+    }
+}
+
+/// The reference implementation of a list that [`Op`]s are checked against, a `Vec` of the
+/// elements in list order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model<T> {
+    items: Vec<T>,
+}
+
+impl<T> Default for Model<T> {
+    fn default() -> Self {
+        Model { items: Vec::new() }
+    }
+}
+
+impl<T> Model<T> {
+    /// Creates an empty model.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the elements of the model in list order.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Applies the operation to the model and returns the element it removed or replaced,
+    /// if any, exactly like [`Op::apply`] does for an `IndexList`.
+    pub fn apply(&mut self, op: Op<T>) -> Option<T> {
+        let len = self.items.len();
+        match op {
+            Op::PushBack(item) => self.items.push(item),
+            Op::PushFront(item) => self.items.insert(0, item),
+            Op::InsertBefore(_, item) | Op::InsertAfter(_, item) if len == 0 => {
+                self.items.push(item)
+            }
+            Op::InsertBefore(position, item) => self.items.insert(position % len, item),
+            Op::InsertAfter(position, item) => self.items.insert(position % len + 1, item),
+            Op::Replace(_, _) | Op::Remove(_) if len == 0 => {}
+            Op::Replace(position, item) => {
+                return Some(std::mem::replace(&mut self.items[position % len], item));
+            }
+            Op::Remove(position) => return Some(self.items.remove(position % len)),
+            Op::PopFront if len > 0 => return Some(self.items.remove(0)),
+            Op::PopBack => return self.items.pop(),
+            Op::PopFront => {}
+            Op::Reverse => self.items.reverse(),
+            Op::Truncate(len) => self.items.truncate(len),
+        }
+        None
+    }
+}
+
+/// Panics if `list` is corrupted or does not hold the same elements as `model`.
+pub fn check_against_model<T: PartialEq + Debug>(list: &IndexList<T>, model: &Model<T>) {
+    if let Err(error) = list.validate() {
+        panic!("Corrupted list: {error}");
+    }
+    assert_eq!(
+        list.iter().collect::<Vec<_>>(),
+        model.items().iter().collect::<Vec<_>>(),
+        "the list differs from the model"
+    );
+}
+
+/// Applies `ops` in order to a new `IndexList` and a new [`Model`] and returns the list.
+///
+/// After every operation, the elements the two returned must be equal, the list must
+/// validate and hold the same elements as the model, otherwise this panics with the number
+/// of the operation that went wrong.
+pub fn apply_and_check<T: Clone + PartialEq + Debug>(
+    ops: impl IntoIterator<Item = Op<T>>,
+) -> IndexList<T> {
+    let mut list = IndexList::new();
+    let mut model = Model::new();
+    for (step, op) in ops.into_iter().enumerate() {
+        let description = format!("operation {step}, {op:?}");
+        let expected = model.apply(op.clone());
+        let actual = op.apply(&mut list);
+        assert_eq!(
+            actual, expected,
+            "{description} returned a different element"
+        );
+        if let Err(error) = list.validate() {
+            panic!("{description} corrupted the list: {error}");
+        }
+        assert_eq!(
+            list.iter().collect::<Vec<_>>(),
+            model.items().iter().collect::<Vec<_>>(),
+            "{description} made the list differ from the model"
+        );
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudo_random_histories_match_the_model() {
+        let mut state = 0x2545_f491_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for _ in 0..20 {
+            let ops: Vec<Op<usize>> = (0..200)
+                .map(|item| match next() % 10 {
+                    0 => Op::PushBack(item),
+                    1 => Op::PushFront(item),
+                    2 => Op::InsertBefore(next(), item),
+                    3 => Op::InsertAfter(next(), item),
+                    4 => Op::Replace(next(), item),
+                    5 => Op::Remove(next()),
+                    6 => Op::PopFront,
+                    7 => Op::PopBack,
+                    8 => Op::Reverse,
+                    _ => Op::Truncate(next() % 64),
+                })
+                .collect();
+            apply_and_check(ops);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "differs from the model")]
+    fn a_diverging_list_is_caught() {
+        let mut list = IndexList::new();
+        let mut model = Model::new();
+        Op::PushBack(1).apply(&mut list);
+        model.apply(Op::PushFront(2));
+        check_against_model(&list, &model);
+    }
+}
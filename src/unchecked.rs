@@ -0,0 +1,90 @@
+//! Element access without checking the index, for hot loops where the caller already knows
+//! that the index is valid.
+
+// Skipping the checks is only sound if the caller upholds them, so these are `unsafe`.
+#![allow(unsafe_code)]
+
+use crate::Entry::Occupied;
+use crate::{Allocator, Index, IndexList, IndexType};
+
+impl<T, I: IndexType, A: Allocator + Clone> IndexList<T, I, A> {
+    /// Returns a reference to the element at the given index, without checking that the
+    /// index is valid.
+    ///
+    /// Debug builds still check the index and panic if it is not valid.
+    ///
+    /// # Safety
+    ///
+    /// `index` must refer to an element that is currently in this list, that is,
+    /// [`IndexList::contains_index`] must return `true` for it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// assert_eq!(unsafe { list.get_unchecked(five) }, &5);
+    /// ```
+    pub unsafe fn get_unchecked(&self, index: Index<T, I>) -> &T {
+        debug_assert!(self.contains_index(index), "invalid index into IndexList");
+        // SAFETY: the caller guarantees that the slot is in bounds and occupied.
+        match unsafe { self.contents.get_unchecked(index.slot()) } {
+            Occupied(oc) => &oc.item,
+            _ => unsafe { std::hint::unreachable_unchecked() },
+        }
+    }
+
+    /// Returns a mutable reference to the element at the given index, without checking that
+    /// the index is valid.
+    ///
+    /// Debug builds still check the index and panic if it is not valid.
+    ///
+    /// # Safety
+    ///
+    /// `index` must refer to an element that is currently in this list, that is,
+    /// [`IndexList::contains_index`] must return `true` for it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list = indexlist::IndexList::new();
+    /// let five = list.push_back(5);
+    /// unsafe { *list.get_unchecked_mut(five) = 10 };
+    /// assert_eq!(list.get(five), Some(&10));
+    /// ```
+    pub unsafe fn get_unchecked_mut(&mut self, index: Index<T, I>) -> &mut T {
+        debug_assert!(self.contains_index(index), "invalid index into IndexList");
+        // SAFETY: the caller guarantees that the slot is in bounds and occupied.
+        match unsafe { self.contents.get_unchecked_mut(index.slot()) } {
+            Occupied(oc) => &mut oc.item,
+            _ => unsafe { std::hint::unreachable_unchecked() },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchecked_access() {
+        let mut list = IndexList::new();
+        let indices: Vec<_> = (0..4).map(|item| list.push_back(item)).collect();
+        for &index in &indices {
+            unsafe { *list.get_unchecked_mut(index) *= 10 };
+        }
+        let items: Vec<_> = indices
+            .iter()
+            .map(|&index| unsafe { *list.get_unchecked(index) })
+            .collect();
+        assert_eq!(items, vec![0, 10, 20, 30]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "invalid index into IndexList")]
+    fn unchecked_access_is_checked_in_debug_builds() {
+        let mut list = IndexList::new();
+        let five = list.push_back(5);
+        list.remove(five);
+        unsafe { list.get_unchecked(five) };
+    }
+}
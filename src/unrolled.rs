@@ -0,0 +1,552 @@
+//! An unrolled `IndexList`, which stores several elements per node for fast iteration.
+
+use std::fmt;
+
+use crate::{Index, IndexList, ListId};
+
+/// The elements of a node, in list order, each with the slot of its location.
+type Node<T> = Vec<(T, usize)>;
+
+/// Where the element behind an `Index` currently is.
+enum Location<T> {
+    Free {
+        next_free: Option<usize>,
+    },
+    Live {
+        generation: usize,
+        node: Index<Node<T>>,
+        offset: usize,
+    },
+}
+
+/// `UnrolledIndexList` is a doubly linked list of nodes that each hold up to `B` elements
+/// next to each other, so iterating over it scans memory mostly linearly instead of
+/// following a link per element.
+///
+/// It hands out the same generational [`Index`] as [`IndexList`] and follows the same rules:
+/// removing an element moves the list to a new generation, so an index of a removed element
+/// never refers to a newer element. Indices stay valid while elements move between nodes.
+///
+/// Inserting into a full node splits it in two, and removing elements merges a node with
+/// its neighbor once both fit into half a node, so insertions and removals take O(B) time.
+///
+/// # Examples
+///
+/// ```rust
+/// use indexlist::unrolled::UnrolledIndexList;
+///
+/// let mut list: UnrolledIndexList<i32, 4> = UnrolledIndexList::new();
+/// let indices: Vec<_> = (0..10).map(|item| list.push_back(item)).collect();
+/// list.insert_after(indices[1], 100);
+/// assert_eq!(list.remove(indices[5]), Some(5));
+///
+/// assert_eq!(list.get(indices[9]), Some(&9));
+/// assert_eq!(list.get(indices[5]), None);
+/// assert_eq!(
+///     list.iter().copied().collect::<Vec<_>>(),
+///     vec![0, 1, 100, 2, 3, 4, 6, 7, 8, 9]
+/// );
+/// ```
+pub struct UnrolledIndexList<T, const B: usize = 32> {
+    id: ListId,
+    nodes: IndexList<Node<T>>,
+    locations: Vec<Location<T>>,
+    generation: usize,
+    next_free: Option<usize>,
+    len: usize,
+}
+
+impl<T, const B: usize> Default for UnrolledIndexList<T, B> {
+    fn default() -> Self {
+        const { assert!(B >= 2, "a node must hold at least two elements") };
+        UnrolledIndexList {
+            id: ListId::next(),
+            nodes: IndexList::new(),
+            locations: Vec::new(),
+            generation: 0,
+            next_free: None,
+            len: 0,
+        }
+    }
+}
+
+impl<T, const B: usize> UnrolledIndexList<T, B> {
+    /// Creates a new, empty `UnrolledIndexList`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use indexlist::unrolled::UnrolledIndexList;
+    /// let list: UnrolledIndexList<i32> = UnrolledIndexList::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of elements in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// list.push_back(5);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list contains no elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the index refers to an element that is still in the list.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// list.remove(five);
+    /// assert!(!list.contains_index(five));
+    /// ```
+    pub fn contains_index(&self, index: Index<T>) -> bool {
+        self.locate(index).is_some()
+    }
+
+    /// Returns a reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn get(&self, index: Index<T>) -> Option<&T> {
+        let (node, offset) = self.locate(index)?;
+        Some(&self.nodes[node][offset].0)
+    }
+
+    /// Returns a mutable reference to the element at the given index, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// *list.get_mut(five).unwrap() = 10;
+    /// assert_eq!(list.get(five), Some(&10));
+    /// ```
+    pub fn get_mut(&mut self, index: Index<T>) -> Option<&mut T> {
+        let (node, offset) = self.locate(index)?;
+        Some(&mut self.nodes[node][offset].0)
+    }
+
+    /// Returns the index of the first element, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// list.push_back(10);
+    /// assert_eq!(list.head_index(), Some(five));
+    /// ```
+    pub fn head_index(&self) -> Option<Index<T>> {
+        let &(_, slot) = self.nodes.head()?.first()?;
+        Some(self.index_at(slot))
+    }
+
+    /// Returns the index of the last element, or `None` if the list is empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.tail_index(), Some(ten));
+    /// ```
+    pub fn tail_index(&self) -> Option<Index<T>> {
+        let &(_, slot) = self.nodes.tail()?.last()?;
+        Some(self.index_at(slot))
+    }
+
+    /// Returns the next index after the given one, or `None` if it is the last element or
+    /// the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.next_index(five), Some(ten));
+    /// assert_eq!(list.next_index(ten), None);
+    /// ```
+    pub fn next_index(&self, index: Index<T>) -> Option<Index<T>> {
+        let (node, offset) = self.locate(index)?;
+        let &(_, slot) = match self.nodes[node].get(offset + 1) {
+            Some(next) => next,
+            None => self.nodes[self.nodes.next_index(node)?].first()?,
+        };
+        Some(self.index_at(slot))
+    }
+
+    /// Returns the previous index before the given one, or `None` if it is the first element
+    /// or the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// let ten = list.push_back(10);
+    /// assert_eq!(list.prev_index(ten), Some(five));
+    /// assert_eq!(list.prev_index(five), None);
+    /// ```
+    pub fn prev_index(&self, index: Index<T>) -> Option<Index<T>> {
+        let (node, offset) = self.locate(index)?;
+        let &(_, slot) = match offset.checked_sub(1) {
+            Some(prev) => &self.nodes[node][prev],
+            None => self.nodes[self.nodes.prev_index(node)?].last()?,
+        };
+        Some(self.index_at(slot))
+    }
+
+    /// Appends an element to the back of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.get(five), Some(&5));
+    /// ```
+    pub fn push_back(&mut self, item: T) -> Index<T> {
+        let node = match self.nodes.tail_index() {
+            Some(tail) if self.nodes[tail].len() < B => tail,
+            _ => self.nodes.push_back(Vec::with_capacity(B)),
+        };
+        let offset = self.nodes[node].len();
+        self.insert_at(node, offset, item)
+    }
+
+    /// Adds an element to the front of the list and returns its index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// list.push_back(10);
+    /// let five = list.push_front(5);
+    /// assert_eq!(list.head_index(), Some(five));
+    /// ```
+    pub fn push_front(&mut self, item: T) -> Index<T> {
+        let node = match self.nodes.head_index() {
+            Some(head) if self.nodes[head].len() < B => head,
+            _ => self.nodes.push_front(Vec::with_capacity(B)),
+        };
+        self.insert_at(node, 0, item)
+    }
+
+    /// Inserts an element before the specified index and returns its index, or `None` if
+    /// the index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let ten = list.push_back(10);
+    /// let five = list.insert_before(ten, 5).unwrap();
+    /// assert_eq!(list.next_index(five), Some(ten));
+    /// ```
+    pub fn insert_before(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        let (node, offset) = self.locate(index)?;
+        Some(self.insert_at(node, offset, item))
+    }
+
+    /// Inserts an element after the specified index and returns its index, or `None` if the
+    /// index is not valid.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// let ten = list.insert_after(five, 10).unwrap();
+    /// assert_eq!(list.prev_index(ten), Some(five));
+    /// ```
+    pub fn insert_after(&mut self, index: Index<T>, item: T) -> Option<Index<T>> {
+        let (node, offset) = self.locate(index)?;
+        Some(self.insert_at(node, offset + 1, item))
+    }
+
+    /// Removes the element at the given index and returns it, if it exists.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// let five = list.push_back(5);
+    /// assert_eq!(list.remove(five), Some(5));
+    /// assert_eq!(list.remove(five), None);
+    /// ```
+    pub fn remove(&mut self, index: Index<T>) -> Option<T> {
+        let (node, offset) = self.locate(index)?;
+        let (item, slot) = self.nodes[node].remove(offset);
+        self.relocate(node, offset);
+        self.free(slot);
+        self.generation = self.generation.saturating_add(1);
+        self.len -= 1;
+        if self.nodes[node].is_empty() {
+            self.nodes.remove(node);
+        } else if let Some(next) = self
+            .nodes
+            .next_index(node)
+            .filter(|&n| self.mergeable(node, n))
+        {
+            self.merge(node, next);
+        } else if let Some(prev) = self
+            .nodes
+            .prev_index(node)
+            .filter(|&p| self.mergeable(p, node))
+        {
+            self.merge(prev, node);
+        }
+        Some(item)
+    }
+
+    /// Removes the first element from the list and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// assert_eq!(list.pop_front(), Some(5));
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(self.head_index()?)
+    }
+
+    /// Removes the last element from the list and returns it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// list.push_back(5);
+    /// list.push_back(10);
+    /// assert_eq!(list.pop_back(), Some(10));
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.tail_index()?)
+    }
+
+    /// Returns an iterator over the elements in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// list.push_back(5);
+    /// list.push_front(4);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![4, 5]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.iter().map(|(item, _)| item))
+    }
+
+    /// Returns an iterator over mutable references to the elements in list order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut list: indexlist::unrolled::UnrolledIndexList<i32> = Default::default();
+    /// list.push_back(5);
+    /// list.iter_mut().for_each(|item| *item *= 2);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![10]);
+    /// ```
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.nodes
+            .iter_mut()
+            .flat_map(|node| node.iter_mut().map(|(item, _)| item))
+    }
+
+    /// Returns the node and offset of the element at the given index, if it exists.
+    fn locate(&self, index: Index<T>) -> Option<(Index<Node<T>>, usize)> {
+        if index.list != self.id {
+            return None;
+        }
+        match self.locations.get(index.slot())? {
+            &Location::Live {
+                generation,
+                node,
+                offset,
+            } if generation == index.generation => Some((node, offset)),
+            _ => None,
+        }
+    }
+
+    fn index_at(&self, slot: usize) -> Index<T> {
+        match self.locations[slot] {
+            Location::Live { generation, .. } => Index::new(slot, generation, self.id),
+            Location::Free { .. } => panic!("Corrupted list"),
+        }
+    }
+
+    /// Stores `item` at `offset` in `node`, splitting the node first if it is full.
+    fn insert_at(&mut self, mut node: Index<Node<T>>, mut offset: usize, item: T) -> Index<T> {
+        if self.nodes[node].len() == B {
+            let mut second = Vec::with_capacity(B);
+            second.extend(self.nodes[node].drain(B / 2..));
+            let new_node = self
+                .nodes
+                .insert_after(node, second)
+                .expect("Corrupted list");
+            self.relocate(new_node, 0);
+            if offset > B / 2 {
+                node = new_node;
+                offset -= B / 2;
+            }
+        }
+        let slot = match self.next_free {
+            Some(slot) => {
+                self.next_free = match self.locations[slot] {
+                    Location::Free { next_free } => next_free,
+                    Location::Live { .. } => panic!("Corrupted list"),
+                };
+                slot
+            }
+            None => {
+                self.locations.push(Location::Free { next_free: None });
+                self.locations.len() - 1
+            }
+        };
+        self.locations[slot] = Location::Live {
+            generation: self.generation,
+            node,
+            offset,
+        };
+        self.nodes[node].insert(offset, (item, slot));
+        self.relocate(node, offset);
+        self.len += 1;
+        Index::new(slot, self.generation, self.id)
+    }
+
+    /// Updates the locations of the elements of `node` from `from` on.
+    fn relocate(&mut self, node: Index<Node<T>>, from: usize) {
+        for (offset, &(_, slot)) in self.nodes[node].iter().enumerate().skip(from) {
+            if let Location::Live {
+                node: location_node,
+                offset: location_offset,
+                ..
+            } = &mut self.locations[slot]
+            {
+                *location_node = node;
+                *location_offset = offset;
+            }
+        }
+    }
+
+    fn free(&mut self, slot: usize) {
+        let Location::Live { generation, .. } = self.locations[slot] else {
+            panic!("Corrupted list");
+        };
+        // A reused slot would get the saturated generation again and revive stale indices.
+        if generation == usize::MAX {
+            self.locations[slot] = Location::Free { next_free: None };
+        } else {
+            self.locations[slot] = Location::Free {
+                next_free: self.next_free,
+            };
+            self.next_free = Some(slot);
+        }
+    }
+
+    /// Returns `true` if the adjacent nodes `first` and `second` together fit into half a
+    /// node.
+    fn mergeable(&self, first: Index<Node<T>>, second: Index<Node<T>>) -> bool {
+        self.nodes[first].len() + self.nodes[second].len() <= B / 2
+    }
+
+    /// Moves the elements of `second` to the end of the node before it, `first`.
+    fn merge(&mut self, first: Index<Node<T>>, second: Index<Node<T>>) {
+        let moved = self.nodes.remove(second).expect("Corrupted list");
+        let from = self.nodes[first].len();
+        self.nodes[first].extend(moved);
+        self.relocate(first, from);
+    }
+}
+
+impl<T: fmt::Debug, const B: usize> fmt::Debug for UnrolledIndexList<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_a_vec_through_splits_and_merges() {
+        let mut list: UnrolledIndexList<usize, 4> = UnrolledIndexList::new();
+        let mut model: Vec<(usize, Index<usize>)> = Vec::new();
+        let mut state = 0x9e37_79b9_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        for item in 0..2000 {
+            let position = if model.is_empty() {
+                0
+            } else {
+                next() % model.len()
+            };
+            match next() % 6 {
+                0 => model.push((item, list.push_back(item))),
+                1 => model.insert(0, (item, list.push_front(item))),
+                2 if !model.is_empty() => {
+                    let index = list.insert_before(model[position].1, item).unwrap();
+                    model.insert(position, (item, index));
+                }
+                3 if !model.is_empty() => {
+                    let index = list.insert_after(model[position].1, item).unwrap();
+                    model.insert(position + 1, (item, index));
+                }
+                _ if !model.is_empty() => {
+                    let (removed, index) = model.remove(position);
+                    assert_eq!(list.remove(index), Some(removed));
+                    assert_eq!(list.get(index), None);
+                }
+                _ => {}
+            }
+            assert_eq!(list.len(), model.len());
+        }
+        assert!(list.iter().eq(model.iter().map(|(item, _)| item)));
+        for (position, &(item, index)) in model.iter().enumerate() {
+            assert_eq!(list.get(index), Some(&item));
+            let next = model.get(position + 1).map(|&(_, index)| index);
+            assert_eq!(list.next_index(index), next);
+            let prev = position.checked_sub(1).map(|p| model[p].1);
+            assert_eq!(list.prev_index(index), prev);
+        }
+        assert!(list
+            .nodes
+            .iter()
+            .all(|node| !node.is_empty() && node.len() <= 4));
+    }
+
+    #[test]
+    fn stale_indices_are_rejected() {
+        let mut list: UnrolledIndexList<i32, 2> = UnrolledIndexList::new();
+        let one = list.push_back(1);
+        list.push_back(2);
+        list.remove(one);
+        let three = list.push_front(3);
+        assert_eq!(list.get(one), None);
+        assert_eq!(list.insert_after(one, 4), None);
+        assert_eq!(list.get(three), Some(&3));
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert!(list.is_empty());
+        assert_eq!(list.head_index(), None);
+    }
+}